@@ -0,0 +1,2 @@
+-- Add serialized transaction size for fee/efficiency analysis
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS transaction_size_bytes INTEGER;
@@ -0,0 +1,12 @@
+-- Create missed_transactions table for signatures that could not be fetched
+CREATE TABLE IF NOT EXISTS missed_transactions (
+    id BIGSERIAL PRIMARY KEY,
+    signature VARCHAR(88) NOT NULL UNIQUE,
+    reason VARCHAR(32) NOT NULL,
+    error_message TEXT,
+    recorded_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL
+);
+
+-- Create indexes for common query patterns
+CREATE INDEX IF NOT EXISTS idx_missed_transactions_reason ON missed_transactions(reason);
+CREATE INDEX IF NOT EXISTS idx_missed_transactions_recorded_at ON missed_transactions(recorded_at);
@@ -0,0 +1,2 @@
+-- Flag transactions that use a durable nonce instead of a recent blockhash
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS uses_durable_nonce BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,2 @@
+-- Precomputed net SOL change of the fee payer (lamports) for cheap PnL queries
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS fee_payer_sol_delta BIGINT NOT NULL DEFAULT 0;
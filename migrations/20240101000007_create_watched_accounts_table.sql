@@ -0,0 +1,8 @@
+-- Create watched_accounts table for a database-managed watchlist
+-- Used when TARGET_ACCOUNT is unset
+CREATE TABLE IF NOT EXISTS watched_accounts (
+    id BIGSERIAL PRIMARY KEY,
+    address VARCHAR(44) NOT NULL UNIQUE,
+    label TEXT,
+    added_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL
+);
@@ -0,0 +1,15 @@
+-- Create blocks table for block metadata from the blocks_meta subscription
+CREATE TABLE IF NOT EXISTS blocks (
+    slot BIGINT PRIMARY KEY,
+    blockhash VARCHAR(44) NOT NULL,
+    parent_slot BIGINT NOT NULL,
+    block_height BIGINT,
+    block_time TIMESTAMP WITH TIME ZONE,
+    leader VARCHAR(44),
+    rewards_lamports BIGINT NOT NULL,
+    ingested_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL
+);
+
+-- Create indexes for common query patterns
+CREATE INDEX IF NOT EXISTS idx_blocks_block_time ON blocks(block_time);
+CREATE INDEX IF NOT EXISTS idx_blocks_leader ON blocks(leader);
@@ -0,0 +1,6 @@
+-- Compute balance_delta in the database so it can never disagree with pre/post balances.
+-- A column can't be converted to a generated one in place, so it is recreated;
+-- existing rows get the computed value.
+ALTER TABLE account_balance_changes DROP COLUMN IF EXISTS balance_delta;
+ALTER TABLE account_balance_changes
+    ADD COLUMN balance_delta BIGINT GENERATED ALWAYS AS (post_balance - pre_balance) STORED;
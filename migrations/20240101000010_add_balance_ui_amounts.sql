@@ -0,0 +1,4 @@
+-- Decimal-adjusted balances (raw amount / 10^decimals), populated when STORE_UI_AMOUNTS is enabled.
+-- NUMERIC keeps every digit of large token amounts that a float would round.
+ALTER TABLE account_balance_changes ADD COLUMN IF NOT EXISTS pre_ui_amount NUMERIC;
+ALTER TABLE account_balance_changes ADD COLUMN IF NOT EXISTS post_ui_amount NUMERIC;
@@ -0,0 +1,3 @@
+-- Program return data (set_return_data) from transaction metadata; NULL when no program returned data
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS return_data_program_id VARCHAR(44);
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS return_data TEXT;
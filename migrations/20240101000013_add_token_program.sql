@@ -0,0 +1,3 @@
+-- Token program owning the token account (SPL Token or Token-2022); NULL for native SOL
+-- and for token balances from nodes that don't report the program id
+ALTER TABLE account_balance_changes ADD COLUMN IF NOT EXISTS token_program VARCHAR(44);
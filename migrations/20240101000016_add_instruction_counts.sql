@@ -0,0 +1,3 @@
+-- Top-level and CPI instruction counts, a rough measure of transaction complexity
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS instruction_count INTEGER;
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS inner_instruction_count INTEGER;
@@ -0,0 +1,7 @@
+-- Full RPC responses of persisted transactions, kept for audit and reprocessing when STORE_RAW_JSON is set
+CREATE TABLE IF NOT EXISTS raw_transactions (
+    id BIGSERIAL PRIMARY KEY,
+    signature VARCHAR(88) NOT NULL UNIQUE,
+    raw_json JSONB NOT NULL,
+    recorded_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL
+);
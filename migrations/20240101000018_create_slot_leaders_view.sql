@@ -0,0 +1,6 @@
+-- Slot -> leader mapping for attributing transactions to the validator that produced their block.
+-- A view over blocks (populated when SUBSCRIBE_BLOCKS_META is set), so each leader is stored once
+CREATE OR REPLACE VIEW slot_leaders AS
+SELECT slot, leader
+FROM blocks
+WHERE leader IS NOT NULL;
@@ -0,0 +1,3 @@
+-- Balance changes as a JSONB array, used instead of account_balance_changes
+-- when BALANCE_CHANGES_STORAGE=jsonb; NULL for rows stored in the table
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS balance_changes JSONB;
@@ -0,0 +1,28 @@
+-- One balance change per account and mint within a transaction, so rewriting a change
+-- that is already stored is skipped rather than duplicated. Native SOL has no mint,
+-- which is folded to '' so those changes are unique too.
+--
+-- Existing rows are never deleted here. If duplicates are already stored, the index is
+-- skipped with a warning giving their count; review them and run
+-- migrations_optional/dedupe_balance_changes.sql by hand to remove them and add the index.
+-- Until then the indexer keeps treating a unique violation as a benign duplicate.
+DO $$
+DECLARE
+    duplicate_rows BIGINT;
+BEGIN
+    SELECT COALESCE(SUM(copies - 1), 0) INTO duplicate_rows
+    FROM (
+        SELECT COUNT(*) AS copies
+        FROM account_balance_changes
+        GROUP BY transaction_id, account_address, COALESCE(mint_address, '')
+        HAVING COUNT(*) > 1
+    ) duplicates;
+
+    IF duplicate_rows > 0 THEN
+        RAISE WARNING 'account_balance_changes has % duplicate rows; skipping idx_balance_changes_unique. Run migrations_optional/dedupe_balance_changes.sql to remove them and create it.', duplicate_rows;
+    ELSE
+        CREATE UNIQUE INDEX IF NOT EXISTS idx_balance_changes_unique
+            ON account_balance_changes (transaction_id, account_address, COALESCE(mint_address, ''));
+    END IF;
+END
+$$;
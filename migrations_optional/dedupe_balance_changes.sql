@@ -0,0 +1,24 @@
+-- Opt-in: remove duplicate balance changes and add the unique index that the regular
+-- migration skipped because duplicates were already stored.
+--
+-- This script is not applied automatically. Review the duplicates first, then run it
+-- once by hand; psql reports the number of rows deleted:
+--
+--   psql "$DATABASE_URL" -f migrations_optional/dedupe_balance_changes.sql
+--
+-- Of each set of rows with the same transaction, account and mint, the one with the
+-- lowest id is kept.
+
+BEGIN;
+
+DELETE FROM account_balance_changes a
+USING account_balance_changes b
+WHERE a.transaction_id = b.transaction_id
+    AND a.account_address = b.account_address
+    AND a.mint_address IS NOT DISTINCT FROM b.mint_address
+    AND a.id > b.id;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_balance_changes_unique
+    ON account_balance_changes (transaction_id, account_address, COALESCE(mint_address, ''));
+
+COMMIT;
@@ -0,0 +1,51 @@
+-- Opt-in: convert transactions into a table hash-partitioned by fee_payer.
+--
+-- This script is not applied automatically. Run it once by hand, with the indexer
+-- stopped and the regular migrations already applied:
+--
+--   psql "$DATABASE_URL" -f migrations_optional/partition_transactions_by_fee_payer.sql
+--
+-- PostgreSQL requires unique constraints on a partitioned table to include the
+-- partition key, so uniqueness moves from signature to (signature, fee_payer) and the
+-- primary key from id to (id, fee_payer). A signature has exactly one fee payer, so
+-- duplicates are still rejected; the indexer detects the partitioned table at startup
+-- and targets the new constraint in its inserts.
+--
+-- The foreign key from account_balance_changes is dropped, as it could only reference
+-- a unique key on id alone. Deleting a transaction no longer cascades to its balance changes.
+
+BEGIN;
+
+CREATE TABLE transactions_partitioned (
+    LIKE transactions INCLUDING DEFAULTS INCLUDING GENERATED INCLUDING CONSTRAINTS
+) PARTITION BY HASH (fee_payer);
+
+CREATE TABLE transactions_p0 PARTITION OF transactions_partitioned FOR VALUES WITH (MODULUS 8, REMAINDER 0);
+CREATE TABLE transactions_p1 PARTITION OF transactions_partitioned FOR VALUES WITH (MODULUS 8, REMAINDER 1);
+CREATE TABLE transactions_p2 PARTITION OF transactions_partitioned FOR VALUES WITH (MODULUS 8, REMAINDER 2);
+CREATE TABLE transactions_p3 PARTITION OF transactions_partitioned FOR VALUES WITH (MODULUS 8, REMAINDER 3);
+CREATE TABLE transactions_p4 PARTITION OF transactions_partitioned FOR VALUES WITH (MODULUS 8, REMAINDER 4);
+CREATE TABLE transactions_p5 PARTITION OF transactions_partitioned FOR VALUES WITH (MODULUS 8, REMAINDER 5);
+CREATE TABLE transactions_p6 PARTITION OF transactions_partitioned FOR VALUES WITH (MODULUS 8, REMAINDER 6);
+CREATE TABLE transactions_p7 PARTITION OF transactions_partitioned FOR VALUES WITH (MODULUS 8, REMAINDER 7);
+
+-- LIKE keeps the column order, so rows copy over positionally
+INSERT INTO transactions_partitioned SELECT * FROM transactions;
+
+ALTER TABLE account_balance_changes DROP CONSTRAINT IF EXISTS account_balance_changes_transaction_id_fkey;
+
+-- Keep the id sequence when the old table is dropped
+ALTER SEQUENCE transactions_id_seq OWNED BY transactions_partitioned.id;
+DROP TABLE transactions;
+ALTER TABLE transactions_partitioned RENAME TO transactions;
+
+ALTER TABLE transactions ADD CONSTRAINT transactions_pkey PRIMARY KEY (id, fee_payer);
+ALTER TABLE transactions ADD CONSTRAINT transactions_signature_fee_payer_key UNIQUE (signature, fee_payer);
+
+CREATE INDEX idx_transactions_block_time ON transactions(block_time);
+CREATE INDEX idx_transactions_slot ON transactions(slot);
+CREATE INDEX idx_transactions_ingested_at ON transactions(ingested_at);
+CREATE INDEX idx_transactions_fee_payer ON transactions(fee_payer);
+CREATE INDEX idx_transactions_tx_type ON transactions(tx_type);
+
+COMMIT;
@@ -0,0 +1,19 @@
+-- Create transactions table (SQLite equivalent of the PostgreSQL schema)
+CREATE TABLE IF NOT EXISTS transactions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    signature TEXT NOT NULL UNIQUE,
+    slot INTEGER NOT NULL,
+    block_time TEXT,
+    fee INTEGER NOT NULL,
+    fee_payer TEXT NOT NULL,
+    success INTEGER NOT NULL,
+    compute_units_consumed INTEGER,
+    transaction_size_bytes INTEGER,
+    ingested_at TEXT DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+-- Create indexes for common query patterns
+CREATE INDEX IF NOT EXISTS idx_transactions_block_time ON transactions(block_time);
+CREATE INDEX IF NOT EXISTS idx_transactions_slot ON transactions(slot);
+CREATE INDEX IF NOT EXISTS idx_transactions_ingested_at ON transactions(ingested_at);
+CREATE INDEX IF NOT EXISTS idx_transactions_fee_payer ON transactions(fee_payer);
@@ -0,0 +1,15 @@
+-- Create account_balance_changes table
+CREATE TABLE IF NOT EXISTS account_balance_changes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    transaction_id INTEGER NOT NULL REFERENCES transactions(id) ON DELETE CASCADE,
+    account_address TEXT NOT NULL,
+    mint_address TEXT,
+    pre_balance INTEGER NOT NULL,
+    post_balance INTEGER NOT NULL,
+    balance_delta INTEGER NOT NULL
+);
+
+-- Create indexes for common query patterns
+CREATE INDEX IF NOT EXISTS idx_balance_changes_transaction_id ON account_balance_changes(transaction_id);
+CREATE INDEX IF NOT EXISTS idx_balance_changes_account_address ON account_balance_changes(account_address);
+CREATE INDEX IF NOT EXISTS idx_balance_changes_mint_address ON account_balance_changes(mint_address);
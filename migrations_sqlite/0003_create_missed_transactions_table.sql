@@ -0,0 +1,12 @@
+-- Create missed_transactions table for signatures that could not be fetched
+CREATE TABLE IF NOT EXISTS missed_transactions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    signature TEXT NOT NULL UNIQUE,
+    reason TEXT NOT NULL,
+    error_message TEXT,
+    recorded_at TEXT DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+-- Create indexes for common query patterns
+CREATE INDEX IF NOT EXISTS idx_missed_transactions_reason ON missed_transactions(reason);
+CREATE INDEX IF NOT EXISTS idx_missed_transactions_recorded_at ON missed_transactions(recorded_at);
@@ -0,0 +1,2 @@
+-- Flag transactions that use a durable nonce instead of a recent blockhash
+ALTER TABLE transactions ADD COLUMN uses_durable_nonce INTEGER NOT NULL DEFAULT 0;
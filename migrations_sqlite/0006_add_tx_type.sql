@@ -0,0 +1,4 @@
+-- Coarse transaction classification (swap, transfer, nft, stake, unknown)
+ALTER TABLE transactions ADD COLUMN tx_type TEXT NOT NULL DEFAULT 'unknown';
+
+CREATE INDEX IF NOT EXISTS idx_transactions_tx_type ON transactions(tx_type);
@@ -0,0 +1,8 @@
+-- Create watched_accounts table for a database-managed watchlist
+-- Used when TARGET_ACCOUNT is unset
+CREATE TABLE IF NOT EXISTS watched_accounts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    address TEXT NOT NULL UNIQUE,
+    label TEXT,
+    added_at TEXT DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
@@ -0,0 +1,15 @@
+-- Create blocks table for block metadata from the blocks_meta subscription
+CREATE TABLE IF NOT EXISTS blocks (
+    slot INTEGER PRIMARY KEY,
+    blockhash TEXT NOT NULL,
+    parent_slot INTEGER NOT NULL,
+    block_height INTEGER,
+    block_time TEXT,
+    leader TEXT,
+    rewards_lamports INTEGER NOT NULL,
+    ingested_at TEXT DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+-- Create indexes for common query patterns
+CREATE INDEX IF NOT EXISTS idx_blocks_block_time ON blocks(block_time);
+CREATE INDEX IF NOT EXISTS idx_blocks_leader ON blocks(leader);
@@ -0,0 +1,5 @@
+-- Compute balance_delta in the database so it can never disagree with pre/post balances.
+-- SQLite can only add VIRTUAL generated columns with ALTER TABLE; the value is computed on read.
+ALTER TABLE account_balance_changes DROP COLUMN balance_delta;
+ALTER TABLE account_balance_changes
+    ADD COLUMN balance_delta INTEGER GENERATED ALWAYS AS (post_balance - pre_balance) VIRTUAL;
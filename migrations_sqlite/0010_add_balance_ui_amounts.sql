@@ -0,0 +1,4 @@
+-- Decimal-adjusted balances (raw amount / 10^decimals), populated when STORE_UI_AMOUNTS is enabled.
+-- Stored as TEXT because SQLite's NUMERIC affinity would convert them to floating point.
+ALTER TABLE account_balance_changes ADD COLUMN pre_ui_amount TEXT;
+ALTER TABLE account_balance_changes ADD COLUMN post_ui_amount TEXT;
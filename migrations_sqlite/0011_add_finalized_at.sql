@@ -0,0 +1,3 @@
+-- When the transaction was seen finalized on the transaction status subscription
+-- (NULL until then, or always when TRACK_FINALIZATION is disabled)
+ALTER TABLE transactions ADD COLUMN finalized_at TEXT;
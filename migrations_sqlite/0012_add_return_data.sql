@@ -0,0 +1,3 @@
+-- Program return data (set_return_data) from transaction metadata; NULL when no program returned data
+ALTER TABLE transactions ADD COLUMN return_data_program_id TEXT;
+ALTER TABLE transactions ADD COLUMN return_data TEXT;
@@ -0,0 +1,2 @@
+-- Token balance changes of the native mint (wSOL), which downstream accounting can merge with SOL
+ALTER TABLE account_balance_changes ADD COLUMN is_wrapped_sol INTEGER NOT NULL DEFAULT 0;
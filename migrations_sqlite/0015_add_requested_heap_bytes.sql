@@ -0,0 +1,2 @@
+-- Heap frame size requested with ComputeBudget RequestHeapFrame; NULL when the default heap was used
+ALTER TABLE transactions ADD COLUMN requested_heap_bytes INTEGER;
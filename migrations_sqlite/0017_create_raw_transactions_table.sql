@@ -0,0 +1,7 @@
+-- Full RPC responses of persisted transactions, kept for audit and reprocessing when STORE_RAW_JSON is set
+CREATE TABLE IF NOT EXISTS raw_transactions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    signature TEXT NOT NULL UNIQUE,
+    raw_json TEXT NOT NULL,
+    recorded_at TEXT DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
@@ -0,0 +1,2 @@
+-- Lamports of the base fee burned, computed at parse time from BURN_RATE_PERCENT
+ALTER TABLE transactions ADD COLUMN sol_burned INTEGER;
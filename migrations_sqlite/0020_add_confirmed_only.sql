@@ -0,0 +1,2 @@
+-- Flag transactions fetched at Confirmed after Finalized fetches kept missing (CONFIRMED_FALLBACK_AFTER)
+ALTER TABLE transactions ADD COLUMN confirmed_only INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,16 @@
+-- One balance change per account and mint within a transaction, so rewriting a change
+-- that is already stored is skipped rather than duplicated. Native SOL has no mint,
+-- which is folded to '' so those changes are unique too.
+--
+-- SQLite has no conditional DDL, so unlike the PostgreSQL migration, which skips the
+-- index and leaves existing rows alone, this keeps the lowest-id row of each duplicate
+-- set. The SQLite backend is for local experimentation rather than production data.
+DELETE FROM account_balance_changes
+WHERE id NOT IN (
+    SELECT MIN(id)
+    FROM account_balance_changes
+    GROUP BY transaction_id, account_address, COALESCE(mint_address, '')
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_balance_changes_unique
+    ON account_balance_changes (transaction_id, account_address, COALESCE(mint_address, ''));
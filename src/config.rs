@@ -1,5 +1,43 @@
 use crate::error::AppError;
+use crate::labels::AccountLabels;
+use std::collections::HashMap;
 use std::env;
+use std::path::PathBuf;
+
+/// Commitment level as accepted in configuration.
+/// 
+/// Parsed case-insensitively from "processed", "confirmed", or "finalized" and
+/// converted to the gRPC or RPC commitment types where it is used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Commitment {
+    Processed,
+    Confirmed,
+    Finalized,
+}
+
+impl Commitment {
+    /// Lowercase name of the commitment level, as used in configuration.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Processed => "processed",
+            Self::Confirmed => "confirmed",
+            Self::Finalized => "finalized",
+        }
+    }
+
+    /// Parse a commitment level from an environment variable value.
+    fn parse(var: &str, value: &str) -> Result<Self, AppError> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "processed" => Ok(Self::Processed),
+            "confirmed" => Ok(Self::Confirmed),
+            "finalized" => Ok(Self::Finalized),
+            other => Err(AppError::Config(format!(
+                "{} must be one of processed, confirmed, finalized, got: {}",
+                var, other
+            ))),
+        }
+    }
+}
 
 /// Application configuration loaded from environment variables.
 /// 
@@ -7,42 +45,161 @@ use std::env;
 /// if the environment is misconfigured.
 #[derive(Debug, Clone)]
 pub struct AppConfig {
-    pub grpc_endpoint: String,
-    pub grpc_token: String,
+    pub grpc_endpoints: Vec<String>,
+    pub grpc_tokens: Vec<String>,
     pub rpc_http_url: String,
-    pub target_account: String,
+    pub target_account: Option<String>,
     pub database_url: String,
     pub log_level: String,
     pub metrics_port: u16,
     pub include_failed_transactions: bool,
+    pub dual_commitment: bool,
+    pub persist_only_finalized: bool,
+    pub account_labels: AccountLabels,
+    pub per_account_metrics: bool,
+    pub max_balance_changes: Option<usize>,
+    pub stream_commitment: Commitment,
+    pub fetch_commitment: Option<Commitment>,
+    pub confirmed_fallback_after: u32,
+    pub slot_commitment: Option<Commitment>,
+    pub balance_changes_target_only: bool,
+    pub target_is_program: bool,
+    pub record_updates_file: Option<PathBuf>,
+    pub min_fee_lamports: u64,
+    pub min_token_change: HashMap<String, u64>,
+    pub mint_allowlist: Vec<String>,
+    pub store_sol_balance_changes: bool,
+    pub burn_rate_percent: u8,
+    pub processing_time_buckets: Option<Vec<f64>>,
+    pub db_time_buckets: Option<Vec<f64>>,
+    pub rpc_max_concurrency: usize,
+    pub program_allowlist: Vec<String>,
+    pub account_required: Vec<String>,
+    pub retryable_error_patterns: Vec<String>,
+    pub fetch_delay_ms: u64,
+    pub subscribe_blocks_meta: bool,
+    pub process_timeout_secs: u64,
+    pub db_statement_timeout_ms: u64,
+    pub slow_db_log_ms: u64,
+    pub db_connect_attempts: u32,
+    pub db_connect_retry_delay_ms: u64,
+    pub reconcile_interval_secs: u64,
+    pub reconcile_backfill: bool,
+    pub db_maintenance_interval_secs: u64,
+    pub db_maintenance_vacuum: bool,
+    pub store_ui_amounts: bool,
+    pub balance_changes_jsonb: bool,
+    pub db_application_name: String,
+    pub track_finalization: bool,
+    pub grpc_compression: bool,
+    pub tokio_console: bool,
+    pub sample_rate: f64,
+    pub skip_zero_balance_change: bool,
+    pub strict_target_only: bool,
+    pub store_raw_json: bool,
+    pub drain_timeout_secs: u64,
+    pub max_reconnect_attempts: u32,
+    pub grpc_max_conn_age_secs: u64,
+    pub self_report_interval_secs: u64,
+    pub metrics_push_url: Option<String>,
+    pub metrics_push_interval_secs: u64,
+    pub kafka_brokers: Option<String>,
+    pub kafka_topic: Option<String>,
+    pub preflight_strict: bool,
+    pub account_data_slice: Vec<(u64, u64)>,
 }
 
 impl AppConfig {
     /// Load configuration from environment variables.
     /// 
     /// Required environment variables:
-    /// - GRPC_ENDPOINT: The gRPC endpoint URL (WebSocket)
-    /// - GRPC_TOKEN: Authentication token for RPC services
-    /// - TARGET_ACCOUNT: Solana account address to monitor
+    /// - GRPC_ENDPOINT: The gRPC endpoint URL (WebSocket), or a comma-separated list to
+    ///   subscribe to several endpoints at once for redundancy
+    /// - GRPC_TOKEN: Authentication token for RPC services; with several endpoints, either
+    ///   one token for all of them or a comma-separated list in endpoint order
     /// - DATABASE_URL: PostgreSQL connection string
     /// 
+    /// TARGET_ACCOUNT, the Solana account address to monitor, may be left unset when
+    /// the `watched_accounts` table lists the accounts to monitor instead.
+    /// 
+    /// GRPC_TOKEN and DATABASE_URL may instead be read from a file named by
+    /// GRPC_TOKEN_FILE or DATABASE_URL_FILE (e.g. a Kubernetes secret mount),
+    /// which takes precedence over the variable itself.
+    /// 
     /// Optional environment variables:
     /// - RPC_HTTP_URL: HTTP RPC endpoint (defaults to public Solana mainnet)
     /// - LOG_LEVEL: Logging level (default: "info")
     /// - METRICS_PORT: Port for Prometheus metrics server (default: 9090)
     /// - INCLUDE_FAILED_TRANSACTIONS: Whether to include failed transactions (default: "true")
+    /// - DUAL_COMMITMENT: Run Confirmed and Finalized streams side by side (default: "false")
+    /// - PERSIST_ONLY_FINALIZED: Hold transactions from a non-Finalized stream until their slot finalizes (default: "false")
+    /// - ACCOUNT_LABELS: JSON map of pubkey -> name used to annotate logs (default: none)
+    /// - PER_ACCOUNT_METRICS: Count processed transactions per monitored account (default: "false")
+    /// - MAX_BALANCE_CHANGES: Cap on balance changes stored per transaction (default: unlimited)
+    /// - STREAM_COMMITMENT: Commitment of the gRPC subscription (default: "confirmed")
+    /// - FETCH_COMMITMENT: Commitment of RPC transaction fetches (default: same as the stream)
+    /// - SLOT_COMMITMENT: Commitment of the slot updates that track the chain head (default: same as the stream)
+    /// - CONFIRMED_FALLBACK_AFTER: Finalized fetches not found before one retry at confirmed, 0 to disable (default: 0)
+    /// - BALANCE_CHANGES_TARGET_ONLY: Only store balance changes of the target account (default: "false")
+    /// - RECORD_UPDATES_FILE: Append raw gRPC updates to this file for replay (default: no recording)
+    /// - MIN_FEE_LAMPORTS: Skip persisting transactions with a lower fee (default: 0)
+    /// - MIN_TOKEN_CHANGE: JSON map of mint -> smallest token balance change to store, in raw units (default: none)
+    /// - MINT_ALLOWLIST: Comma-separated mints; only token balance changes of these are stored (default: all)
+    /// - STORE_SOL_BALANCE_CHANGES: Store native SOL balance changes (default: "true")
+    /// - BURN_RATE_PERCENT: Percentage of the base fee burned, recorded per transaction as sol_burned (default: 50)
+    /// - PROCESSING_TIME_BUCKETS: Comma-separated processing-time histogram buckets in seconds (default: built-in)
+    /// - DB_TIME_BUCKETS: Comma-separated database-time histogram buckets in seconds (default: built-in)
+    /// - RPC_MAX_CONCURRENCY: Upper bound for adaptive concurrent RPC fetches (default: 8)
+    /// - PROGRAM_ALLOWLIST: Comma-separated program ids; only transactions invoking one are stored (default: all)
+    /// - ACCOUNT_REQUIRED: Comma-separated accounts that must all appear in a streamed transaction (default: none)
+    /// - RETRYABLE_ERROR_PATTERNS: Comma-separated message substrings that make an error retryable (default: none)
+    /// - FETCH_DELAY_MS: Delay before fetching a signature first seen on the stream (default: 0)
+    /// - SUBSCRIBE_BLOCKS_META: Store block leaders and rewards in the blocks table (default: "false")
+    /// - PROCESS_TIMEOUT_SECS: Per-transaction fetch and persist time limit, 0 to disable (default: 0)
+    /// - DB_STATEMENT_TIMEOUT_MS: PostgreSQL statement_timeout, 0 to disable (default: 30000)
+    /// - SLOW_DB_LOG_MS: Warn about transaction writes slower than this, 0 to disable (default: 0)
+    /// - DB_CONNECT_ATTEMPTS: PostgreSQL connection attempts at startup before giving up (default: 10)
+    /// - DB_CONNECT_RETRY_DELAY_MS: Wait after the first failed attempt, doubling after each further one (default: 1000)
+    /// - RECONCILE_INTERVAL_SECS: Seconds between reconcile runs, 0 to disable (default: 0)
+    /// - RECONCILE_BACKFILL: Fetch signatures the reconcile job finds missing (default: false)
+    /// - DB_MAINTENANCE_INTERVAL_SECS: Seconds between ANALYZE runs on the indexer's tables, 0 to disable (default: 0)
+    /// - DB_MAINTENANCE_VACUUM: Also VACUUM the tables on each maintenance run (default: false)
+    /// - STORE_UI_AMOUNTS: Also store decimal-adjusted balances in balance changes (default: "false")
+    /// - BALANCE_CHANGES_STORAGE: "table" (account_balance_changes) or "jsonb" (column on transactions, PostgreSQL only) (default: "table")
+    /// - DB_APPLICATION_NAME: PostgreSQL application_name shown in pg_stat_activity (default: "mev-burn-indexer")
+    /// - TRACK_FINALIZATION: Mark stored transactions finalized from a status subscription (default: "false")
+    /// - GRPC_COMPRESSION: gRPC stream compression, "gzip" or "none" (default: "none")
+    /// - TOKIO_CONSOLE: Serve task states to tokio-console; needs the tokio-console feature (default: "false")
+    /// - SAMPLE_RATE: Fraction of transactions to fetch and persist, 0.0 to 1.0 (default: 1.0)
+    /// - SKIP_ZERO_BALANCE_CHANGE: Skip transactions without balance changes (default: "false")
+    /// - STRICT_TARGET_ONLY: Skip transactions no target account signed, changed balance in, or was invoked in (default: "false")
+    /// - TARGET_IS_PROGRAM: The target accounts are program ids rather than wallets (default: "false")
+    /// - STORE_RAW_JSON: Also store each persisted transaction's full RPC response (default: "false")
+    /// - DRAIN_TIMEOUT_SECS: Time to let in-flight fetches finish when processing stops, 0 to wait indefinitely (default: 30)
+    /// - MAX_RECONNECT_ATTEMPTS: Exit after this many consecutive failed stream reconnects, 0 for unlimited (default: 0)
+    /// - GRPC_MAX_CONN_AGE_SECS: Reconnect streams that have been connected this long, 0 to disable (default: 0)
+    /// - SELF_REPORT_INTERVAL_SECS: Seconds between summary log lines, 0 to disable (default: 60)
+    /// - METRICS_PUSH_URL: OTLP/HTTP metrics endpoint to push metrics to as JSON (default: none, push disabled)
+    /// - METRICS_PUSH_INTERVAL_SECS: Seconds between metric pushes (default: 15)
+    /// - KAFKA_BROKERS / KAFKA_TOPIC: Publish inserted transactions to this topic; needs the kafka feature (default: none)
+    /// - PREFLIGHT_STRICT: Abort startup when the RPC preflight check fails (default: "true")
+    /// - ACCOUNT_DATA_SLICE: Comma-separated offset:length byte ranges of account data to receive (default: full data)
     pub fn from_env() -> Result<Self, AppError> {
         let grpc_endpoint = env::var("GRPC_ENDPOINT")
             .map_err(|_| AppError::Config("GRPC_ENDPOINT not set".to_string()))?;
 
-        let grpc_token = env::var("GRPC_TOKEN")
-            .map_err(|_| AppError::Config("GRPC_TOKEN not set".to_string()))?;
+        let grpc_token = Self::env_or_file("GRPC_TOKEN")?
+            .ok_or_else(|| AppError::Config("GRPC_TOKEN not set".to_string()))?;
+
+        let (grpc_endpoints, grpc_tokens) = Self::parse_grpc_endpoints(&grpc_endpoint, &grpc_token)?;
 
+        // Optional when the watchlist is managed in the watched_accounts table
         let target_account = env::var("TARGET_ACCOUNT")
-            .map_err(|_| AppError::Config("TARGET_ACCOUNT not set".to_string()))?;
+            .ok()
+            .filter(|val| !val.trim().is_empty());
 
-        let database_url = env::var("DATABASE_URL")
-            .map_err(|_| AppError::Config("DATABASE_URL not set".to_string()))?;
+        let database_url = Self::env_or_file("DATABASE_URL")?
+            .ok_or_else(|| AppError::Config("DATABASE_URL not set".to_string()))?;
 
         // HTTP RPC endpoint with fallback to public Solana mainnet
         let rpc_http_url = env::var("RPC_HTTP_URL")
@@ -63,24 +220,640 @@ impl AppConfig {
             .and_then(|val| val.parse::<bool>().ok())
             .unwrap_or(true);
 
+        // Parse dual_commitment flag
+        // Default to false since a second stream doubles gRPC and RPC usage
+        let dual_commitment = env::var("DUAL_COMMITMENT")
+            .ok()
+            .and_then(|val| val.parse::<bool>().ok())
+            .unwrap_or(false);
+
+        // Parse persist_only_finalized flag
+        // Default to false so Confirmed transactions are visible as soon as they are fetched
+        let persist_only_finalized = env::var("PERSIST_ONLY_FINALIZED")
+            .ok()
+            .and_then(|val| val.parse::<bool>().ok())
+            .unwrap_or(false);
+
+        // Parse optional account labels for human-readable logs
+        let account_labels = match env::var("ACCOUNT_LABELS") {
+            Ok(json) if !json.trim().is_empty() => AccountLabels::from_json(&json)?,
+            _ => AccountLabels::default(),
+        };
+
+        // Parse per_account_metrics flag
+        // Default to false since every monitored account adds a series
+        let per_account_metrics = env::var("PER_ACCOUNT_METRICS")
+            .ok()
+            .and_then(|val| val.parse::<bool>().ok())
+            .unwrap_or(false);
+
+        // Parse optional cap on balance changes per transaction
+        // Unset means unlimited, preserving the default behavior
+        let max_balance_changes = env::var("MAX_BALANCE_CHANGES")
+            .ok()
+            .and_then(|val| val.parse::<usize>().ok());
+
+        // Parse commitment levels for the stream and the RPC fetch separately
+        // A finalized fetch behind a confirmed stream trades latency for correctness
+        let stream_commitment = match env::var("STREAM_COMMITMENT") {
+            Ok(val) => Commitment::parse("STREAM_COMMITMENT", &val)?,
+            Err(_) => Commitment::Confirmed,
+        };
+
+        let fetch_commitment = env::var("FETCH_COMMITMENT")
+            .ok()
+            .map(|val| Commitment::parse("FETCH_COMMITMENT", &val))
+            .transpose()?;
+
+        // getTransaction only serves confirmed or finalized data
+        if fetch_commitment == Some(Commitment::Processed) {
+            return Err(AppError::Config(
+                "FETCH_COMMITMENT must be confirmed or finalized, got: processed".to_string()
+            ));
+        }
+
+        // Slot updates can track the head at a different commitment than transactions
+        let slot_commitment = env::var("SLOT_COMMITMENT")
+            .ok()
+            .map(|val| Commitment::parse("SLOT_COMMITMENT", &val))
+            .transpose()?;
+
+        // Parse finalized-miss count before falling back to a confirmed fetch
+        // Default to 0 (disabled) so finalized fetches never store confirmed data
+        let confirmed_fallback_after = env::var("CONFIRMED_FALLBACK_AFTER")
+            .ok()
+            .and_then(|val| val.parse::<u32>().ok())
+            .unwrap_or(0);
+
+        // Parse balance_changes_target_only flag
+        // Default to false to record every account touched by the transaction
+        let balance_changes_target_only = env::var("BALANCE_CHANGES_TARGET_ONLY")
+            .ok()
+            .and_then(|val| val.parse::<bool>().ok())
+            .unwrap_or(false);
+
+        // Parse target_is_program flag
+        // Default to false since the indexer was built around monitoring a wallet
+        let target_is_program = env::var("TARGET_IS_PROGRAM")
+            .ok()
+            .and_then(|val| val.parse::<bool>().ok())
+            .unwrap_or(false);
+
+        // A program holds no balances of its own, so every balance change would be dropped
+        if target_is_program && balance_changes_target_only {
+            return Err(AppError::Config(
+                "BALANCE_CHANGES_TARGET_ONLY cannot be combined with TARGET_IS_PROGRAM".to_string(),
+            ));
+        }
+
+        // Optional recording of raw gRPC updates for `replay --file`
+        let record_updates_file = env::var("RECORD_UPDATES_FILE")
+            .ok()
+            .filter(|val| !val.trim().is_empty())
+            .map(PathBuf::from);
+
+        // Parse minimum fee filter
+        // Default to 0 so every transaction is persisted
+        let min_fee_lamports = env::var("MIN_FEE_LAMPORTS")
+            .ok()
+            .and_then(|val| val.parse::<u64>().ok())
+            .unwrap_or(0);
+
+        // Parse optional per-mint token change thresholds
+        let min_token_change = match env::var("MIN_TOKEN_CHANGE") {
+            Ok(json) if !json.trim().is_empty() => Self::parse_min_token_change(&json)?,
+            _ => HashMap::new(),
+        };
+
+        // Parse optional token mint allowlist for balance changes
+        let mint_allowlist: Vec<String> = env::var("MINT_ALLOWLIST")
+            .unwrap_or_default()
+            .split(',')
+            .map(|mint| mint.trim().to_string())
+            .filter(|mint| !mint.is_empty())
+            .collect();
+
+        for mint in &mint_allowlist {
+            bs58::decode(mint).into_vec().map_err(|e| {
+                AppError::Config(format!("Invalid base58 mint in MINT_ALLOWLIST '{}': {}", mint, e))
+            })?;
+        }
+
+        // Parse native SOL balance change flag
+        // Default to true; disable to keep only token changes
+        let store_sol_balance_changes = env::var("STORE_SOL_BALANCE_CHANGES")
+            .ok()
+            .and_then(|val| val.parse::<bool>().ok())
+            .unwrap_or(true);
+
+        // Parse burn rate applied to the base fee
+        // Default to 50%, the share of the base fee the protocol burns
+        let burn_rate_percent = match env::var("BURN_RATE_PERCENT") {
+            Ok(val) => val
+                .parse::<u8>()
+                .ok()
+                .filter(|percent| *percent <= 100)
+                .ok_or_else(|| {
+                    AppError::Config(format!(
+                        "BURN_RATE_PERCENT must be a whole number from 0 to 100, got '{}'",
+                        val
+                    ))
+                })?,
+            Err(_) => 50,
+        };
+
+        // Parse optional histogram bucket overrides for the timing metrics
+        let processing_time_buckets = env::var("PROCESSING_TIME_BUCKETS")
+            .ok()
+            .map(|val| Self::parse_buckets("PROCESSING_TIME_BUCKETS", &val))
+            .transpose()?;
+
+        let db_time_buckets = env::var("DB_TIME_BUCKETS")
+            .ok()
+            .map(|val| Self::parse_buckets("DB_TIME_BUCKETS", &val))
+            .transpose()?;
+
+        // Parse the ceiling for adaptive RPC fetch concurrency
+        // Concurrency starts at 1 and grows toward this while the endpoint keeps up
+        let rpc_max_concurrency = env::var("RPC_MAX_CONCURRENCY")
+            .ok()
+            .and_then(|val| val.parse::<usize>().ok())
+            .filter(|max| *max > 0)
+            .unwrap_or(8);
+
+        // Parse optional program allow-list, validating each id up front
+        let program_allowlist: Vec<String> = env::var("PROGRAM_ALLOWLIST")
+            .unwrap_or_default()
+            .split(',')
+            .map(|id| id.trim().to_string())
+            .filter(|id| !id.is_empty())
+            .collect();
+
+        for program_id in &program_allowlist {
+            bs58::decode(program_id).into_vec().map_err(|e| {
+                AppError::Config(format!("Invalid base58 program id in PROGRAM_ALLOWLIST '{}': {}", program_id, e))
+            })?;
+        }
+
+        // Parse accounts every streamed transaction must include, on top of the targets
+        let account_required: Vec<String> = env::var("ACCOUNT_REQUIRED")
+            .unwrap_or_default()
+            .split(',')
+            .map(|account| account.trim().to_string())
+            .filter(|account| !account.is_empty())
+            .collect();
+
+        for account in &account_required {
+            let valid = bs58::decode(account)
+                .into_vec()
+                .is_ok_and(|bytes| bytes.len() == 32);
+            if !valid {
+                return Err(AppError::Config(format!(
+                    "Invalid account in ACCOUNT_REQUIRED '{}': expected a base58 public key",
+                    account
+                )));
+            }
+        }
+
+        // Parse extra retryable error patterns for provider-specific messages
+        let retryable_error_patterns: Vec<String> = env::var("RETRYABLE_ERROR_PATTERNS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|pattern| pattern.trim().to_string())
+            .filter(|pattern| !pattern.is_empty())
+            .collect();
+
+        // Parse delay before fetching freshly streamed signatures
+        // Default to 0 to fetch immediately, as before
+        let fetch_delay_ms = env::var("FETCH_DELAY_MS")
+            .ok()
+            .and_then(|val| val.parse::<u64>().ok())
+            .unwrap_or(0);
+
+        // Parse subscribe_blocks_meta flag
+        // Default to false since it adds an update for every slot
+        let subscribe_blocks_meta = env::var("SUBSCRIBE_BLOCKS_META")
+            .ok()
+            .and_then(|val| val.parse::<bool>().ok())
+            .unwrap_or(false);
+
+        // Parse per-transaction processing timeout
+        // Timed-out signatures are recorded as missed for a later retry
+        // Default to no limit, matching the behavior before the timeout existed
+        let process_timeout_secs = env::var("PROCESS_TIMEOUT_SECS")
+            .ok()
+            .and_then(|val| val.parse::<u64>().ok())
+            .unwrap_or(0);
+
+        // Parse PostgreSQL statement timeout
+        // Default to 30 seconds so a stuck query can't wedge the shared connection
+        let db_statement_timeout_ms = env::var("DB_STATEMENT_TIMEOUT_MS")
+            .ok()
+            .and_then(|val| val.parse::<u64>().ok())
+            .unwrap_or(30_000);
+
+        // Parse slow database write threshold
+        // Default to 0 (disabled); DATABASE_OPERATION_TIME already covers the aggregate view
+        let slow_db_log_ms = env::var("SLOW_DB_LOG_MS")
+            .ok()
+            .and_then(|val| val.parse::<u64>().ok())
+            .unwrap_or(0);
+
+        // Parse PostgreSQL connection retries at startup
+        // Default to 10 attempts from 1 second apart, which covers a database container still booting
+        let db_connect_attempts = env::var("DB_CONNECT_ATTEMPTS")
+            .ok()
+            .and_then(|val| val.parse::<u32>().ok())
+            .filter(|attempts| *attempts > 0)
+            .unwrap_or(10);
+
+        let db_connect_retry_delay_ms = env::var("DB_CONNECT_RETRY_DELAY_MS")
+            .ok()
+            .and_then(|val| val.parse::<u64>().ok())
+            .unwrap_or(1000);
+
+        // Parse reconcile job interval
+        // Default to 0 (disabled) since each run costs one RPC call per account
+        let reconcile_interval_secs = env::var("RECONCILE_INTERVAL_SECS")
+            .ok()
+            .and_then(|val| val.parse::<u64>().ok())
+            .unwrap_or(0);
+
+        // Parse reconcile backfill flag
+        // Default to false so the job only reports gaps
+        let reconcile_backfill = env::var("RECONCILE_BACKFILL")
+            .ok()
+            .and_then(|val| val.parse::<bool>().ok())
+            .unwrap_or(false);
+
+        // Parse database maintenance interval
+        // Default to 0 (disabled) since autovacuum normally covers this
+        let db_maintenance_interval_secs = env::var("DB_MAINTENANCE_INTERVAL_SECS")
+            .ok()
+            .and_then(|val| val.parse::<u64>().ok())
+            .unwrap_or(0);
+
+        // Parse database maintenance vacuum flag
+        // Default to false; ANALYZE alone is cheap, VACUUM holds up writes while it runs
+        let db_maintenance_vacuum = env::var("DB_MAINTENANCE_VACUUM")
+            .ok()
+            .and_then(|val| val.parse::<bool>().ok())
+            .unwrap_or(false);
+
+        // Parse store_ui_amounts flag
+        // Default to false to keep balance change rows compact
+        let store_ui_amounts = env::var("STORE_UI_AMOUNTS")
+            .ok()
+            .and_then(|val| val.parse::<bool>().ok())
+            .unwrap_or(false);
+
+        // Parse where balance changes are stored
+        // Default to the account_balance_changes table, which per-account queries can index
+        let balance_changes_jsonb = match env::var("BALANCE_CHANGES_STORAGE") {
+            Ok(val) => match val.trim().to_ascii_lowercase().as_str() {
+                "table" => false,
+                "jsonb" => true,
+                other => {
+                    return Err(AppError::Config(format!(
+                        "BALANCE_CHANGES_STORAGE must be one of table, jsonb, got: {}",
+                        other
+                    )))
+                }
+            },
+            Err(_) => false,
+        };
+
+        // Parse PostgreSQL application_name
+        // Default to the service name so DBAs can attribute connections
+        let db_application_name = env::var("DB_APPLICATION_NAME")
+            .ok()
+            .filter(|val| !val.trim().is_empty())
+            .unwrap_or_else(|| "mev-burn-indexer".to_string());
+
+        // Parse track_finalization flag
+        // Default to false since it opens a second gRPC subscription
+        let track_finalization = env::var("TRACK_FINALIZATION")
+            .ok()
+            .and_then(|val| val.parse::<bool>().ok())
+            .unwrap_or(false);
+
+        // Parse gRPC stream compression
+        // Default to none; gzip trades CPU for bandwidth
+        let grpc_compression = match env::var("GRPC_COMPRESSION") {
+            Ok(val) => match val.trim().to_lowercase().as_str() {
+                "gzip" => true,
+                "none" | "" => false,
+                other => {
+                    return Err(AppError::Config(format!(
+                        "Invalid GRPC_COMPRESSION '{}': expected gzip or none",
+                        other
+                    )))
+                }
+            },
+            Err(_) => false,
+        };
+
+        // Parse tokio_console flag
+        // Default to false since task instrumentation adds overhead to every poll
+        let tokio_console = env::var("TOKIO_CONSOLE")
+            .ok()
+            .and_then(|val| val.parse::<bool>().ok())
+            .unwrap_or(false);
+
+        // Parse sampling rate
+        // Default to 1.0 so every transaction is persisted
+        let sample_rate = match env::var("SAMPLE_RATE") {
+            Ok(val) => val
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|rate| (0.0..=1.0).contains(rate))
+                .ok_or_else(|| {
+                    AppError::Config(format!("Invalid SAMPLE_RATE '{}': expected a number from 0.0 to 1.0", val))
+                })?,
+            Err(_) => 1.0,
+        };
+
+        // Parse skip_zero_balance_change flag
+        // Default to false to keep storing every transaction
+        let skip_zero_balance_change = env::var("SKIP_ZERO_BALANCE_CHANGE")
+            .ok()
+            .and_then(|val| val.parse::<bool>().ok())
+            .unwrap_or(false);
+
+        // Parse store_raw_json flag
+        // Default to false since raw responses are several times the size of the parsed rows
+        let store_raw_json = env::var("STORE_RAW_JSON")
+            .ok()
+            .and_then(|val| val.parse::<bool>().ok())
+            .unwrap_or(false);
+
+        // Parse strict_target_only flag
+        // Default to false to keep every transaction the subscription matches
+        let strict_target_only = env::var("STRICT_TARGET_ONLY")
+            .ok()
+            .and_then(|val| val.parse::<bool>().ok())
+            .unwrap_or(false);
+
+        // Parse drain timeout in seconds
+        // Default to 30 so stopping stays bounded under an orchestrator's grace period
+        let drain_timeout_secs = env::var("DRAIN_TIMEOUT_SECS")
+            .ok()
+            .and_then(|val| val.parse::<u64>().ok())
+            .unwrap_or(30);
+
+        // Parse reconnect attempt limit
+        // Default to 0 (unlimited) so transient outages of any length are ridden out
+        let max_reconnect_attempts = env::var("MAX_RECONNECT_ATTEMPTS")
+            .ok()
+            .and_then(|val| val.parse::<u32>().ok())
+            .unwrap_or(0);
+
+        // Parse maximum gRPC connection age in seconds
+        // Default to 0 (disabled) since each recycle briefly leaves the stream disconnected
+        let grpc_max_conn_age_secs = env::var("GRPC_MAX_CONN_AGE_SECS")
+            .ok()
+            .and_then(|val| val.parse::<u64>().ok())
+            .unwrap_or(0);
+
+        // Parse self-report interval in seconds
+        // Default to 60 so deployments without Prometheus still get a periodic summary
+        let self_report_interval_secs = env::var("SELF_REPORT_INTERVAL_SECS")
+            .ok()
+            .and_then(|val| val.parse::<u64>().ok())
+            .unwrap_or(60);
+
+        // Parse optional metrics push target
+        // Unset by default; scraping /metrics remains the primary path
+        let metrics_push_url = env::var("METRICS_PUSH_URL")
+            .ok()
+            .map(|url| url.trim().to_string())
+            .filter(|url| !url.is_empty());
+
+        if let Some(url) = &metrics_push_url {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                return Err(AppError::Config(format!(
+                    "Invalid METRICS_PUSH_URL '{}': must start with http:// or https://",
+                    url
+                )));
+            }
+        }
+
+        let metrics_push_interval_secs = env::var("METRICS_PUSH_INTERVAL_SECS")
+            .ok()
+            .and_then(|val| val.parse::<u64>().ok())
+            .filter(|secs| *secs > 0)
+            .unwrap_or(15);
+
+        // Parse optional Kafka sink
+        // Unset by default; the database is the only required output
+        let kafka_brokers = env::var("KAFKA_BROKERS")
+            .ok()
+            .map(|brokers| brokers.trim().to_string())
+            .filter(|brokers| !brokers.is_empty());
+        let kafka_topic = env::var("KAFKA_TOPIC")
+            .ok()
+            .map(|topic| topic.trim().to_string())
+            .filter(|topic| !topic.is_empty());
+
+        if kafka_brokers.is_some() != kafka_topic.is_some() {
+            return Err(AppError::Config(
+                "KAFKA_BROKERS and KAFKA_TOPIC must be set together".to_string(),
+            ));
+        }
+
+        // Parse preflight_strict flag
+        // Default to true so a lagging or misconfigured RPC node is caught before streaming
+        let preflight_strict = env::var("PREFLIGHT_STRICT")
+            .ok()
+            .and_then(|val| val.parse::<bool>().ok())
+            .unwrap_or(true);
+
+        // Parse optional account data slices as offset:length pairs
+        // Default to empty, which delivers full account data
+        let account_data_slice = env::var("ACCOUNT_DATA_SLICE")
+            .ok()
+            .filter(|val| !val.trim().is_empty())
+            .map(|val| Self::parse_data_slices(&val))
+            .transpose()?
+            .unwrap_or_default();
+
         // Validate target account is a valid base58 string
-        Self::validate_base58_address(&target_account)?;
+        if let Some(target_account) = &target_account {
+            Self::validate_base58_address(target_account)?;
+        }
 
-        // Validate gRPC endpoint URL has correct protocol scheme
-        Self::validate_grpc_url(&grpc_endpoint)?;
+        // Validate gRPC endpoint URLs have correct protocol scheme
+        for endpoint in &grpc_endpoints {
+            Self::validate_grpc_url(endpoint)?;
+        }
 
         Ok(Self {
-            grpc_endpoint,
-            grpc_token,
+            grpc_endpoints,
+            grpc_tokens,
             rpc_http_url,
             target_account,
             database_url,
             log_level,
             metrics_port,
             include_failed_transactions,
+            dual_commitment,
+            persist_only_finalized,
+            account_labels,
+            per_account_metrics,
+            max_balance_changes,
+            stream_commitment,
+            fetch_commitment,
+            confirmed_fallback_after,
+            slot_commitment,
+            balance_changes_target_only,
+            target_is_program,
+            record_updates_file,
+            min_fee_lamports,
+            min_token_change,
+            mint_allowlist,
+            store_sol_balance_changes,
+            burn_rate_percent,
+            processing_time_buckets,
+            db_time_buckets,
+            rpc_max_concurrency,
+            program_allowlist,
+            account_required,
+            retryable_error_patterns,
+            fetch_delay_ms,
+            subscribe_blocks_meta,
+            process_timeout_secs,
+            db_statement_timeout_ms,
+            slow_db_log_ms,
+            db_connect_attempts,
+            db_connect_retry_delay_ms,
+            reconcile_interval_secs,
+            reconcile_backfill,
+            db_maintenance_interval_secs,
+            db_maintenance_vacuum,
+            store_ui_amounts,
+            balance_changes_jsonb,
+            db_application_name,
+            track_finalization,
+            grpc_compression,
+            tokio_console,
+            sample_rate,
+            skip_zero_balance_change,
+            strict_target_only,
+            store_raw_json,
+            drain_timeout_secs,
+            max_reconnect_attempts,
+            grpc_max_conn_age_secs,
+            self_report_interval_secs,
+            metrics_push_url,
+            metrics_push_interval_secs,
+            kafka_brokers,
+            kafka_topic,
+            preflight_strict,
+            account_data_slice,
         })
     }
 
+    /// Parse the MIN_TOKEN_CHANGE JSON object of mint -> raw amount.
+    /// 
+    /// Every key must be valid base58 so a mistyped mint fails at startup instead of
+    /// silently never matching.
+    fn parse_min_token_change(json: &str) -> Result<HashMap<String, u64>, AppError> {
+        let thresholds: HashMap<String, u64> = serde_json::from_str(json)
+            .map_err(|e| AppError::Config(format!("Invalid MIN_TOKEN_CHANGE JSON: {}", e)))?;
+
+        for mint in thresholds.keys() {
+            bs58::decode(mint).into_vec().map_err(|e| {
+                AppError::Config(format!("Invalid base58 mint in MIN_TOKEN_CHANGE '{}': {}", mint, e))
+            })?;
+        }
+
+        Ok(thresholds)
+    }
+
+    /// Parse comma-separated `offset:length` byte ranges for ACCOUNT_DATA_SLICE.
+    /// 
+    /// Offsets and lengths must be non-negative integers, and lengths non-zero.
+    fn parse_data_slices(value: &str) -> Result<Vec<(u64, u64)>, AppError> {
+        value
+            .split(',')
+            .map(|slice| {
+                let slice = slice.trim();
+                let (offset, length) = slice.split_once(':').ok_or_else(|| {
+                    AppError::Config(format!(
+                        "Invalid ACCOUNT_DATA_SLICE entry '{}': expected offset:length",
+                        slice
+                    ))
+                })?;
+
+                let parse = |field: &str, value: &str| {
+                    value.trim().parse::<u64>().map_err(|e| {
+                        AppError::Config(format!(
+                            "Invalid {} '{}' in ACCOUNT_DATA_SLICE entry '{}': {}",
+                            field, value.trim(), slice, e
+                        ))
+                    })
+                };
+
+                let offset = parse("offset", offset)?;
+                let length = parse("length", length)?;
+                if length == 0 {
+                    return Err(AppError::Config(format!(
+                        "Invalid ACCOUNT_DATA_SLICE entry '{}': length must be greater than 0",
+                        slice
+                    )));
+                }
+
+                Ok((offset, length))
+            })
+            .collect()
+    }
+
+    /// Parse a comma-separated list of histogram bucket upper bounds.
+    /// 
+    /// Buckets must be finite and strictly ascending, as Prometheus requires.
+    fn parse_buckets(var: &str, value: &str) -> Result<Vec<f64>, AppError> {
+        let buckets = value
+            .split(',')
+            .map(|bucket| {
+                bucket.trim().parse::<f64>().map_err(|e| {
+                    AppError::Config(format!("Invalid bucket '{}' in {}: {}", bucket.trim(), var, e))
+                })
+            })
+            .collect::<Result<Vec<f64>, AppError>>()?;
+
+        if buckets.iter().any(|bucket| !bucket.is_finite()) {
+            return Err(AppError::Config(format!("{} buckets must be finite numbers", var)));
+        }
+
+        if buckets.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err(AppError::Config(format!(
+                "{} buckets must be sorted in strictly ascending order, got: {}",
+                var, value
+            )));
+        }
+
+        Ok(buckets)
+    }
+
+    /// Read a secret from the file named by `<var>_FILE`, falling back to `<var>` itself.
+    /// 
+    /// Trailing newlines are trimmed since secret files usually end with one. An
+    /// unreadable file is a startup error rather than a silent fallback.
+    fn env_or_file(var: &str) -> Result<Option<String>, AppError> {
+        let file_var = format!("{}_FILE", var);
+
+        match env::var(&file_var) {
+            Ok(path) => {
+                let contents = std::fs::read_to_string(&path).map_err(|e| {
+                    AppError::Config(format!("Failed to read {} '{}': {}", file_var, path, e))
+                })?;
+                Ok(Some(contents.trim_end_matches(['\n', '\r']).to_string()))
+            }
+            Err(_) => Ok(env::var(var).ok()),
+        }
+    }
+
     /// Validate that a string is a valid base58-encoded Solana address.
     fn validate_base58_address(address: &str) -> Result<(), AppError> {
         bs58::decode(address)
@@ -89,6 +862,49 @@ impl AppConfig {
         Ok(())
     }
 
+    /// Split GRPC_ENDPOINT and GRPC_TOKEN into matching per-endpoint lists.
+    /// 
+    /// A single token is used for every endpoint. Repeated endpoints are dropped,
+    /// keeping the first occurrence and its token.
+    fn parse_grpc_endpoints(
+        endpoints: &str,
+        tokens: &str,
+    ) -> Result<(Vec<String>, Vec<String>), AppError> {
+        let endpoints: Vec<&str> = endpoints
+            .split(',')
+            .map(str::trim)
+            .filter(|endpoint| !endpoint.is_empty())
+            .collect();
+
+        if endpoints.is_empty() {
+            return Err(AppError::Config("GRPC_ENDPOINT is empty".to_string()));
+        }
+
+        let tokens: Vec<&str> = tokens.split(',').map(str::trim).collect();
+        let tokens = match tokens.len() {
+            1 => vec![tokens[0]; endpoints.len()],
+            n if n == endpoints.len() => tokens,
+            n => {
+                return Err(AppError::Config(format!(
+                    "GRPC_TOKEN has {} comma-separated tokens but GRPC_ENDPOINT has {} endpoints; \
+                     use one token for all endpoints or one per endpoint",
+                    n,
+                    endpoints.len()
+                )));
+            }
+        };
+
+        // Listing an endpoint twice would only open a duplicate subscription
+        let mut pairs: Vec<(String, String)> = Vec::new();
+        for (endpoint, token) in endpoints.into_iter().zip(tokens) {
+            if !pairs.iter().any(|(existing, _)| existing == endpoint) {
+                pairs.push((endpoint.to_string(), token.to_string()));
+            }
+        }
+
+        Ok(pairs.into_iter().unzip())
+    }
+
     /// Validate that the endpoint URL uses the HTTPS protocol for gRPC.
     /// 
     /// gRPC connections require http:// or https:// protocol schemes.
@@ -106,3 +922,88 @@ impl AppConfig {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ascending_buckets() {
+        assert_eq!(
+            AppConfig::parse_buckets("DB_TIME_BUCKETS", "0.001, 0.01,0.1 ,1").unwrap(),
+            vec![0.001, 0.01, 0.1, 1.0]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_buckets() {
+        for value in ["0.1,abc", "0.1,inf", "0.1,0.1", "1,0.5", ""] {
+            assert!(
+                AppConfig::parse_buckets("DB_TIME_BUCKETS", value).is_err(),
+                "accepted {:?}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn shares_a_single_token_across_endpoints() {
+        let (endpoints, tokens) =
+            AppConfig::parse_grpc_endpoints("https://a:10000, https://b:10000", "secret").unwrap();
+        assert_eq!(endpoints, vec!["https://a:10000", "https://b:10000"]);
+        assert_eq!(tokens, vec!["secret", "secret"]);
+    }
+
+    #[test]
+    fn pairs_tokens_and_drops_repeated_endpoints() {
+        let (endpoints, tokens) = AppConfig::parse_grpc_endpoints(
+            "https://a:10000,https://b:10000,https://a:10000,",
+            "one,two,three",
+        )
+        .unwrap();
+        assert_eq!(endpoints, vec!["https://a:10000", "https://b:10000"]);
+        assert_eq!(tokens, vec!["one", "two"]);
+    }
+
+    #[test]
+    fn rejects_mismatched_token_count_and_empty_endpoints() {
+        let endpoints = "https://a:10000,https://b:10000,https://c:10000";
+        assert!(AppConfig::parse_grpc_endpoints(endpoints, "one,two").is_err());
+        assert!(AppConfig::parse_grpc_endpoints(" , ", "secret").is_err());
+    }
+
+    #[test]
+    fn parses_data_slices() {
+        assert_eq!(
+            AppConfig::parse_data_slices("0:32, 64 : 8").unwrap(),
+            vec![(0, 32), (64, 8)]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_data_slices() {
+        for value in ["32", "0:0", "-1:8", "0:abc", "0:32,"] {
+            assert!(AppConfig::parse_data_slices(value).is_err(), "accepted {:?}", value);
+        }
+    }
+
+    #[test]
+    fn parses_min_token_change() {
+        let thresholds = AppConfig::parse_min_token_change(
+            r#"{"EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v": 1000000}"#,
+        )
+        .unwrap();
+        assert_eq!(thresholds["EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"], 1_000_000);
+    }
+
+    #[test]
+    fn rejects_invalid_min_token_change() {
+        for json in [
+            r#"{"not-base58!": 1}"#,
+            r#"{"EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v": -1}"#,
+            "[1000]",
+        ] {
+            assert!(AppConfig::parse_min_token_change(json).is_err(), "accepted {}", json);
+        }
+    }
+}
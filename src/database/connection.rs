@@ -1,8 +1,12 @@
 use crate::error::AppError;
 use refinery::embed_migrations;
+use std::time::Duration;
 use tokio_postgres::Client;
 use tokio_postgres_rustls::MakeRustlsConnect;
-use tracing::info;
+use tracing::{info, warn};
+
+/// Upper bound on the wait between connection attempts at startup.
+const MAX_CONNECT_RETRY_DELAY: Duration = Duration::from_secs(30);
 
 // Embed migration files at compile time from the migrations directory
 embed_migrations!("migrations");
@@ -12,9 +16,33 @@ embed_migrations!("migrations");
 /// This establishes a connection to PostgreSQL using tokio-postgres.
 /// The connection is managed manually since tokio-postgres doesn't have
 /// a built-in connection pool like sqlx.
-pub async fn create_client(database_url: &str) -> Result<Client, AppError> {
+/// 
+/// No statement timeout is set here, so migrations run without one; apply it with
+/// `set_statement_timeout` once they are done.
+/// 
+/// `application_name` identifies the indexer's sessions in `pg_stat_activity`; an
+/// `application_name` given in the connection string takes precedence.
+/// 
+/// Connecting is tried up to `connect_attempts` times, waiting `retry_delay` after the
+/// first failure and doubling the wait after each further one (capped at 30 seconds),
+/// so the indexer can start alongside a database that is still booting. Failures
+/// `AppError::is_retryable` rejects, such as authentication errors, are not retried.
+pub async fn create_client(
+    database_url: &str,
+    application_name: &str,
+    connect_attempts: u32,
+    retry_delay: Duration,
+) -> Result<Client, AppError> {
     info!("Establishing database connection");
 
+    let mut pg_config = database_url
+        .parse::<tokio_postgres::Config>()
+        .map_err(|e| AppError::database("Invalid database URL", e))?;
+
+    if pg_config.get_application_name().is_none() {
+        pg_config.application_name(application_name);
+    }
+
     // Create TLS connector for secure database connections (required for Neon and other cloud providers)
     let mut root_store = rustls::RootCertStore::empty();
     root_store.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|ta| {
@@ -32,9 +60,35 @@ pub async fn create_client(database_url: &str) -> Result<Client, AppError> {
     
     let tls_connector = MakeRustlsConnect::new(tls_config);
 
-    let (client, connection) = tokio_postgres::connect(database_url, tls_connector)
-        .await
-        .map_err(|e| AppError::Database(format!("Failed to connect: {}", e)))?;
+    let connect_attempts = connect_attempts.max(1);
+    let mut delay = retry_delay;
+    let mut attempt = 1;
+
+    let (client, connection) = loop {
+        let error = match pg_config.connect(tls_connector.clone()).await {
+            Ok(connected) => break connected,
+            Err(e) => AppError::database(
+                format!("Failed to connect on attempt {} of {}", attempt, connect_attempts),
+                e,
+            ),
+        };
+
+        // Bad credentials or a missing database fail the same way on every attempt
+        if attempt >= connect_attempts || !error.is_retryable() {
+            return Err(error);
+        }
+
+        warn!(
+            attempt = attempt,
+            max_attempts = connect_attempts,
+            retry_in_ms = delay.as_millis() as u64,
+            error = %error,
+            "Database connection failed, retrying"
+        );
+        tokio::time::sleep(delay).await;
+        delay = (delay * 2).min(MAX_CONNECT_RETRY_DELAY);
+        attempt += 1;
+    };
 
     // Spawn the connection to run in the background
     tokio::spawn(async move {
@@ -43,7 +97,10 @@ pub async fn create_client(database_url: &str) -> Result<Client, AppError> {
         }
     });
 
-    info!("Database connection established successfully");
+    info!(
+        application_name = pg_config.get_application_name().unwrap_or_default(),
+        "Database connection established successfully"
+    );
 
     Ok(client)
 }
@@ -60,9 +117,59 @@ pub async fn run_migrations(client: &mut Client) -> Result<(), AppError> {
     migrations::runner()
         .run_async(client)
         .await
-        .map_err(|e| AppError::Database(format!("Migration failed: {}", e)))?;
+        .map_err(|e| AppError::database("Migration failed", e))?;
 
     info!("Database migrations completed successfully");
 
     Ok(())
 }
+
+/// Apply a non-zero `statement_timeout_ms` as the session's `statement_timeout`.
+/// 
+/// A stuck query then errors out instead of wedging the single shared connection.
+/// Call it after `run_migrations`, since an index build in a migration can take far
+/// longer than any query the indexer runs.
+pub async fn set_statement_timeout(client: &Client, statement_timeout_ms: u64) -> Result<(), AppError> {
+    if statement_timeout_ms == 0 {
+        return Ok(());
+    }
+
+    client
+        .batch_execute(&format!("SET statement_timeout = {}", statement_timeout_ms))
+        .await
+        .map_err(|e| AppError::database("Failed to set statement timeout", e))?;
+
+    info!(statement_timeout_ms = statement_timeout_ms, "Database statement timeout applied");
+
+    Ok(())
+}
+
+/// Whether account_balance_changes has its unique index.
+/// 
+/// The migration that adds it skips the index, rather than deleting rows, when
+/// duplicates are already stored; `migrations_optional/dedupe_balance_changes.sql`
+/// removes them and adds it.
+pub async fn has_unique_balance_changes(client: &Client) -> Result<bool, AppError> {
+    let row = client
+        .query_one("SELECT to_regclass('idx_balance_changes_unique') IS NOT NULL", &[])
+        .await
+        .map_err(|e| AppError::database("Failed to inspect balance change indexes", e))?;
+
+    Ok(row.get(0))
+}
+
+/// Whether the transactions table has been converted to a partitioned table.
+/// 
+/// The conversion is an opt-in script in `migrations_optional/` rather than a regular
+/// migration; the repository checks for it at startup to pick the matching unique key.
+pub async fn is_transactions_partitioned(client: &Client) -> Result<bool, AppError> {
+    let row = client
+        .query_one(
+            "SELECT COALESCE((SELECT relkind = 'p' FROM pg_class WHERE oid = to_regclass('transactions')), false)",
+            &[],
+        )
+        .await
+        .map_err(|e| AppError::database("Failed to inspect transactions table", e))?;
+
+    Ok(row.get(0))
+}
@@ -0,0 +1,94 @@
+use crate::database::store::TransactionStore;
+use crate::error::AppError;
+use chrono::{DateTime, Utc};
+use futures::stream::{self, Stream, TryStreamExt};
+use std::sync::Arc;
+
+/// Rows fetched per query while exporting.
+/// 
+/// Each page is a short query, so an export never holds the database connection
+/// long enough to stall ingestion.
+const EXPORT_PAGE_SIZE: u32 = 1000;
+
+/// Column names of the CSV export, in `TransactionRow::to_csv_record` order.
+pub const CSV_HEADER: &str = "signature,slot,block_time,fee,fee_payer,success,compute_units_consumed,transaction_size_bytes,fee_payer_sol_delta,tx_type\n";
+
+/// A stored transaction as exported for offline analysis.
+#[derive(Debug, Clone)]
+pub struct TransactionRow {
+    /// Database id, used to resume paging after the last exported row
+    pub id: i64,
+    pub signature: String,
+    pub slot: i64,
+    pub block_time: Option<DateTime<Utc>>,
+    pub fee: i64,
+    pub fee_payer: String,
+    pub success: bool,
+    pub compute_units_consumed: Option<i64>,
+    pub transaction_size_bytes: Option<i32>,
+    pub fee_payer_sol_delta: i64,
+    pub tx_type: String,
+}
+
+impl TransactionRow {
+    /// Format the row as one CSV record, including the trailing newline.
+    pub fn to_csv_record(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{},{},{},{}\n",
+            csv_field(&self.signature),
+            self.slot,
+            self.block_time.map(|t| t.to_rfc3339()).unwrap_or_default(),
+            self.fee,
+            csv_field(&self.fee_payer),
+            self.success,
+            self.compute_units_consumed.map(|v| v.to_string()).unwrap_or_default(),
+            self.transaction_size_bytes.map(|v| v.to_string()).unwrap_or_default(),
+            self.fee_payer_sol_delta,
+            csv_field(&self.tx_type),
+        )
+    }
+}
+
+/// Stream every transaction with a block time at or after `since`, oldest id first.
+/// 
+/// Rows are read a page at a time with keyset pagination on the id, so memory use
+/// stays bounded however large the export is.
+pub fn transaction_rows<S: TransactionStore>(
+    repository: Arc<S>,
+    since: DateTime<Utc>,
+) -> impl Stream<Item = Result<TransactionRow, AppError>> + Send {
+    stream::try_unfold(Some(0i64), move |after_id| {
+        let repository = repository.clone();
+        async move {
+            let Some(after_id) = after_id else {
+                return Ok::<_, AppError>(None);
+            };
+
+            let page = repository
+                .export_transactions(since, after_id, EXPORT_PAGE_SIZE)
+                .await?;
+
+            // A short page is the last one
+            let next = match page.last() {
+                Some(last) if page.len() == EXPORT_PAGE_SIZE as usize => Some(last.id),
+                _ => None,
+            };
+
+            if page.is_empty() {
+                Ok(None)
+            } else {
+                Ok(Some((stream::iter(page.into_iter().map(Ok)), next)))
+            }
+        }
+    })
+    .try_flatten()
+}
+
+/// Quote a text field if it contains a delimiter, quote, or newline.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,46 @@
+use crate::solana::models::ParsedTransaction;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tracing::warn;
+
+/// Transactions buffered per subscriber before a slow one starts missing events.
+pub const DEFAULT_FEED_CAPACITY: usize = 1024;
+
+/// Broadcast of newly inserted transactions, serialized as JSON.
+/// 
+/// Stores publish here after a row is first written, and the `/stream` endpoint
+/// forwards each event to its Server-Sent Events clients. The channel is bounded:
+/// a subscriber that falls `capacity` events behind skips the oldest ones instead
+/// of holding up ingestion.
+pub struct TransactionFeed {
+    sender: broadcast::Sender<Arc<str>>,
+}
+
+impl TransactionFeed {
+    /// Create a feed buffering up to `capacity` events per subscriber.
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self { sender }
+    }
+
+    /// Publish a newly inserted transaction to current subscribers.
+    pub fn publish(&self, tx: &ParsedTransaction) {
+        // Skip serializing when nobody is listening
+        if self.sender.receiver_count() == 0 {
+            return;
+        }
+
+        match serde_json::to_string(tx) {
+            // Fails only when every subscriber disconnected in the meantime
+            Ok(json) => {
+                let _ = self.sender.send(json.into());
+            }
+            Err(e) => warn!(signature = %tx.signature, error = %e, "Failed to serialize transaction for the feed"),
+        }
+    }
+
+    /// Subscribe to transactions published from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<str>> {
+        self.sender.subscribe()
+    }
+}
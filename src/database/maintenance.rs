@@ -0,0 +1,32 @@
+use crate::database::store::TransactionStore;
+use std::sync::Arc;
+use tokio::time::{Duration, Instant, MissedTickBehavior};
+use tracing::{error, info};
+
+/// Periodically run ANALYZE, and VACUUM when `vacuum` is set, on the indexer's tables.
+/// 
+/// Meant for deployments where autovacuum isn't tuned for the insert rate, so
+/// planner statistics go stale and queries slow down over time. The first run
+/// happens one `interval` after startup. A failed run is logged and retried at the
+/// next interval rather than stopping the indexer.
+pub async fn run_maintenance<S: TransactionStore>(repository: Arc<S>, interval: Duration, vacuum: bool) {
+    let mut ticker = tokio::time::interval_at(Instant::now() + interval, interval);
+    // A slow VACUUM shouldn't be followed by a burst of catch-up runs
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        ticker.tick().await;
+
+        let started = Instant::now();
+        info!(vacuum = vacuum, "Running database maintenance");
+
+        match repository.run_maintenance(vacuum).await {
+            Ok(()) => info!(
+                vacuum = vacuum,
+                duration_ms = started.elapsed().as_millis() as u64,
+                "Database maintenance complete"
+            ),
+            Err(e) => error!(error = %e, vacuum = vacuum, "Database maintenance failed"),
+        }
+    }
+}
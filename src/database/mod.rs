@@ -1,2 +1,8 @@
 pub mod connection;
+pub mod export;
+pub mod feed;
+pub mod maintenance;
 pub mod repository;
+pub mod stats;
+pub mod sqlite;
+pub mod store;
@@ -1,10 +1,27 @@
+use crate::database::export::TransactionRow;
+use crate::database::feed::TransactionFeed;
+use crate::database::store::TransactionStore;
 use crate::error::AppError;
-use crate::solana::models::{BalanceChange, ParsedTransaction};
+use crate::metrics;
+use crate::solana::models::{BalanceChange, BlockMeta, ParsedTransaction};
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
 use std::sync::Arc;
 use tokio::sync::Mutex;
-use tokio_postgres::Client;
+use tokio_postgres::error::SqlState;
+use tokio_postgres::{Client, GenericClient};
 use tracing::{debug, warn};
 
+/// Tables the periodic maintenance task analyzes (and optionally vacuums).
+const MAINTAINED_TABLES: &[&str] = &[
+    "transactions",
+    "account_balance_changes",
+    "missed_transactions",
+    "raw_transactions",
+    "blocks",
+    "watched_accounts",
+];
+
 /// Repository for persisting transaction data to PostgreSQL.
 /// 
 /// This struct encapsulates all database operations related to transactions
@@ -13,6 +30,11 @@ use tracing::{debug, warn};
 #[derive(Clone)]
 pub struct TransactionRepository {
     client: Arc<Mutex<Client>>,
+    conflict_target: &'static str,
+    feed: Option<Arc<TransactionFeed>>,
+    balance_changes_jsonb: bool,
+    /// Separate session without a statement timeout for ANALYZE and VACUUM
+    maintenance_client: Option<Arc<Mutex<Client>>>,
 }
 
 impl TransactionRepository {
@@ -20,7 +42,60 @@ impl TransactionRepository {
     pub fn new(client: Client) -> Self {
         Self {
             client: Arc::new(Mutex::new(client)),
+            conflict_target: "signature",
+            feed: None,
+            balance_changes_jsonb: false,
+            maintenance_client: None,
+        }
+    }
+
+    /// Publish newly inserted transactions to `feed`.
+    pub fn with_feed(mut self, feed: Arc<TransactionFeed>) -> Self {
+        self.feed = Some(feed);
+        self
+    }
+
+    /// Target the (signature, fee_payer) unique key in transaction inserts.
+    /// 
+    /// Needed once the transactions table has been converted by the opt-in
+    /// partitioning script, whose unique keys must include the fee_payer partition key.
+    pub fn with_partitioned_transactions(mut self, partitioned: bool) -> Self {
+        if partitioned {
+            self.conflict_target = "signature, fee_payer";
         }
+        self
+    }
+
+    /// Store balance changes as a JSONB array on the transaction row instead of in
+    /// `account_balance_changes`.
+    /// 
+    /// This saves one insert per balance change and keeps each transaction in a single
+    /// row, at the cost of per-account queries: those must expand the arrays with
+    /// `jsonb_array_elements` and can't use the table's account index.
+    pub fn with_balance_changes_jsonb(mut self, enabled: bool) -> Self {
+        self.balance_changes_jsonb = enabled;
+        self
+    }
+
+    /// Run maintenance on `client`, a session of its own with no statement timeout.
+    /// 
+    /// VACUUM on a large table can take far longer than the shared connection's
+    /// statement timeout allows, and writes would wait on it meanwhile.
+    pub fn with_maintenance_client(mut self, client: Client) -> Self {
+        self.maintenance_client = Some(Arc::new(Mutex::new(client)));
+        self
+    }
+
+    /// Serialize a transaction's balance changes for the `balance_changes` column,
+    /// or None when they are stored in `account_balance_changes`.
+    fn balance_changes_json(&self, tx: &ParsedTransaction) -> Result<Option<String>, AppError> {
+        if !self.balance_changes_jsonb {
+            return Ok(None);
+        }
+
+        serde_json::to_string(&tx.balance_changes)
+            .map(Some)
+            .map_err(|e| AppError::ParseError(format!("Failed to serialize balance changes: {}", e)))
     }
 
     /// Insert a transaction into the database.
@@ -30,28 +105,55 @@ impl TransactionRepository {
     /// This ensures idempotency in case we receive duplicate transaction events.
     /// 
     /// Returns the database ID of the inserted transaction, or None if it was a duplicate.
+    // Public API; the stream handler inserts through insert_complete_transaction_detailed
+    #[allow(dead_code)]
     pub async fn insert_transaction(
         &self,
         tx: &ParsedTransaction,
     ) -> Result<Option<i64>, AppError> {
         let client = self.client.lock().await;
+        self.insert_transaction_row(&*client, tx).await
+    }
+
+    /// Run the transaction insert on `client`, which may be an open database transaction.
+    async fn insert_transaction_row<C: GenericClient + Sync>(
+        &self,
+        client: &C,
+        tx: &ParsedTransaction,
+    ) -> Result<Option<i64>, AppError> {
+        let balance_changes = self.balance_changes_json(tx)?;
 
         let result = client
             .query_opt(
-                r#"
-                INSERT INTO transactions (
-                    signature,
-                    slot,
-                    block_time,
-                    fee,
-                    fee_payer,
-                    success,
-                    compute_units_consumed
-                )
-                VALUES ($1, $2, $3, $4, $5, $6, $7)
-                ON CONFLICT (signature) DO NOTHING
-                RETURNING id
-                "#,
+                &format!(
+                    r#"
+                    INSERT INTO transactions (
+                        signature,
+                        slot,
+                        block_time,
+                        fee,
+                        fee_payer,
+                        success,
+                        compute_units_consumed,
+                        transaction_size_bytes,
+                        uses_durable_nonce,
+                        fee_payer_sol_delta,
+                        tx_type,
+                        return_data_program_id,
+                        return_data,
+                        requested_heap_bytes,
+                        instruction_count,
+                        inner_instruction_count,
+                        sol_burned,
+                        balance_changes,
+                        confirmed_only
+                    )
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18::TEXT::JSONB, $19)
+                    ON CONFLICT ({conflict_target}) DO NOTHING
+                    RETURNING id
+                    "#,
+                    conflict_target = self.conflict_target,
+                ),
                 &[
                     &tx.signature,
                     &(tx.slot as i64),
@@ -60,10 +162,22 @@ impl TransactionRepository {
                     &tx.fee_payer,
                     &tx.success,
                     &tx.compute_units_consumed.map(|u| u as i64),
+                    &tx.transaction_size_bytes.map(|b| b as i32),
+                    &tx.uses_durable_nonce,
+                    &tx.fee_payer_sol_delta,
+                    &tx.tx_type.as_str(),
+                    &tx.return_data.as_ref().map(|r| r.program_id.as_str()),
+                    &tx.return_data.as_ref().map(|r| r.data.as_str()),
+                    &tx.requested_heap_bytes.map(|b| b as i32),
+                    &(tx.instruction_count as i32),
+                    &(tx.inner_instruction_count as i32),
+                    &(tx.sol_burned as i64),
+                    &balance_changes,
+                    &tx.confirmed_only,
                 ],
             )
             .await
-            .map_err(|e| AppError::Database(format!("Failed to insert transaction: {}", e)))?;
+            .map_err(|e| AppError::database("Failed to insert transaction", e))?;
 
         match result {
             Some(row) => {
@@ -85,6 +199,102 @@ impl TransactionRepository {
         }
     }
 
+    /// Insert a transaction, or correct the existing row for the same signature.
+    /// 
+    /// This is used by the Finalized stream when running alongside a Confirmed stream.
+    /// A row written earlier at Confirmed is updated in place: a missing block_time is
+    /// filled in and the success flag is overwritten with the finalized outcome, which
+    /// corrects rows whose status changed after a reorg.
+    /// 
+    /// Returns the database ID and whether the row was newly inserted (as opposed to updated).
+    pub async fn upsert_transaction(
+        &self,
+        tx: &ParsedTransaction,
+    ) -> Result<(i64, bool), AppError> {
+        let balance_changes = self.balance_changes_json(tx)?;
+        let client = self.client.lock().await;
+
+        let row = client
+            .query_one(
+                &format!(
+                    r#"
+                    INSERT INTO transactions (
+                        signature,
+                        slot,
+                        block_time,
+                        fee,
+                        fee_payer,
+                        success,
+                        compute_units_consumed,
+                        transaction_size_bytes,
+                        uses_durable_nonce,
+                        fee_payer_sol_delta,
+                        tx_type,
+                        return_data_program_id,
+                        return_data,
+                        requested_heap_bytes,
+                        instruction_count,
+                        inner_instruction_count,
+                        sol_burned,
+                        balance_changes,
+                        confirmed_only
+                    )
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18::TEXT::JSONB, $19)
+                    ON CONFLICT ({conflict_target}) DO UPDATE SET
+                        slot = EXCLUDED.slot,
+                        block_time = COALESCE(EXCLUDED.block_time, transactions.block_time),
+                        success = EXCLUDED.success,
+                        compute_units_consumed = COALESCE(
+                            EXCLUDED.compute_units_consumed,
+                            transactions.compute_units_consumed
+                        ),
+                        transaction_size_bytes = COALESCE(
+                            EXCLUDED.transaction_size_bytes,
+                            transactions.transaction_size_bytes
+                        ),
+                        confirmed_only = EXCLUDED.confirmed_only
+                    RETURNING id, (xmax = 0) AS inserted
+                    "#,
+                    conflict_target = self.conflict_target,
+                ),
+                &[
+                    &tx.signature,
+                    &(tx.slot as i64),
+                    &tx.block_time,
+                    &(tx.fee as i64),
+                    &tx.fee_payer,
+                    &tx.success,
+                    &tx.compute_units_consumed.map(|u| u as i64),
+                    &tx.transaction_size_bytes.map(|b| b as i32),
+                    &tx.uses_durable_nonce,
+                    &tx.fee_payer_sol_delta,
+                    &tx.tx_type.as_str(),
+                    &tx.return_data.as_ref().map(|r| r.program_id.as_str()),
+                    &tx.return_data.as_ref().map(|r| r.data.as_str()),
+                    &tx.requested_heap_bytes.map(|b| b as i32),
+                    &(tx.instruction_count as i32),
+                    &(tx.inner_instruction_count as i32),
+                    &(tx.sol_burned as i64),
+                    &balance_changes,
+                    &tx.confirmed_only,
+                ],
+            )
+            .await
+            .map_err(|e| AppError::database("Failed to upsert transaction", e))?;
+
+        let id: i64 = row.get(0);
+        let inserted: bool = row.get(1);
+
+        debug!(
+            signature = %tx.signature,
+            transaction_id = id,
+            inserted = inserted,
+            "Upserted transaction into database"
+        );
+
+        Ok((id, inserted))
+    }
+
     /// Insert balance changes associated with a transaction.
     /// 
     /// This inserts all balance changes for a given transaction ID. Balance changes
@@ -101,8 +311,53 @@ impl TransactionRepository {
         let client = self.client.lock().await;
 
         for change in changes {
-            let result = client
-                .execute(
+            let result = Self::insert_balance_change(&*client, transaction_id, change, false).await;
+
+            match result {
+                Ok(_) => {}
+                // The change is already stored, e.g. from an earlier partial write
+                Err(e) if e.code() == Some(&SqlState::UNIQUE_VIOLATION) => {
+                    debug!(
+                        transaction_id = transaction_id,
+                        account = %change.account_address,
+                        "Balance change already recorded, skipping"
+                    );
+                }
+                Err(e) => {
+                    metrics::BALANCE_CHANGE_INSERT_FAILURES.inc();
+                    warn!(
+                        transaction_id = transaction_id,
+                        error = %e,
+                        "Failed to insert balance change, continuing with others"
+                    );
+                }
+            }
+        }
+
+        debug!(
+            transaction_id = transaction_id,
+            balance_changes_count = changes.len(),
+            "Inserted balance changes"
+        );
+
+        Ok(())
+    }
+
+    /// Insert a single balance change row on `client`, which may be an open database transaction.
+    /// 
+    /// With `skip_conflicts` a change that is already stored affects 0 rows instead of
+    /// raising a unique violation, which would abort an open database transaction.
+    async fn insert_balance_change<C: GenericClient + Sync>(
+        client: &C,
+        transaction_id: i64,
+        change: &BalanceChange,
+        skip_conflicts: bool,
+    ) -> Result<u64, tokio_postgres::Error> {
+        let on_conflict = if skip_conflicts { "ON CONFLICT DO NOTHING" } else { "" };
+
+        client
+            .execute(
+                &format!(
                     r#"
                     INSERT INTO account_balance_changes (
                         transaction_id,
@@ -110,53 +365,621 @@ impl TransactionRepository {
                         mint_address,
                         pre_balance,
                         post_balance,
-                        balance_delta
+                        pre_ui_amount,
+                        post_ui_amount,
+                        token_program,
+                        is_wrapped_sol
                     )
-                    VALUES ($1, $2, $3, $4, $5, $6)
-                    "#,
-                    &[
-                        &transaction_id,
-                        &change.account_address,
-                        &change.mint_address,
-                        &change.pre_balance,
-                        &change.post_balance,
-                        &change.delta(),
-                    ],
-                )
-                .await;
+                    VALUES ($1, $2, $3, $4, $5, $6::TEXT::NUMERIC, $7::TEXT::NUMERIC, $8, $9)
+                    {on_conflict}
+                    "#
+                ),
+                &[
+                    &transaction_id,
+                    &change.account_address,
+                    &change.mint_address,
+                    &change.pre_balance,
+                    &change.post_balance,
+                    &change.pre_ui_amount,
+                    &change.post_ui_amount,
+                    &change.token_program,
+                    &change.is_wrapped_sol,
+                ],
+            )
+            .await
+    }
 
-            if let Err(e) = result {
-                warn!(
-                    transaction_id = transaction_id,
-                    error = %e,
-                    "Failed to insert balance change, continuing with others"
-                );
+    /// Insert a complete parsed transaction with all its balance changes.
+    /// 
+    /// This is a convenience wrapper around `insert_complete_transaction_detailed`
+    /// for callers that don't need the new row's id.
+    #[allow(dead_code)]
+    pub async fn insert_complete_transaction(
+        &self,
+        tx: &ParsedTransaction,
+    ) -> Result<(), AppError> {
+        self.insert_complete_transaction_detailed(tx).await.map(|_| ())
+    }
+
+    /// Insert a transaction and its balance changes in a single database transaction.
+    /// 
+    /// Returns the new row id and the number of balance changes written, or None when
+    /// the signature was already stored. Unlike `insert_balance_changes`, a balance
+    /// change that fails to insert rolls back the whole write, so a transaction is never
+    /// stored with only some of its changes. With JSONB storage the changes are written
+    /// with the transaction row and all of them are counted.
+    pub async fn insert_complete_transaction_detailed(
+        &self,
+        tx: &ParsedTransaction,
+    ) -> Result<Option<(i64, usize)>, AppError> {
+        let mut client = self.client.lock().await;
+        let db_tx = client
+            .transaction()
+            .await
+            .map_err(|e| AppError::database("Failed to begin transaction", e))?;
+
+        // Dropping the open transaction on a duplicate or error rolls it back
+        let transaction_id = match self.insert_transaction_row(&db_tx, tx).await? {
+            Some(id) => id,
+            None => return Ok(None),
+        };
+
+        let balance_changes_count = if self.balance_changes_jsonb {
+            tx.balance_changes.len()
+        } else {
+            let mut balance_changes_written = 0;
+            for change in &tx.balance_changes {
+                balance_changes_written += Self::insert_balance_change(&db_tx, transaction_id, change, true)
+                    .await
+                    .map_err(|e| {
+                        metrics::BALANCE_CHANGE_INSERT_FAILURES.inc();
+                        AppError::database("Failed to insert balance change", e)
+                    })? as usize;
             }
-        }
+            balance_changes_written
+        };
+
+        db_tx
+            .commit()
+            .await
+            .map_err(|e| AppError::database("Failed to commit transaction", e))?;
 
         debug!(
             transaction_id = transaction_id,
-            balance_changes_count = changes.len(),
+            balance_changes_count = balance_changes_count,
             "Inserted balance changes"
         );
 
-        Ok(())
+        if let Some(feed) = &self.feed {
+            feed.publish(tx);
+        }
+
+        Ok(Some((transaction_id, balance_changes_count)))
     }
 
-    /// Insert a complete parsed transaction with all its balance changes.
+    /// Upsert a complete parsed transaction with all its balance changes.
     /// 
-    /// This is a convenience method that combines transaction insertion with
-    /// balance change insertion in a single operation. It ensures data consistency
-    /// by using the returned transaction ID to link balance changes.
-    pub async fn insert_complete_transaction(
+    /// Balance changes are only written when the transaction row is new. When the row
+    /// already existed, its balance changes were recorded by the earlier insert (the
+    /// update leaves a JSONB `balance_changes` column untouched for the same reason).
+    /// 
+    /// Returns whether the row was newly inserted rather than corrected.
+    pub async fn upsert_complete_transaction(
         &self,
         tx: &ParsedTransaction,
+    ) -> Result<bool, AppError> {
+        let (transaction_id, inserted) = self.upsert_transaction(tx).await?;
+
+        if inserted {
+            if !self.balance_changes_jsonb {
+                self.insert_balance_changes(transaction_id, &tx.balance_changes)
+                    .await?;
+            }
+
+            if let Some(feed) = &self.feed {
+                feed.publish(tx);
+            }
+        }
+
+        Ok(inserted)
+    }
+
+    /// Count persisted transactions by outcome since the given time.
+    /// 
+    /// Unlike the stream counters, this reflects what is actually stored and
+    /// survives restarts. Returns `(successful, failed)`.
+    pub async fn count_by_success(
+        &self,
+        since: DateTime<Utc>,
+    ) -> Result<(u64, u64), AppError> {
+        let client = self.client.lock().await;
+
+        let row = client
+            .query_one(
+                r#"
+                SELECT
+                    COUNT(*) FILTER (WHERE success),
+                    COUNT(*) FILTER (WHERE NOT success)
+                FROM transactions
+                WHERE block_time >= $1
+                "#,
+                &[&since],
+            )
+            .await
+            .map_err(|e| AppError::database("Failed to count transactions", e))?;
+
+        let successful: i64 = row.get(0);
+        let failed: i64 = row.get(1);
+
+        Ok((successful as u64, failed as u64))
+    }
+
+    /// Record a signature that could not be fetched from RPC.
+    /// 
+    /// Missed signatures are kept in the missed_transactions table with a reason
+    /// (e.g. "beyond_retention") so they can be backfilled from an archival node
+    /// later instead of failing on every attempt. Re-recording a signature
+    /// refreshes its reason and error message.
+    pub async fn record_missed_transaction(
+        &self,
+        signature: &str,
+        reason: &str,
+        error_message: &str,
     ) -> Result<(), AppError> {
-        if let Some(transaction_id) = self.insert_transaction(tx).await? {
-            self.insert_balance_changes(transaction_id, &tx.balance_changes)
-                .await?;
+        let client = self.client.lock().await;
+
+        client
+            .execute(
+                r#"
+                INSERT INTO missed_transactions (signature, reason, error_message)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (signature) DO UPDATE SET
+                    reason = EXCLUDED.reason,
+                    error_message = EXCLUDED.error_message,
+                    recorded_at = NOW()
+                "#,
+                &[&signature, &reason, &error_message],
+            )
+            .await
+            .map_err(|e| AppError::database("Failed to record missed transaction", e))?;
+
+        debug!(
+            signature = %signature,
+            reason = %reason,
+            "Recorded missed transaction"
+        );
+
+        Ok(())
+    }
+
+    /// List the addresses in the watched_accounts table, oldest first.
+    /// 
+    /// This is the database-managed watchlist used when TARGET_ACCOUNT is unset.
+    pub async fn list_watched_accounts(&self) -> Result<Vec<String>, AppError> {
+        let client = self.client.lock().await;
+
+        let rows = client
+            .query("SELECT address FROM watched_accounts ORDER BY id", &[])
+            .await
+            .map_err(|e| AppError::database("Failed to list watched accounts", e))?;
+
+        Ok(rows.iter().map(|row| row.get(0)).collect())
+    }
+
+    /// Insert block metadata from the blocks_meta subscription.
+    /// 
+    /// Blocks are keyed by slot; a block seen again (e.g. after a reconnect) is skipped.
+    pub async fn insert_block_meta(&self, block: &BlockMeta) -> Result<(), AppError> {
+        let client = self.client.lock().await;
+
+        client
+            .execute(
+                r#"
+                INSERT INTO blocks (
+                    slot,
+                    blockhash,
+                    parent_slot,
+                    block_height,
+                    block_time,
+                    leader,
+                    rewards_lamports
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                ON CONFLICT (slot) DO NOTHING
+                "#,
+                &[
+                    &(block.slot as i64),
+                    &block.blockhash,
+                    &(block.parent_slot as i64),
+                    &block.block_height.map(|h| h as i64),
+                    &block.block_time,
+                    &block.leader,
+                    &block.rewards_lamports,
+                ],
+            )
+            .await
+            .map_err(|e| AppError::database("Failed to insert block", e))?;
+
+        debug!(slot = block.slot, "Inserted block into database");
+
+        Ok(())
+    }
+
+    /// Return which of the given signatures are already stored.
+    /// 
+    /// Used by the reconcile job to find on-chain signatures the indexer never persisted.
+    pub async fn find_existing_signatures(
+        &self,
+        signatures: &[String],
+    ) -> Result<HashSet<String>, AppError> {
+        let client = self.client.lock().await;
+
+        let rows = client
+            .query(
+                "SELECT signature FROM transactions WHERE signature = ANY($1)",
+                &[&signatures],
+            )
+            .await
+            .map_err(|e| AppError::database("Failed to look up signatures", e))?;
+
+        Ok(rows.iter().map(|row| row.get(0)).collect())
+    }
+
+    /// Store the raw RPC response of a transaction in `raw_transactions`.
+    /// 
+    /// The JSON is passed as text and cast on the server, since the client isn't
+    /// built with serde_json support for JSONB parameters.
+    pub async fn insert_raw_transaction(&self, signature: &str, raw_json: &str) -> Result<(), AppError> {
+        let client = self.client.lock().await;
+
+        client
+            .execute(
+                r#"
+                INSERT INTO raw_transactions (signature, raw_json)
+                VALUES ($1, $2::TEXT::JSONB)
+                ON CONFLICT (signature) DO NOTHING
+                "#,
+                &[&signature, &raw_json],
+            )
+            .await
+            .map_err(|e| AppError::database("Failed to insert raw transaction", e))?;
+
+        Ok(())
+    }
+
+    /// Set `finalized_at` on a stored transaction that isn't marked finalized yet.
+    /// 
+    /// Returns false when the signature isn't stored (yet) or was already marked.
+    pub async fn mark_finalized(&self, signature: &str) -> Result<bool, AppError> {
+        let client = self.client.lock().await;
+
+        let updated = client
+            .execute(
+                "UPDATE transactions SET finalized_at = NOW() WHERE signature = $1 AND finalized_at IS NULL",
+                &[&signature],
+            )
+            .await
+            .map_err(|e| AppError::database("Failed to mark transaction finalized", e))?;
+
+        Ok(updated > 0)
+    }
+
+    /// Fetch one page of transactions for the CSV export.
+    /// 
+    /// Keyset pagination on the id keeps each query short, so the shared client
+    /// isn't held for the duration of a large export.
+    pub async fn export_transactions(
+        &self,
+        since: DateTime<Utc>,
+        after_id: i64,
+        limit: u32,
+    ) -> Result<Vec<TransactionRow>, AppError> {
+        let client = self.client.lock().await;
+
+        let rows = client
+            .query(
+                r#"
+                SELECT
+                    id,
+                    signature,
+                    slot,
+                    block_time,
+                    fee,
+                    fee_payer,
+                    success,
+                    compute_units_consumed,
+                    transaction_size_bytes,
+                    fee_payer_sol_delta,
+                    tx_type
+                FROM transactions
+                WHERE block_time >= $1 AND id > $2
+                ORDER BY id
+                LIMIT $3
+                "#,
+                &[&since, &after_id, &(limit as i64)],
+            )
+            .await
+            .map_err(|e| AppError::database("Failed to export transactions", e))?;
+
+        Ok(rows
+            .iter()
+            .map(|row| TransactionRow {
+                id: row.get(0),
+                signature: row.get(1),
+                slot: row.get(2),
+                block_time: row.get(3),
+                fee: row.get(4),
+                fee_payer: row.get(5),
+                success: row.get(6),
+                compute_units_consumed: row.get(7),
+                transaction_size_bytes: row.get(8),
+                fee_payer_sol_delta: row.get(9),
+                tx_type: row.get(10),
+            })
+            .collect())
+    }
+
+    /// Attribute a stored transaction to the leader of its slot via the slot_leaders view.
+    pub async fn transaction_leader(&self, signature: &str) -> Result<Option<(i64, String)>, AppError> {
+        let client = self.client.lock().await;
+
+        let row = client
+            .query_opt(
+                r#"
+                SELECT t.slot, sl.leader
+                FROM transactions t
+                JOIN slot_leaders sl ON sl.slot = t.slot
+                WHERE t.signature = $1
+                LIMIT 1
+                "#,
+                &[&signature],
+            )
+            .await
+            .map_err(|e| AppError::database("Failed to look up transaction leader", e))?;
+
+        Ok(row.map(|row| (row.get(0), row.get(1))))
+    }
+
+    /// Return the post-balance of an account for one mint (None = native SOL) over time.
+    /// 
+    /// With JSONB storage the arrays of every transaction in the window are expanded,
+    /// so this scans far more rows than the indexed table lookup.
+    pub async fn balance_history(
+        &self,
+        account: &str,
+        mint: Option<&str>,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<(DateTime<Utc>, i64)>, AppError> {
+        let query = if self.balance_changes_jsonb {
+            r#"
+            SELECT t.block_time, (bc.change->>'post_balance')::BIGINT
+            FROM transactions t
+            CROSS JOIN LATERAL jsonb_array_elements(t.balance_changes) WITH ORDINALITY AS bc(change, position)
+            WHERE bc.change->>'account_address' = $1
+                AND bc.change->>'mint_address' IS NOT DISTINCT FROM $2
+                AND t.block_time >= $3
+            ORDER BY t.block_time, t.slot, bc.position
+            "#
+        } else {
+            r#"
+            SELECT t.block_time, bc.post_balance
+            FROM account_balance_changes bc
+            JOIN transactions t ON t.id = bc.transaction_id
+            WHERE bc.account_address = $1
+                AND bc.mint_address IS NOT DISTINCT FROM $2
+                AND t.block_time >= $3
+            ORDER BY t.block_time, t.slot, bc.id
+            "#
+        };
+
+        let client = self.client.lock().await;
+
+        let rows = client
+            .query(query, &[&account, &mint, &since])
+            .await
+            .map_err(|e| AppError::database("Failed to load balance history", e))?;
+
+        Ok(rows.iter().map(|row| (row.get(0), row.get(1))).collect())
+    }
+
+    /// Run ANALYZE (or VACUUM ANALYZE) on each of the indexer's tables.
+    /// 
+    /// Tables are processed one statement at a time, since VACUUM can't run inside a
+    /// transaction block. Statements run on the maintenance client when one is set.
+    /// Otherwise they run on the shared client with its statement timeout lifted, and
+    /// writes wait while each statement runs.
+    pub async fn run_maintenance(&self, vacuum: bool) -> Result<(), AppError> {
+        let command = if vacuum { "VACUUM ANALYZE" } else { "ANALYZE" };
+
+        for table in MAINTAINED_TABLES {
+            let statement = format!("{} {}", command, table);
+
+            let result = match &self.maintenance_client {
+                Some(client) => client.lock().await.batch_execute(&statement).await,
+                None => without_statement_timeout(&*self.client.lock().await, &statement).await,
+            };
+            result.map_err(|e| AppError::database(format!("Failed to run {} on {}", command, table), e))?;
         }
 
         Ok(())
     }
 }
+
+/// Run `statement` with the session's statement timeout lifted, restoring it afterwards.
+/// 
+/// VACUUM can't run in a transaction block, so `SET LOCAL` isn't an option; the
+/// previous value is read back and set again instead, even when `statement` fails.
+async fn without_statement_timeout(client: &Client, statement: &str) -> Result<(), tokio_postgres::Error> {
+    let timeout: String = client.query_one("SHOW statement_timeout", &[]).await?.get(0);
+    client.batch_execute("SET statement_timeout = 0").await?;
+
+    let result = client.batch_execute(statement).await;
+
+    client
+        .batch_execute(&format!("SET statement_timeout = '{}'", timeout))
+        .await?;
+    result
+}
+
+impl TransactionStore for TransactionRepository {
+    async fn insert_complete_transaction_detailed(
+        &self,
+        tx: &ParsedTransaction,
+    ) -> Result<Option<(i64, usize)>, AppError> {
+        TransactionRepository::insert_complete_transaction_detailed(self, tx).await
+    }
+
+    async fn upsert_complete_transaction(&self, tx: &ParsedTransaction) -> Result<bool, AppError> {
+        TransactionRepository::upsert_complete_transaction(self, tx).await
+    }
+
+    async fn record_missed_transaction(
+        &self,
+        signature: &str,
+        reason: &str,
+        error_message: &str,
+    ) -> Result<(), AppError> {
+        TransactionRepository::record_missed_transaction(self, signature, reason, error_message).await
+    }
+
+    async fn insert_raw_transaction(&self, signature: &str, raw_json: &str) -> Result<(), AppError> {
+        TransactionRepository::insert_raw_transaction(self, signature, raw_json).await
+    }
+
+    async fn count_by_success(&self, since: DateTime<Utc>) -> Result<(u64, u64), AppError> {
+        TransactionRepository::count_by_success(self, since).await
+    }
+
+    async fn list_watched_accounts(&self) -> Result<Vec<String>, AppError> {
+        TransactionRepository::list_watched_accounts(self).await
+    }
+
+    async fn insert_block_meta(&self, block: &BlockMeta) -> Result<(), AppError> {
+        TransactionRepository::insert_block_meta(self, block).await
+    }
+
+    async fn find_existing_signatures(&self, signatures: &[String]) -> Result<HashSet<String>, AppError> {
+        TransactionRepository::find_existing_signatures(self, signatures).await
+    }
+
+    async fn mark_finalized(&self, signature: &str) -> Result<bool, AppError> {
+        TransactionRepository::mark_finalized(self, signature).await
+    }
+
+    async fn export_transactions(
+        &self,
+        since: DateTime<Utc>,
+        after_id: i64,
+        limit: u32,
+    ) -> Result<Vec<TransactionRow>, AppError> {
+        TransactionRepository::export_transactions(self, since, after_id, limit).await
+    }
+
+    async fn transaction_leader(&self, signature: &str) -> Result<Option<(i64, String)>, AppError> {
+        TransactionRepository::transaction_leader(self, signature).await
+    }
+
+    async fn balance_history(
+        &self,
+        account: &str,
+        mint: Option<&str>,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<(DateTime<Utc>, i64)>, AppError> {
+        TransactionRepository::balance_history(self, account, mint, since).await
+    }
+
+    async fn run_maintenance(&self, vacuum: bool) -> Result<(), AppError> {
+        TransactionRepository::run_maintenance(self, vacuum).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::connection;
+    use crate::solana::parser::tests::parse_fixture;
+    use tokio_postgres::NoTls;
+
+    /// Connect to `TEST_DATABASE_URL` with a fresh schema, named after the test, that has
+    /// every file in `migrations/` applied in order.
+    async fn postgres_repository(name: &str) -> TransactionRepository {
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must point at a scratch PostgreSQL database");
+        let (client, conn) = tokio_postgres::connect(&url, NoTls).await.unwrap();
+        tokio::spawn(conn);
+
+        let schema = format!("test_{}_{}", name, std::process::id());
+        client
+            .batch_execute(&format!(
+                "DROP SCHEMA IF EXISTS {schema} CASCADE; CREATE SCHEMA {schema}; SET search_path TO {schema}"
+            ))
+            .await
+            .unwrap();
+
+        let mut migrations: Vec<_> = std::fs::read_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations"))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        migrations.sort();
+        for migration in migrations {
+            client.batch_execute(&std::fs::read_to_string(migration).unwrap()).await.unwrap();
+        }
+
+        TransactionRepository::new(client)
+    }
+
+    async fn count(repository: &TransactionRepository, sql: &str) -> i64 {
+        repository.client.lock().await.query_one(sql, &[]).await.unwrap().get(0)
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn skips_balance_change_already_stored() {
+        let _metrics = metrics::reset_metrics().await;
+        let repository = postgres_repository("skips_stored").await;
+        let tx = parse_fixture("sol_transfer");
+        let (id, _) = repository.insert_complete_transaction_detailed(&tx).await.unwrap().unwrap();
+
+        // Rewritten, e.g. after an earlier partial write
+        repository.insert_balance_changes(id, &tx.balance_changes).await.unwrap();
+
+        assert_eq!(metrics::BALANCE_CHANGE_INSERT_FAILURES.get(), 0);
+        assert_eq!(count(&repository, "SELECT COUNT(*) FROM account_balance_changes").await, 2);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn counts_balance_change_that_fails_to_insert() {
+        let _metrics = metrics::reset_metrics().await;
+        let repository = postgres_repository("counts_failed").await;
+        let tx = parse_fixture("sol_transfer");
+
+        // No transaction has this id, so the foreign key rejects the change
+        repository.insert_balance_changes(999, &tx.balance_changes).await.unwrap();
+
+        assert_eq!(metrics::BALANCE_CHANGE_INSERT_FAILURES.get(), 2);
+        assert_eq!(count(&repository, "SELECT COUNT(*) FROM account_balance_changes").await, 0);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn unique_index_migration_keeps_existing_duplicates() {
+        let repository = postgres_repository("keeps_duplicates").await;
+        let tx = parse_fixture("sol_transfer");
+        let (id, _) = repository.insert_complete_transaction_detailed(&tx).await.unwrap().unwrap();
+        {
+            let client = repository.client.lock().await;
+            client.batch_execute("DROP INDEX idx_balance_changes_unique").await.unwrap();
+            for change in &tx.balance_changes {
+                TransactionRepository::insert_balance_change(&*client, id, change, false).await.unwrap();
+            }
+
+            client
+                .batch_execute(include_str!("../../migrations/20240101000022_unique_balance_changes.sql"))
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(count(&repository, "SELECT COUNT(*) FROM account_balance_changes").await, 4);
+        assert!(!connection::has_unique_balance_changes(&*repository.client.lock().await).await.unwrap());
+    }
+}
@@ -0,0 +1,773 @@
+use crate::database::export::TransactionRow;
+use crate::database::feed::TransactionFeed;
+use crate::database::store::TransactionStore;
+use crate::error::{AppError, DatabaseError};
+use crate::metrics;
+use crate::solana::models::{BalanceChange, BlockMeta, ParsedTransaction};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use tracing::{debug, info, warn};
+
+/// SQLite schema migrations, applied in order and tracked via `PRAGMA user_version`.
+/// 
+/// These mirror the PostgreSQL migrations in /migrations. New migrations are
+/// appended to the end of this list, never inserted or edited in place.
+const MIGRATIONS: &[&str] = &[
+    include_str!("../../migrations_sqlite/0001_create_transactions_table.sql"),
+    include_str!("../../migrations_sqlite/0002_create_balance_changes_table.sql"),
+    include_str!("../../migrations_sqlite/0003_create_missed_transactions_table.sql"),
+    include_str!("../../migrations_sqlite/0004_add_uses_durable_nonce.sql"),
+    include_str!("../../migrations_sqlite/0005_add_fee_payer_sol_delta.sql"),
+    include_str!("../../migrations_sqlite/0006_add_tx_type.sql"),
+    include_str!("../../migrations_sqlite/0007_create_watched_accounts_table.sql"),
+    include_str!("../../migrations_sqlite/0008_create_blocks_table.sql"),
+    include_str!("../../migrations_sqlite/0009_generate_balance_delta.sql"),
+    include_str!("../../migrations_sqlite/0010_add_balance_ui_amounts.sql"),
+    include_str!("../../migrations_sqlite/0011_add_finalized_at.sql"),
+    include_str!("../../migrations_sqlite/0012_add_return_data.sql"),
+    include_str!("../../migrations_sqlite/0013_add_token_program.sql"),
+    include_str!("../../migrations_sqlite/0014_add_is_wrapped_sol.sql"),
+    include_str!("../../migrations_sqlite/0015_add_requested_heap_bytes.sql"),
+    include_str!("../../migrations_sqlite/0016_add_instruction_counts.sql"),
+    include_str!("../../migrations_sqlite/0017_create_raw_transactions_table.sql"),
+    include_str!("../../migrations_sqlite/0018_create_slot_leaders_view.sql"),
+    include_str!("../../migrations_sqlite/0019_add_sol_burned.sql"),
+    include_str!("../../migrations_sqlite/0020_add_confirmed_only.sql"),
+    include_str!("../../migrations_sqlite/0021_unique_balance_changes.sql"),
+];
+
+/// Repository for persisting transaction data to a local SQLite database.
+/// 
+/// This is a lightweight alternative to PostgreSQL for local experimentation,
+/// selected with a `sqlite://` DATABASE_URL. rusqlite is synchronous, so every
+/// operation runs on tokio's blocking thread pool.
+#[derive(Clone)]
+pub struct SqliteRepository {
+    conn: Arc<Mutex<Connection>>,
+    feed: Option<Arc<TransactionFeed>>,
+}
+
+impl SqliteRepository {
+    /// Open (or create) the SQLite database at `path` and apply pending migrations.
+    /// 
+    /// Use `:memory:` for a throwaway in-memory database.
+    pub fn open(path: &str) -> Result<Self, AppError> {
+        info!(path = %path, "Opening SQLite database");
+
+        let mut conn = Connection::open(path)
+            .map_err(|e| AppError::database("Failed to open SQLite database", e))?;
+
+        conn.execute_batch("PRAGMA foreign_keys = ON; PRAGMA journal_mode = WAL;")
+            .map_err(|e| AppError::database("Failed to configure SQLite database", e))?;
+
+        Self::run_migrations(&mut conn)?;
+
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+            feed: None,
+        })
+    }
+
+    /// Publish newly inserted transactions to `feed`.
+    pub fn with_feed(mut self, feed: Arc<TransactionFeed>) -> Self {
+        self.feed = Some(feed);
+        self
+    }
+
+    /// Apply migrations newer than the database's recorded `user_version`.
+    fn run_migrations(conn: &mut Connection) -> Result<(), AppError> {
+        let current: i64 = conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .map_err(|e| AppError::database("Failed to read schema version", e))?;
+
+        for (index, migration) in MIGRATIONS.iter().enumerate().skip(current as usize) {
+            let version = index as i64 + 1;
+            let migration_error =
+                |e: rusqlite::Error| AppError::database(format!("Migration {} failed", version), e);
+
+            let tx = conn.transaction().map_err(migration_error)?;
+            tx.execute_batch(migration).map_err(migration_error)?;
+            tx.pragma_update(None, "user_version", version).map_err(migration_error)?;
+            tx.commit().map_err(migration_error)?;
+
+            info!(version = version, "Applied SQLite migration");
+        }
+
+        Ok(())
+    }
+
+    /// Run a closure against the connection on the blocking thread pool.
+    async fn with_conn<T, F>(&self, f: F) -> Result<T, AppError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> Result<T, AppError> + Send + 'static,
+    {
+        let conn = self.conn.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let mut conn = conn
+                .lock()
+                .map_err(|_| AppError::database("SQLite connection unavailable", DatabaseError::Poisoned))?;
+            f(&mut conn)
+        })
+        .await
+        .map_err(|e| AppError::database("SQLite task failed", e))?
+    }
+
+    /// Insert balance changes for a transaction, continuing past individual failures.
+    fn insert_balance_changes(conn: &Connection, transaction_id: i64, changes: &[BalanceChange]) {
+        for change in changes {
+            let result = Self::insert_balance_change(conn, transaction_id, change, false);
+
+            match result {
+                Ok(_) => {}
+                // The change is already stored, e.g. from an earlier partial write
+                Err(rusqlite::Error::SqliteFailure(e, _))
+                    if e.extended_code == rusqlite::ffi::SQLITE_CONSTRAINT_UNIQUE =>
+                {
+                    debug!(
+                        transaction_id = transaction_id,
+                        account = %change.account_address,
+                        "Balance change already recorded, skipping"
+                    );
+                }
+                Err(e) => {
+                    metrics::BALANCE_CHANGE_INSERT_FAILURES.inc();
+                    warn!(
+                        transaction_id = transaction_id,
+                        error = %e,
+                        "Failed to insert balance change, continuing with others"
+                    );
+                }
+            }
+        }
+    }
+
+    /// Insert a single balance change row.
+    /// 
+    /// With `skip_conflicts` a change that is already stored affects 0 rows instead of
+    /// failing with a unique constraint error.
+    fn insert_balance_change(
+        conn: &Connection,
+        transaction_id: i64,
+        change: &BalanceChange,
+        skip_conflicts: bool,
+    ) -> rusqlite::Result<usize> {
+        let on_conflict = if skip_conflicts { "ON CONFLICT DO NOTHING" } else { "" };
+
+        conn.execute(
+            &format!(
+                r#"
+                INSERT INTO account_balance_changes (
+                    transaction_id,
+                    account_address,
+                    mint_address,
+                    pre_balance,
+                    post_balance,
+                    pre_ui_amount,
+                    post_ui_amount,
+                    token_program,
+                    is_wrapped_sol
+                )
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+                {on_conflict}
+                "#
+            ),
+            params![
+                transaction_id,
+                change.account_address,
+                change.mint_address,
+                change.pre_balance,
+                change.post_balance,
+                change.pre_ui_amount,
+                change.post_ui_amount,
+                change.token_program,
+                change.is_wrapped_sol,
+            ],
+        )
+    }
+}
+
+impl TransactionStore for SqliteRepository {
+    async fn insert_complete_transaction_detailed(
+        &self,
+        tx: &ParsedTransaction,
+    ) -> Result<Option<(i64, usize)>, AppError> {
+        let tx = tx.clone();
+
+        let inserted = self.with_conn(move |conn| {
+            let db_tx = conn
+                .transaction()
+                .map_err(|e| AppError::database("Failed to begin transaction", e))?;
+
+            let id: Option<i64> = db_tx
+                .query_row(
+                    r#"
+                    INSERT INTO transactions (
+                        signature,
+                        slot,
+                        block_time,
+                        fee,
+                        fee_payer,
+                        success,
+                        compute_units_consumed,
+                        transaction_size_bytes,
+                        uses_durable_nonce,
+                        fee_payer_sol_delta,
+                        tx_type,
+                        return_data_program_id,
+                        return_data,
+                        requested_heap_bytes,
+                        instruction_count,
+                        inner_instruction_count,
+                        sol_burned,
+                        confirmed_only
+                    )
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)
+                    ON CONFLICT (signature) DO NOTHING
+                    RETURNING id
+                    "#,
+                    params![
+                        tx.signature,
+                        tx.slot as i64,
+                        tx.block_time,
+                        tx.fee as i64,
+                        tx.fee_payer,
+                        tx.success,
+                        tx.compute_units_consumed.map(|u| u as i64),
+                        tx.transaction_size_bytes,
+                        tx.uses_durable_nonce,
+                        tx.fee_payer_sol_delta,
+                        tx.tx_type.as_str(),
+                        tx.return_data.as_ref().map(|r| r.program_id.as_str()),
+                        tx.return_data.as_ref().map(|r| r.data.as_str()),
+                        tx.requested_heap_bytes,
+                        tx.instruction_count,
+                        tx.inner_instruction_count,
+                        tx.sol_burned as i64,
+                        tx.confirmed_only,
+                    ],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(|e| AppError::database("Failed to insert transaction", e))?;
+
+            let id = match id {
+                Some(id) => id,
+                None => {
+                    debug!(
+                        signature = %tx.signature,
+                        "Duplicate transaction skipped"
+                    );
+                    return Ok(None);
+                }
+            };
+
+            // A failed balance change rolls back the transaction row with it
+            let mut balance_changes_written = 0;
+            for change in &tx.balance_changes {
+                balance_changes_written += Self::insert_balance_change(&db_tx, id, change, true).map_err(|e| {
+                    metrics::BALANCE_CHANGE_INSERT_FAILURES.inc();
+                    AppError::database("Failed to insert balance change", e)
+                })?;
+            }
+
+            db_tx
+                .commit()
+                .map_err(|e| AppError::database("Failed to commit transaction", e))?;
+
+            debug!(
+                signature = %tx.signature,
+                transaction_id = id,
+                balance_changes_count = balance_changes_written,
+                "Inserted transaction into database"
+            );
+
+            Ok(Some((id, balance_changes_written, tx)))
+        })
+        .await?;
+
+        Ok(inserted.map(|(id, balance_changes_written, tx)| {
+            if let Some(feed) = &self.feed {
+                feed.publish(&tx);
+            }
+            (id, balance_changes_written)
+        }))
+    }
+
+    async fn upsert_complete_transaction(&self, tx: &ParsedTransaction) -> Result<bool, AppError> {
+        let tx = tx.clone();
+
+        let inserted = self.with_conn(move |conn| {
+            let existing: Option<i64> = conn
+                .query_row(
+                    "SELECT id FROM transactions WHERE signature = ?1",
+                    params![tx.signature],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(|e| AppError::database("Failed to look up transaction", e))?;
+
+            match existing {
+                Some(id) => {
+                    // Correct the earlier row; its balance changes were already recorded
+                    conn.execute(
+                        r#"
+                        UPDATE transactions SET
+                            slot = ?2,
+                            block_time = COALESCE(?3, block_time),
+                            success = ?4,
+                            compute_units_consumed = COALESCE(?5, compute_units_consumed),
+                            transaction_size_bytes = COALESCE(?6, transaction_size_bytes),
+                            confirmed_only = ?7
+                        WHERE id = ?1
+                        "#,
+                        params![
+                            id,
+                            tx.slot as i64,
+                            tx.block_time,
+                            tx.success,
+                            tx.compute_units_consumed.map(|u| u as i64),
+                            tx.transaction_size_bytes,
+                            tx.confirmed_only,
+                        ],
+                    )
+                    .map_err(|e| AppError::database("Failed to upsert transaction", e))?;
+
+                    Ok(None)
+                }
+                None => {
+                    let id: i64 = conn
+                        .query_row(
+                            r#"
+                            INSERT INTO transactions (
+                                signature,
+                                slot,
+                                block_time,
+                                fee,
+                                fee_payer,
+                                success,
+                                compute_units_consumed,
+                                transaction_size_bytes,
+                                uses_durable_nonce,
+                                fee_payer_sol_delta,
+                                tx_type,
+                                return_data_program_id,
+                                return_data,
+                                requested_heap_bytes,
+                                instruction_count,
+                                inner_instruction_count,
+                                sol_burned,
+                                confirmed_only
+                            )
+                            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)
+                            RETURNING id
+                            "#,
+                            params![
+                                tx.signature,
+                                tx.slot as i64,
+                                tx.block_time,
+                                tx.fee as i64,
+                                tx.fee_payer,
+                                tx.success,
+                                tx.compute_units_consumed.map(|u| u as i64),
+                                tx.transaction_size_bytes,
+                                tx.uses_durable_nonce,
+                                tx.fee_payer_sol_delta,
+                                tx.tx_type.as_str(),
+                                tx.return_data.as_ref().map(|r| r.program_id.as_str()),
+                                tx.return_data.as_ref().map(|r| r.data.as_str()),
+                                tx.requested_heap_bytes,
+                                tx.instruction_count,
+                                tx.inner_instruction_count,
+                                tx.sol_burned as i64,
+                                tx.confirmed_only,
+                            ],
+                            |row| row.get(0),
+                        )
+                        .map_err(|e| AppError::database("Failed to upsert transaction", e))?;
+
+                    Self::insert_balance_changes(conn, id, &tx.balance_changes);
+
+                    Ok(Some(tx))
+                }
+            }
+        })
+        .await?;
+
+        let Some(tx) = inserted else {
+            return Ok(false);
+        };
+        if let Some(feed) = &self.feed {
+            feed.publish(&tx);
+        }
+
+        Ok(true)
+    }
+
+    async fn record_missed_transaction(
+        &self,
+        signature: &str,
+        reason: &str,
+        error_message: &str,
+    ) -> Result<(), AppError> {
+        let (signature, reason, error_message) =
+            (signature.to_string(), reason.to_string(), error_message.to_string());
+
+        self.with_conn(move |conn| {
+            conn.execute(
+                r#"
+                INSERT INTO missed_transactions (signature, reason, error_message)
+                VALUES (?1, ?2, ?3)
+                ON CONFLICT (signature) DO UPDATE SET
+                    reason = excluded.reason,
+                    error_message = excluded.error_message,
+                    recorded_at = CURRENT_TIMESTAMP
+                "#,
+                params![signature, reason, error_message],
+            )
+            .map_err(|e| AppError::database("Failed to record missed transaction", e))?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn insert_raw_transaction(&self, signature: &str, raw_json: &str) -> Result<(), AppError> {
+        let (signature, raw_json) = (signature.to_string(), raw_json.to_string());
+
+        self.with_conn(move |conn| {
+            conn.execute(
+                r#"
+                INSERT INTO raw_transactions (signature, raw_json)
+                VALUES (?1, ?2)
+                ON CONFLICT (signature) DO NOTHING
+                "#,
+                params![signature, raw_json],
+            )
+            .map_err(|e| AppError::database("Failed to insert raw transaction", e))?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn count_by_success(&self, since: DateTime<Utc>) -> Result<(u64, u64), AppError> {
+        self.with_conn(move |conn| {
+            let (successful, failed): (i64, i64) = conn
+                .query_row(
+                    r#"
+                    SELECT
+                        COALESCE(SUM(success = 1), 0),
+                        COALESCE(SUM(success = 0), 0)
+                    FROM transactions
+                    WHERE block_time >= ?1
+                    "#,
+                    params![since],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .map_err(|e| AppError::database("Failed to count transactions", e))?;
+
+            Ok((successful as u64, failed as u64))
+        })
+        .await
+    }
+
+    async fn list_watched_accounts(&self) -> Result<Vec<String>, AppError> {
+        self.with_conn(|conn| {
+            let mut statement = conn
+                .prepare("SELECT address FROM watched_accounts ORDER BY id")
+                .map_err(|e| AppError::database("Failed to list watched accounts", e))?;
+
+            let accounts = statement
+                .query_map([], |row| row.get(0))
+                .and_then(|rows| rows.collect::<Result<Vec<String>, _>>())
+                .map_err(|e| AppError::database("Failed to list watched accounts", e))?;
+
+            Ok(accounts)
+        })
+        .await
+    }
+
+    async fn insert_block_meta(&self, block: &BlockMeta) -> Result<(), AppError> {
+        let block = block.clone();
+
+        self.with_conn(move |conn| {
+            conn.execute(
+                r#"
+                INSERT INTO blocks (
+                    slot,
+                    blockhash,
+                    parent_slot,
+                    block_height,
+                    block_time,
+                    leader,
+                    rewards_lamports
+                )
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+                ON CONFLICT (slot) DO NOTHING
+                "#,
+                params![
+                    block.slot as i64,
+                    block.blockhash,
+                    block.parent_slot as i64,
+                    block.block_height.map(|h| h as i64),
+                    block.block_time,
+                    block.leader,
+                    block.rewards_lamports,
+                ],
+            )
+            .map_err(|e| AppError::database("Failed to insert block", e))?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn find_existing_signatures(&self, signatures: &[String]) -> Result<HashSet<String>, AppError> {
+        let signatures = signatures.to_vec();
+
+        self.with_conn(move |conn| {
+            let mut statement = conn
+                .prepare("SELECT 1 FROM transactions WHERE signature = ?1")
+                .map_err(|e| AppError::database("Failed to look up signatures", e))?;
+
+            let mut existing = HashSet::new();
+            for signature in signatures {
+                let found = statement
+                    .exists(params![signature])
+                    .map_err(|e| AppError::database("Failed to look up signatures", e))?;
+                if found {
+                    existing.insert(signature);
+                }
+            }
+
+            Ok(existing)
+        })
+        .await
+    }
+
+    async fn mark_finalized(&self, signature: &str) -> Result<bool, AppError> {
+        let signature = signature.to_string();
+
+        self.with_conn(move |conn| {
+            let updated = conn
+                .execute(
+                    "UPDATE transactions SET finalized_at = CURRENT_TIMESTAMP WHERE signature = ?1 AND finalized_at IS NULL",
+                    params![signature],
+                )
+                .map_err(|e| AppError::database("Failed to mark transaction finalized", e))?;
+
+            Ok(updated > 0)
+        })
+        .await
+    }
+
+    async fn export_transactions(
+        &self,
+        since: DateTime<Utc>,
+        after_id: i64,
+        limit: u32,
+    ) -> Result<Vec<TransactionRow>, AppError> {
+        self.with_conn(move |conn| {
+            let mut statement = conn
+                .prepare(
+                    r#"
+                    SELECT
+                        id,
+                        signature,
+                        slot,
+                        block_time,
+                        fee,
+                        fee_payer,
+                        success,
+                        compute_units_consumed,
+                        transaction_size_bytes,
+                        fee_payer_sol_delta,
+                        tx_type
+                    FROM transactions
+                    WHERE block_time >= ?1 AND id > ?2
+                    ORDER BY id
+                    LIMIT ?3
+                    "#,
+                )
+                .map_err(|e| AppError::database("Failed to export transactions", e))?;
+
+            let rows = statement
+                .query_map(params![since, after_id, limit], |row| {
+                    Ok(TransactionRow {
+                        id: row.get(0)?,
+                        signature: row.get(1)?,
+                        slot: row.get(2)?,
+                        block_time: row.get(3)?,
+                        fee: row.get(4)?,
+                        fee_payer: row.get(5)?,
+                        success: row.get(6)?,
+                        compute_units_consumed: row.get(7)?,
+                        transaction_size_bytes: row.get(8)?,
+                        fee_payer_sol_delta: row.get(9)?,
+                        tx_type: row.get(10)?,
+                    })
+                })
+                .and_then(|rows| rows.collect::<Result<Vec<TransactionRow>, _>>())
+                .map_err(|e| AppError::database("Failed to export transactions", e))?;
+
+            Ok(rows)
+        })
+        .await
+    }
+
+    async fn transaction_leader(&self, signature: &str) -> Result<Option<(i64, String)>, AppError> {
+        let signature = signature.to_string();
+
+        self.with_conn(move |conn| {
+            conn.query_row(
+                r#"
+                SELECT t.slot, sl.leader
+                FROM transactions t
+                JOIN slot_leaders sl ON sl.slot = t.slot
+                WHERE t.signature = ?1
+                LIMIT 1
+                "#,
+                params![signature],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(|e| AppError::database("Failed to look up transaction leader", e))
+        })
+        .await
+    }
+
+    async fn balance_history(
+        &self,
+        account: &str,
+        mint: Option<&str>,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<(DateTime<Utc>, i64)>, AppError> {
+        let account = account.to_string();
+        let mint = mint.map(str::to_string);
+
+        self.with_conn(move |conn| {
+            // IS matches NULL to NULL, so a missing mint selects native SOL changes
+            let mut statement = conn
+                .prepare(
+                    r#"
+                    SELECT t.block_time, bc.post_balance
+                    FROM account_balance_changes bc
+                    JOIN transactions t ON t.id = bc.transaction_id
+                    WHERE bc.account_address = ?1
+                        AND bc.mint_address IS ?2
+                        AND t.block_time >= ?3
+                    ORDER BY t.block_time, t.slot, bc.id
+                    "#,
+                )
+                .map_err(|e| AppError::database("Failed to load balance history", e))?;
+
+            let history = statement
+                .query_map(params![account, mint, since], |row| Ok((row.get(0)?, row.get(1)?)))
+                .and_then(|rows| rows.collect::<Result<Vec<(DateTime<Utc>, i64)>, _>>())
+                .map_err(|e| AppError::database("Failed to load balance history", e))?;
+
+            Ok(history)
+        })
+        .await
+    }
+
+    async fn run_maintenance(&self, vacuum: bool) -> Result<(), AppError> {
+        self.with_conn(move |conn| {
+            // SQLite vacuums the whole file at once; it rewrites the database, so it is slow on large ones
+            if vacuum {
+                conn.execute_batch("VACUUM")
+                    .map_err(|e| AppError::database("Failed to run VACUUM", e))?;
+            }
+
+            conn.execute_batch("ANALYZE")
+                .map_err(|e| AppError::database("Failed to run ANALYZE", e))
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+impl SqliteRepository {
+    /// Read a single value from the database, for tests asserting on stored rows.
+    pub(crate) fn query_value<T: rusqlite::types::FromSql>(&self, sql: &str) -> T {
+        let conn = self.conn.lock().expect("SQLite connection unavailable");
+        conn.query_row(sql, [], |row| row.get(0)).expect("query returns a row")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solana::parser::tests::{load_fixture, PAYER, RECIPIENT};
+    use crate::solana::parser::{parse_transaction, ParseOptions};
+
+    fn memory_repository() -> SqliteRepository {
+        SqliteRepository::open(":memory:").expect("open in-memory database")
+    }
+
+    fn parse_fixture(name: &str) -> ParsedTransaction {
+        parse_transaction(&load_fixture(name), &ParseOptions::default()).expect("fixture parses")
+    }
+
+    #[tokio::test]
+    async fn computes_balance_delta_in_database() {
+        let repository = memory_repository();
+        repository
+            .insert_complete_transaction_detailed(&parse_fixture("sol_transfer"))
+            .await
+            .unwrap();
+
+        let delta = |account: &str| -> i64 {
+            repository.query_value(&format!(
+                "SELECT balance_delta FROM account_balance_changes WHERE account_address = '{}'",
+                account
+            ))
+        };
+        assert_eq!(delta(PAYER), -1_005_000);
+        assert_eq!(delta(RECIPIENT), 1_000_000);
+    }
+
+    #[tokio::test]
+    async fn skips_balance_change_already_stored() {
+        let _metrics = metrics::reset_metrics().await;
+        let repository = memory_repository();
+        let tx = parse_fixture("sol_transfer");
+        let (id, _) = repository.insert_complete_transaction_detailed(&tx).await.unwrap().unwrap();
+
+        // Rewritten, e.g. after an earlier partial write
+        SqliteRepository::insert_balance_changes(&repository.conn.lock().unwrap(), id, &tx.balance_changes);
+
+        assert_eq!(metrics::BALANCE_CHANGE_INSERT_FAILURES.get(), 0);
+        assert_eq!(repository.query_value::<i64>("SELECT COUNT(*) FROM account_balance_changes"), 2);
+    }
+
+    #[tokio::test]
+    async fn counts_balance_change_that_fails_to_insert() {
+        let _metrics = metrics::reset_metrics().await;
+        let repository = memory_repository();
+        let tx = parse_fixture("sol_transfer");
+
+        // No transaction has this id, so the foreign key rejects the change
+        SqliteRepository::insert_balance_changes(&repository.conn.lock().unwrap(), 999, &tx.balance_changes);
+
+        assert_eq!(metrics::BALANCE_CHANGE_INSERT_FAILURES.get(), 2);
+        assert_eq!(repository.query_value::<i64>("SELECT COUNT(*) FROM account_balance_changes"), 0);
+    }
+
+    #[tokio::test]
+    async fn counts_only_balance_changes_written() {
+        let repository = memory_repository();
+        let mut tx = parse_fixture("sol_transfer");
+        let repeated = tx.balance_changes[0].clone();
+        tx.balance_changes.push(repeated);
+
+        let (_, written) = repository.insert_complete_transaction_detailed(&tx).await.unwrap().unwrap();
+
+        assert_eq!(written, 2);
+        assert_eq!(repository.query_value::<i64>("SELECT COUNT(*) FROM account_balance_changes"), 2);
+    }
+}
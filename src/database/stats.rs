@@ -0,0 +1,68 @@
+use crate::database::store::TransactionStore;
+use crate::error::AppError;
+use crate::metrics;
+use serde::Serialize;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tokio::time::{Duration, Instant};
+
+/// Success/failure counts of persisted transactions over a trailing window.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct SuccessStats {
+    pub window_seconds: u64,
+    pub success: u64,
+    pub failed: u64,
+}
+
+/// Short-lived cache in front of `TransactionStore::count_by_success`.
+/// 
+/// The stats endpoint and metrics scrapes can both ask for these counts, so the
+/// result is reused for `ttl` to avoid hammering the database. Each refresh also
+/// updates the `solana_tracker_db_transactions_*` gauges.
+pub struct SuccessStatsCache<S: TransactionStore> {
+    repository: Arc<S>,
+    window: Duration,
+    ttl: Duration,
+    cached: Mutex<Option<(Instant, SuccessStats)>>,
+}
+
+impl<S: TransactionStore> SuccessStatsCache<S> {
+    /// Create a cache counting transactions over `window`, refreshed at most every `ttl`.
+    pub fn new(repository: Arc<S>, window: Duration, ttl: Duration) -> Self {
+        Self {
+            repository,
+            window,
+            ttl,
+            cached: Mutex::new(None),
+        }
+    }
+
+    /// Return cached stats, querying the database if the cache is empty or stale.
+    pub async fn get(&self) -> Result<SuccessStats, AppError> {
+        let mut cached = self.cached.lock().await;
+
+        if let Some((fetched_at, stats)) = *cached {
+            if fetched_at.elapsed() < self.ttl {
+                return Ok(stats);
+            }
+        }
+
+        let window = chrono::Duration::from_std(self.window)
+            .map_err(|e| AppError::Config(format!("Invalid stats window: {}", e)))?;
+        let since = chrono::Utc::now() - window;
+
+        let (success, failed) = self.repository.count_by_success(since).await?;
+        let stats = SuccessStats {
+            window_seconds: self.window.as_secs(),
+            success,
+            failed,
+        };
+
+        metrics::DB_TRANSACTIONS_SUCCESS.set(success as i64);
+        metrics::DB_TRANSACTIONS_FAILED.set(failed as i64);
+
+        *cached = Some((Instant::now(), stats));
+
+        Ok(stats)
+    }
+}
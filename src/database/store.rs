@@ -0,0 +1,110 @@
+use crate::database::export::TransactionRow;
+use crate::error::AppError;
+use crate::solana::models::{BlockMeta, ParsedTransaction};
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
+use std::future::Future;
+
+/// Storage backend for indexed transactions.
+/// 
+/// The stream handler and stats endpoint are generic over this trait, so the
+/// PostgreSQL `TransactionRepository` and the SQLite `SqliteRepository` can be
+/// swapped based on the `DATABASE_URL` scheme without touching the processing path.
+/// 
+/// Methods return `Send` futures so stores can be used from spawned tasks.
+pub trait TransactionStore: Send + Sync + 'static {
+    /// Insert a transaction and its balance changes, skipping duplicates by signature.
+    /// 
+    /// A wrapper over `insert_complete_transaction_detailed` for callers that don't
+    /// need the row id or balance change count.
+    #[allow(dead_code)]
+    fn insert_complete_transaction(
+        &self,
+        tx: &ParsedTransaction,
+    ) -> impl Future<Output = Result<(), AppError>> + Send {
+        async move { self.insert_complete_transaction_detailed(tx).await.map(|_| ()) }
+    }
+
+    /// Insert a transaction and its balance changes in a single database transaction,
+    /// skipping duplicates by signature.
+    /// 
+    /// Returns the new row id and the number of balance changes written, or None when
+    /// the signature was already stored.
+    fn insert_complete_transaction_detailed(
+        &self,
+        tx: &ParsedTransaction,
+    ) -> impl Future<Output = Result<Option<(i64, usize)>, AppError>> + Send;
+
+    /// Insert a transaction, or correct the existing row for the same signature.
+    /// 
+    /// Returns whether the row was newly inserted rather than corrected.
+    fn upsert_complete_transaction(
+        &self,
+        tx: &ParsedTransaction,
+    ) -> impl Future<Output = Result<bool, AppError>> + Send;
+
+    /// Record a signature that could not be fetched from RPC.
+    fn record_missed_transaction(
+        &self,
+        signature: &str,
+        reason: &str,
+        error_message: &str,
+    ) -> impl Future<Output = Result<(), AppError>> + Send;
+
+    /// Store the raw RPC response of a transaction as JSON, skipping signatures already stored.
+    fn insert_raw_transaction(
+        &self,
+        signature: &str,
+        raw_json: &str,
+    ) -> impl Future<Output = Result<(), AppError>> + Send;
+
+    /// Count persisted transactions by outcome since the given time, as `(successful, failed)`.
+    fn count_by_success(
+        &self,
+        since: DateTime<Utc>,
+    ) -> impl Future<Output = Result<(u64, u64), AppError>> + Send;
+
+    /// List the addresses of the database-managed watchlist.
+    fn list_watched_accounts(&self) -> impl Future<Output = Result<Vec<String>, AppError>> + Send;
+
+    /// Insert block metadata, skipping blocks already stored.
+    fn insert_block_meta(&self, block: &BlockMeta) -> impl Future<Output = Result<(), AppError>> + Send;
+
+    /// Return which of the given signatures are already stored in the transactions table.
+    fn find_existing_signatures(
+        &self,
+        signatures: &[String],
+    ) -> impl Future<Output = Result<HashSet<String>, AppError>> + Send;
+
+    /// Stamp a stored transaction as finalized, returning whether a row was updated.
+    fn mark_finalized(&self, signature: &str) -> impl Future<Output = Result<bool, AppError>> + Send;
+
+    /// Return up to `limit` transactions with a block time at or after `since` and an id
+    /// above `after_id`, ordered by id.
+    fn export_transactions(
+        &self,
+        since: DateTime<Utc>,
+        after_id: i64,
+        limit: u32,
+    ) -> impl Future<Output = Result<Vec<TransactionRow>, AppError>> + Send;
+
+    /// Return the slot and slot leader of a stored transaction, or None when the
+    /// transaction or its block's leader isn't stored.
+    fn transaction_leader(
+        &self,
+        signature: &str,
+    ) -> impl Future<Output = Result<Option<(i64, String)>, AppError>> + Send;
+
+    /// Return the post-balance of `account` for `mint` (None = native SOL) after each
+    /// stored transaction with a block time at or after `since`, oldest first.
+    fn balance_history(
+        &self,
+        account: &str,
+        mint: Option<&str>,
+        since: DateTime<Utc>,
+    ) -> impl Future<Output = Result<Vec<(DateTime<Utc>, i64)>, AppError>> + Send;
+
+    /// Refresh planner statistics for the indexer's tables, reclaiming dead rows
+    /// first when `vacuum` is set.
+    fn run_maintenance(&self, vacuum: bool) -> impl Future<Output = Result<(), AppError>> + Send;
+}
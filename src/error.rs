@@ -1,20 +1,60 @@
+use solana_client::client_error::{ClientError, ClientErrorKind};
+use solana_client::rpc_request::RpcError;
+use std::sync::OnceLock;
 use thiserror::Error;
+use tokio_postgres::error::SqlState;
+use tonic::{Code, Status};
+use yellowstone_grpc_client::{GeyserGrpcBuilderError, GeyserGrpcClientError};
+
+/// Lowercased message substrings that mark an error retryable, from RETRYABLE_ERROR_PATTERNS.
+static RETRYABLE_PATTERNS: OnceLock<Vec<String>> = OnceLock::new();
+
+/// Treat errors whose message contains any of `patterns` (case-insensitively) as retryable.
+/// 
+/// An escape hatch for providers that report transient failures, such as rate
+/// limiting, in ways the built-in classification misses. Must be called before the
+/// streams start; later calls are ignored.
+pub fn set_retryable_patterns(patterns: Vec<String>) {
+    let _ = RETRYABLE_PATTERNS.set(patterns.iter().map(|pattern| pattern.to_lowercase()).collect());
+}
 
 /// Application-level errors with context-rich messages.
 /// 
 /// All fallible operations in this application return Result<T, AppError>.
 /// This enum provides specific error variants for different failure modes,
 /// enabling proper error handling and informative error messages.
+/// 
+/// Variants wrapping an external failure keep it as the error `source`, so callers
+/// can match on the concrete error kind instead of inspecting the message.
 #[derive(Debug, Error)]
 pub enum AppError {
-    #[error("gRPC connection error: {0}")]
-    GrpcConnection(String),
+    #[error("gRPC connection error: {context}: {source}")]
+    GrpcConnection {
+        context: String,
+        #[source]
+        source: Box<GrpcError>,
+    },
+
+    #[error("gRPC stream error: {context}: {source}")]
+    GrpcStream {
+        context: String,
+        #[source]
+        source: Box<GrpcError>,
+    },
 
-    #[error("gRPC stream error: {0}")]
-    GrpcStream(String),
+    #[error("Fatal subscription error: {context}: {source}")]
+    FatalSubscription {
+        context: String,
+        #[source]
+        source: Box<tonic::Status>,
+    },
 
-    #[error("Database error: {0}")]
-    Database(String),
+    #[error("Database error: {context}: {source}")]
+    Database {
+        context: String,
+        #[source]
+        source: DatabaseError,
+    },
 
     #[error("Transaction parsing error: {0}")]
     ParseError(String),
@@ -22,16 +62,322 @@ pub enum AppError {
     #[error("Configuration error: {0}")]
     Config(String),
 
-    #[error("Solana client error: {0}")]
-    SolanaClient(String),
+    #[error("Solana client error: {context}: {source}")]
+    SolanaClient {
+        context: String,
+        #[source]
+        source: Box<ClientError>,
+    },
+
+    #[error("Gave up reconnecting after {attempts} consecutive failed attempts: {source}")]
+    ReconnectLimitExceeded {
+        attempts: u32,
+        #[source]
+        source: Box<AppError>,
+    },
+
+    #[error("Fetch concurrency limiter closed: {0}")]
+    LimiterClosed(#[from] tokio::sync::AcquireError),
 
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
 }
 
+/// Underlying cause of an `AppError::Database`.
+#[derive(Debug, Error)]
+pub enum DatabaseError {
+    #[error(transparent)]
+    Postgres(#[from] tokio_postgres::Error),
+
+    #[error(transparent)]
+    Sqlite(#[from] rusqlite::Error),
+
+    #[error(transparent)]
+    Migration(#[from] refinery::Error),
+
+    #[error(transparent)]
+    Task(#[from] tokio::task::JoinError),
+
+    #[error("connection mutex poisoned")]
+    Poisoned,
+}
+
+/// Underlying cause of an `AppError::GrpcConnection` or `AppError::GrpcStream`.
+#[derive(Debug, Error)]
+pub enum GrpcError {
+    #[error(transparent)]
+    Builder(#[from] GeyserGrpcBuilderError),
+
+    #[error(transparent)]
+    Client(#[from] GeyserGrpcClientError),
+
+    #[error(transparent)]
+    Status(#[from] tonic::Status),
+
+    #[error(transparent)]
+    Send(#[from] futures::channel::mpsc::SendError),
+}
+
+impl AppError {
+    /// Wrap a database failure with a description of the operation that failed.
+    pub fn database(context: impl Into<String>, source: impl Into<DatabaseError>) -> Self {
+        AppError::Database {
+            context: context.into(),
+            source: source.into(),
+        }
+    }
+
+    /// Wrap a failure to establish the gRPC connection.
+    pub fn grpc_connection(context: impl Into<String>, source: impl Into<GrpcError>) -> Self {
+        AppError::GrpcConnection {
+            context: context.into(),
+            source: Box::new(source.into()),
+        }
+    }
+
+    /// Wrap a failure on an established gRPC stream.
+    pub fn grpc_stream(context: impl Into<String>, source: impl Into<GrpcError>) -> Self {
+        AppError::GrpcStream {
+            context: context.into(),
+            source: Box::new(source.into()),
+        }
+    }
+
+    /// Wrap a subscription rejection that retrying cannot fix.
+    pub fn fatal_subscription(context: impl Into<String>, status: tonic::Status) -> Self {
+        AppError::FatalSubscription {
+            context: context.into(),
+            source: Box::new(status),
+        }
+    }
+
+    /// Wrap a failed Solana RPC call.
+    pub fn solana_client(context: impl Into<String>, source: ClientError) -> Self {
+        AppError::SolanaClient {
+            context: context.into(),
+            source: Box::new(source),
+        }
+    }
+
+    /// Whether retrying the failed operation could succeed.
+    /// 
+    /// The gRPC reconnect loop and the database connect retry decide on this:
+    /// connection resets, timeouts, overload, and rate limiting are retryable;
+    /// authentication, invalid configuration or input, and constraint violations are
+    /// not. Errors whose message matches a RETRYABLE_ERROR_PATTERNS entry are retryable
+    /// regardless, except for the reconnect limit, which must stop the stream. RPC
+    /// fetches are not retried; their rate limiting is recognized by `is_rate_limited`.
+    pub fn is_retryable(&self) -> bool {
+        if !matches!(self, AppError::ReconnectLimitExceeded { .. }) && self.matches_retryable_pattern() {
+            return true;
+        }
+
+        match self {
+            AppError::GrpcConnection { source, .. } | AppError::GrpcStream { source, .. } => {
+                source.is_retryable()
+            }
+            AppError::FatalSubscription { .. } | AppError::ReconnectLimitExceeded { .. } => false,
+            AppError::Database { source, .. } => source.is_retryable(),
+            AppError::ParseError(_) | AppError::Config(_) | AppError::LimiterClosed(_) => false,
+            AppError::SolanaClient { source, .. } => is_retryable_client_error(source),
+            AppError::Io(e) => is_retryable_io_error(e),
+        }
+    }
+
+    /// Whether the error message contains one of the configured retryable patterns.
+    fn matches_retryable_pattern(&self) -> bool {
+        matches_retryable_pattern(&self.to_string())
+    }
+}
+
+/// Whether `message` contains one of the configured retryable patterns.
+fn matches_retryable_pattern(message: &str) -> bool {
+    let Some(patterns) = RETRYABLE_PATTERNS.get().filter(|patterns| !patterns.is_empty()) else {
+        return false;
+    };
+
+    let message = message.to_lowercase();
+    patterns.iter().any(|pattern| message.contains(pattern.as_str()))
+}
+
+impl DatabaseError {
+    /// Whether the failed statement or connection attempt could succeed if retried.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            DatabaseError::Postgres(e) => {
+                if let Some(code) = e.code() {
+                    // Class 08 is "connection exception"
+                    return code.code().starts_with("08")
+                        || *code == SqlState::T_R_SERIALIZATION_FAILURE
+                        || *code == SqlState::T_R_DEADLOCK_DETECTED
+                        || *code == SqlState::QUERY_CANCELED
+                        || *code == SqlState::ADMIN_SHUTDOWN
+                        || *code == SqlState::CANNOT_CONNECT_NOW
+                        || *code == SqlState::TOO_MANY_CONNECTIONS;
+                }
+
+                // Without a SQLSTATE the server never answered: the connection dropped or failed
+                e.is_closed()
+                    || std::error::Error::source(e)
+                        .is_some_and(|source| source.downcast_ref::<std::io::Error>().is_some())
+            }
+            DatabaseError::Sqlite(e) => matches!(
+                e.sqlite_error_code(),
+                Some(rusqlite::ErrorCode::DatabaseBusy | rusqlite::ErrorCode::DatabaseLocked)
+            ),
+            DatabaseError::Migration(_) | DatabaseError::Task(_) | DatabaseError::Poisoned => false,
+        }
+    }
+}
+
+impl GrpcError {
+    /// Whether reconnecting could get past this failure.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            // Transport failures (refused, reset, TLS handshake) are worth retrying;
+            // a malformed x-token is not
+            GrpcError::Builder(GeyserGrpcBuilderError::TonicError(_)) => true,
+            GrpcError::Builder(_) => false,
+            GrpcError::Client(GeyserGrpcClientError::TonicStatus(status)) => is_retryable_status(status),
+            GrpcError::Client(_) => true,
+            GrpcError::Status(status) => is_retryable_status(status),
+            GrpcError::Send(_) => true,
+        }
+    }
+}
+
+/// Whether a gRPC status from the Yellowstone endpoint is worth retrying.
+/// 
+/// Authentication, permission, and invalid-filter rejections will fail the same way
+/// on every attempt; everything else (unavailable, deadline exceeded, resets) is transient.
+pub fn is_retryable_status(status: &Status) -> bool {
+    !matches!(
+        status.code(),
+        Code::Unauthenticated | Code::PermissionDenied | Code::InvalidArgument
+    )
+}
+
+/// Whether a Solana RPC failure is the node's rate limiter turning the request away.
+/// 
+/// That is an HTTP 429, or a message matching a RETRYABLE_ERROR_PATTERNS entry, which
+/// is how nonstandard provider rate-limit errors are recognized. The Solana client
+/// already retries 429s a few times internally, so reaching this point means the
+/// endpoint is persistently over its limit.
+pub fn is_rate_limited(error: &ClientError) -> bool {
+    matches!(
+        error.kind(),
+        ClientErrorKind::Reqwest(e) if e.status().is_some_and(|status| status.as_u16() == 429)
+    ) || matches_retryable_pattern(&error.to_string())
+}
+
+/// Whether a Solana RPC failure is transient: I/O errors, timeouts, connection
+/// failures, rate limiting and 5xx responses, and an unhealthy node.
+fn is_retryable_client_error(error: &ClientError) -> bool {
+    use solana_client::rpc_custom_error::JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY;
+
+    if is_rate_limited(error) {
+        return true;
+    }
+
+    match error.kind() {
+        ClientErrorKind::Io(_) => true,
+        ClientErrorKind::Reqwest(e) => {
+            e.is_timeout()
+                || e.is_connect()
+                || e.status().is_some_and(|status| status.is_server_error())
+        }
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. }) => {
+            *code == JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY
+        }
+        _ => false,
+    }
+}
+
+/// Whether a local I/O error is a transient network condition.
+fn is_retryable_io_error(error: &std::io::Error) -> bool {
+    use std::io::ErrorKind;
+
+    matches!(
+        error.kind(),
+        ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::ConnectionRefused
+            | ErrorKind::BrokenPipe
+            | ErrorKind::TimedOut
+            | ErrorKind::Interrupted
+            | ErrorKind::UnexpectedEof
+    )
+}
+
 /// Convert anyhow::Error to AppError for broader compatibility
 impl From<anyhow::Error> for AppError {
     fn from(err: anyhow::Error) -> Self {
         AppError::Config(err.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::ErrorKind;
+
+    #[test]
+    fn timeouts_are_retryable() {
+        assert!(AppError::Io(ErrorKind::TimedOut.into()).is_retryable());
+        assert!(AppError::solana_client("fetch", ClientError::from(std::io::Error::from(ErrorKind::TimedOut))).is_retryable());
+        assert!(AppError::grpc_stream("stream", Status::deadline_exceeded("timed out")).is_retryable());
+    }
+
+    #[test]
+    fn connection_resets_are_retryable() {
+        assert!(AppError::Io(ErrorKind::ConnectionReset.into()).is_retryable());
+        assert!(AppError::grpc_stream("stream", Status::unavailable("connection reset")).is_retryable());
+    }
+
+    #[test]
+    fn rate_limiting_is_retryable() {
+        let response = tonic::codegen::http::Response::builder()
+            .status(429)
+            .body("Too Many Requests")
+            .unwrap();
+        let error = reqwest::Response::from(response).error_for_status().unwrap_err();
+
+        let error = ClientError::from(error);
+        assert!(is_rate_limited(&error));
+        assert!(AppError::solana_client("fetch", error).is_retryable());
+    }
+
+    #[test]
+    fn configured_patterns_mark_rpc_errors_rate_limited() {
+        // The patterns are process-wide, so this is the only test that sets them
+        set_retryable_patterns(vec!["Capacity Exceeded".to_string()]);
+        let limited = ClientError::from(RpcError::RpcRequestError(
+            "provider capacity exceeded, slow down".to_string(),
+        ));
+        let other = ClientError::from(RpcError::RpcRequestError("invalid params".to_string()));
+
+        assert!(is_rate_limited(&limited));
+        assert!(AppError::solana_client("fetch", limited).is_retryable());
+        assert!(!is_rate_limited(&other));
+    }
+
+    #[test]
+    fn auth_failures_are_not_retryable() {
+        assert!(!AppError::grpc_stream("subscribe", Status::unauthenticated("bad x-token")).is_retryable());
+        assert!(!AppError::fatal_subscription("subscribe", Status::permission_denied("denied")).is_retryable());
+    }
+
+    #[test]
+    fn config_errors_are_not_retryable() {
+        assert!(!AppError::Config("GRPC_ENDPOINT is not set".to_string()).is_retryable());
+    }
+
+    #[test]
+    fn constraint_violations_are_not_retryable() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE t (id INTEGER PRIMARY KEY); INSERT INTO t VALUES (1);").unwrap();
+        let error = conn.execute("INSERT INTO t VALUES (1)", []).unwrap_err();
+
+        assert!(!AppError::database("Failed to insert", error).is_retryable());
+    }
+}
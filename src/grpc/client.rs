@@ -1,11 +1,27 @@
-use crate::error::AppError;
+use crate::config::Commitment;
+use crate::error::{is_retryable_status, AppError};
+use crate::grpc::source::{StreamSource, Subscription};
+use crate::labels::endpoint_label;
+use futures::channel::mpsc;
+use futures::{SinkExt, StreamExt};
 use solana_sdk::pubkey::Pubkey;
 use std::str::FromStr;
+use tonic::codec::CompressionEncoding;
 use tracing::info;
 use yellowstone_grpc_client::GeyserGrpcClient;
 use yellowstone_grpc_proto::geyser::SubscribeRequest;
 use yellowstone_grpc_proto::prelude::CommitmentLevel;
 
+impl From<Commitment> for CommitmentLevel {
+    fn from(commitment: Commitment) -> Self {
+        match commitment {
+            Commitment::Processed => CommitmentLevel::Processed,
+            Commitment::Confirmed => CommitmentLevel::Confirmed,
+            Commitment::Finalized => CommitmentLevel::Finalized,
+        }
+    }
+}
+
 /// Manages the gRPC connection to Solana RPC via Yellowstone (Triton One's Dragons Mouth).
 /// 
 /// This client handles connection establishment to Triton One's gRPC streaming service,
@@ -13,12 +29,20 @@ use yellowstone_grpc_proto::prelude::CommitmentLevel;
 pub struct RpcClient {
     grpc_endpoint: String,
     auth_token: String,
-    account: Pubkey,
+    accounts: Vec<Pubkey>,
     include_failed_transactions: bool,
+    commitment: CommitmentLevel,
+    subscribe_blocks_meta: bool,
+    status_only: bool,
+    gzip_compression: bool,
+    account_data_slice: Vec<(u64, u64)>,
+    all_slot_statuses: bool,
+    target_is_program: bool,
+    account_required: Vec<String>,
 }
 
 impl RpcClient {
-    /// Create a new RPC client for the given gRPC endpoint and account.
+    /// Create a new RPC client for the given gRPC endpoint and accounts.
     /// 
     /// The gRPC endpoint should be in the format: https://host:port
     /// Authentication is provided via the x-token header.
@@ -26,29 +50,108 @@ impl RpcClient {
     /// The `include_failed_transactions` parameter controls whether failed transactions
     /// are captured. Setting this to true provides more comprehensive data about the
     /// bot's operations, including unsuccessful attempts that still incur fees.
+    /// 
+    /// The `commitment` parameter sets the commitment level of the subscription and
+    /// of the follow-up RPC fetches made for each transaction it delivers.
+    /// 
+    /// The `subscribe_blocks_meta` parameter adds a blocks_meta subscription so block
+    /// leaders and rewards are delivered alongside transactions.
+    /// 
+    /// The `status_only` parameter subscribes to transaction status updates instead of
+    /// full transactions. Status updates carry only the signature, slot, and error, so
+    /// they are a cheap way to learn that a transaction reached the subscription commitment.
     pub fn new(
         grpc_endpoint: String,
         auth_token: String,
-        account: &str,
+        accounts: &[String],
         include_failed_transactions: bool,
+        commitment: CommitmentLevel,
+        subscribe_blocks_meta: bool,
+        status_only: bool,
     ) -> Result<Self, AppError> {
         info!(
             grpc_endpoint = %grpc_endpoint,
-            account = %account,
+            accounts = ?accounts,
+            commitment = ?commitment,
+            status_only = status_only,
             "Creating Yellowstone gRPC client"
         );
 
-        let account = Pubkey::from_str(account)
-            .map_err(|e| AppError::Config(format!("Invalid account pubkey: {}", e)))?;
+        let accounts = accounts
+            .iter()
+            .map(|account| {
+                Pubkey::from_str(account).map_err(|e| {
+                    AppError::Config(format!("Invalid account pubkey '{}': {}", account, e))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
 
         Ok(Self {
             grpc_endpoint,
             auth_token,
-            account,
+            accounts,
             include_failed_transactions,
+            commitment,
+            subscribe_blocks_meta,
+            status_only,
+            gzip_compression: false,
+            account_data_slice: Vec::new(),
+            all_slot_statuses: false,
+            target_is_program: false,
+            account_required: Vec::new(),
         })
     }
 
+    /// Enable gzip compression on the gRPC stream.
+    /// 
+    /// Compressed updates cut bandwidth substantially for high-volume subscriptions,
+    /// at the cost of CPU to decompress every message on our side (and compress on the server's).
+    pub fn with_gzip_compression(mut self, enabled: bool) -> Self {
+        self.gzip_compression = enabled;
+        self
+    }
+
+    /// Receive only these `(offset, length)` byte ranges of account data.
+    /// 
+    /// Account updates otherwise carry the full account data; when only a few fields
+    /// are needed, slicing saves most of the bandwidth. Empty means full data.
+    pub fn with_account_data_slice(mut self, slices: Vec<(u64, u64)>) -> Self {
+        self.account_data_slice = slices;
+        self
+    }
+
+    /// Receive slot updates at every commitment level, not just the subscription's.
+    /// 
+    /// A Confirmed subscription then also learns when each slot is finalized, which
+    /// PERSIST_ONLY_FINALIZED relies on to release held transactions, and a
+    /// SLOT_COMMITMENT other than the subscription's gets the statuses it tracks.
+    pub fn with_all_slot_statuses(mut self, enabled: bool) -> Self {
+        self.all_slot_statuses = enabled;
+        self
+    }
+
+    /// Treat the target accounts as program ids rather than wallets.
+    /// 
+    /// Transactions are still matched by `account_include`, which for a program is
+    /// any transaction listing it, but no account updates are subscribed: a program
+    /// account only changes on upgrade, and subscribing by owner would stream every
+    /// account the program owns, which the indexer doesn't use.
+    pub fn with_target_is_program(mut self, enabled: bool) -> Self {
+        self.target_is_program = enabled;
+        self
+    }
+
+    /// Only receive transactions that list every one of these accounts.
+    /// 
+    /// The target accounts match with OR semantics (`account_include`: any one of them
+    /// is enough), while these are ANDed on top (`account_required`: all must be
+    /// present), so pairing a target with a DEX program narrows the stream to their
+    /// interactions. Empty means no additional requirement.
+    pub fn with_account_required(mut self, accounts: Vec<String>) -> Self {
+        self.account_required = accounts;
+        self
+    }
+
     /// Connect to the gRPC endpoint and return a configured Yellowstone client.
     /// 
     /// This creates a persistent gRPC connection to monitor all transactions
@@ -60,79 +163,161 @@ impl RpcClient {
         );
 
         // Connect with x-token authentication
-        let client = GeyserGrpcClient::build_from_shared(self.grpc_endpoint.clone())
-            .map_err(|e| AppError::GrpcConnection(format!("Invalid gRPC endpoint: {}", e)))?
+        let mut builder = GeyserGrpcClient::build_from_shared(self.grpc_endpoint.clone())
+            .map_err(|e| AppError::grpc_connection("Invalid gRPC endpoint", e))?
             .x_token(Some(self.auth_token.clone()))
-            .map_err(|e| AppError::Config(format!("Invalid auth token: {}", e)))?
+            .map_err(|e| AppError::Config(format!("Invalid auth token: {}", e)))?;
+
+        if self.gzip_compression {
+            builder = builder
+                .send_compressed(CompressionEncoding::Gzip)
+                .accept_compressed(CompressionEncoding::Gzip);
+        }
+
+        let client = builder
             .connect()
             .await
-            .map_err(|e| AppError::GrpcConnection(format!("Failed to connect to gRPC endpoint: {}", e)))?;
+            .map_err(|e| AppError::grpc_connection("Failed to connect to gRPC endpoint", e))?;
 
-        info!("Successfully connected to Yellowstone gRPC endpoint");
+        info!(
+            gzip_compression = self.gzip_compression,
+            "Successfully connected to Yellowstone gRPC endpoint"
+        );
 
         Ok(client)
     }
 
-    /// Create a subscription request for monitoring the target account's transactions.
+    /// Create a subscription request for monitoring the target accounts' transactions.
     /// 
     /// This builds a SubscribeRequest configured to receive updates for all transactions
-    /// that mention the target account. Vote transactions are always excluded.
+    /// that mention any of the target accounts. Vote transactions are always excluded.
     /// Failed transactions are included or excluded based on the configuration flag.
+    /// Status-only clients use the same filter for transaction status updates instead.
     pub fn create_subscription_request(&self) -> SubscribeRequest {
         use std::collections::HashMap;
         use yellowstone_grpc_proto::geyser::{
-            SubscribeRequestFilterAccounts, SubscribeRequestFilterSlots,
-            SubscribeRequestFilterTransactions,
+            SubscribeRequestAccountsDataSlice, SubscribeRequestFilterAccounts, SubscribeRequestFilterBlocksMeta,
+            SubscribeRequestFilterSlots, SubscribeRequestFilterTransactions,
         };
 
+        let account_keys: Vec<String> = self.accounts.iter().map(|a| a.to_string()).collect();
+
         let mut accounts = HashMap::new();
-        accounts.insert(
-            "target_account".to_string(),
-            SubscribeRequestFilterAccounts {
-                account: vec![self.account.to_string()],
-                owner: vec![],
-                filters: vec![],
+        if !self.target_is_program {
+            accounts.insert(
+                "target_account".to_string(),
+                SubscribeRequestFilterAccounts {
+                    account: account_keys.clone(),
+                    owner: vec![],
+                    filters: vec![],
+                },
+            );
+        }
+
+        let filter = SubscribeRequestFilterTransactions {
+            vote: Some(false), // Always exclude vote transactions
+            // Include or exclude failed transactions based on configuration
+            // When set to None, both successful and failed transactions are included
+            failed: if self.include_failed_transactions {
+                None
+            } else {
+                Some(false)
             },
-        );
+            signature: None,
+            account_include: account_keys,
+            account_exclude: vec![],
+            account_required: self.account_required.clone(),
+        };
 
         let mut transactions = HashMap::new();
-        transactions.insert(
-            "target_transactions".to_string(),
-            SubscribeRequestFilterTransactions {
-                vote: Some(false), // Always exclude vote transactions
-                // Include or exclude failed transactions based on configuration
-                // When set to None, both successful and failed transactions are included
-                failed: if self.include_failed_transactions {
-                    None
-                } else {
-                    Some(false)
-                },
-                signature: None,
-                account_include: vec![self.account.to_string()],
-                account_exclude: vec![],
-                account_required: vec![],
-            },
-        );
+        let mut transactions_status = HashMap::new();
+        if self.status_only {
+            transactions_status.insert("target_transaction_status".to_string(), filter);
+        } else {
+            transactions.insert("target_transactions".to_string(), filter);
+        }
 
         let mut slots = HashMap::new();
         slots.insert(
             "slots".to_string(),
             SubscribeRequestFilterSlots {
-                filter_by_commitment: Some(true),
+                filter_by_commitment: Some(!self.all_slot_statuses),
             },
         );
 
+        // Block metadata is opt-in since it adds an update for every slot
+        let mut blocks_meta = HashMap::new();
+        if self.subscribe_blocks_meta {
+            blocks_meta.insert("blocks_meta".to_string(), SubscribeRequestFilterBlocksMeta {});
+        }
+
         SubscribeRequest {
             accounts,
             slots,
             transactions,
-            transactions_status: HashMap::new(),
+            transactions_status,
             blocks: HashMap::new(),
-            blocks_meta: HashMap::new(),
+            blocks_meta,
             entry: HashMap::new(),
-            commitment: Some(CommitmentLevel::Confirmed as i32),
-            accounts_data_slice: vec![],
+            commitment: Some(self.commitment as i32),
+            accounts_data_slice: self
+                .account_data_slice
+                .iter()
+                .map(|&(offset, length)| SubscribeRequestAccountsDataSlice { offset, length })
+                .collect(),
             ping: None,
         }
     }
 }
+
+impl StreamSource for RpcClient {
+    fn endpoint(&self) -> &str {
+        &self.grpc_endpoint
+    }
+
+    fn commitment(&self) -> CommitmentLevel {
+        self.commitment
+    }
+
+    /// Connect to the endpoint and open the transaction subscription.
+    /// 
+    /// Rejections that retrying cannot fix (authentication, permission, or an invalid
+    /// filter) are returned as `AppError::FatalSubscription`.
+    async fn subscribe(&self) -> Result<Subscription<'_>, AppError> {
+        let mut geyser_client = self.connect().await?;
+
+        info!(
+            endpoint = %endpoint_label(&self.grpc_endpoint),
+            "Subscribing to Yellowstone gRPC stream"
+        );
+
+        // The subscription request goes first on the request stream, pings follow it
+        let (mut requests, request_stream) = mpsc::unbounded();
+        requests
+            .send(self.create_subscription_request())
+            .await
+            .map_err(|e| AppError::grpc_stream("Failed to send subscription request", e))?;
+
+        let updates = geyser_client
+            .geyser
+            .subscribe(request_stream)
+            .await
+            .map_err(|status| {
+                if !is_retryable_status(&status) {
+                    AppError::fatal_subscription("Subscription rejected", status)
+                } else {
+                    AppError::grpc_stream("Failed to create subscription", status)
+                }
+            })?
+            .into_inner();
+
+        // The client owns the channel the stream runs on, so it lives as long as the stream
+        let updates = updates
+            .inspect(move |_| {
+                let _ = &geyser_client;
+            })
+            .boxed();
+
+        Ok(Subscription::new(updates, requests))
+    }
+}
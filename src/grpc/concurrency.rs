@@ -0,0 +1,170 @@
+use crate::error::AppError;
+use crate::metrics;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// Minimum time between two multiplicative decreases.
+/// 
+/// A burst of in-flight requests often fails with 429 together; they all reflect
+/// the same overload, so only the first one shrinks the limit.
+const DECREASE_COOLDOWN: Duration = Duration::from_secs(1);
+
+/// Additive-increase/multiplicative-decrease limit on concurrent RPC fetches.
+/// 
+/// Fetches hold a permit from the inner semaphore. Every `limit` successful fetches
+/// grow the limit by one, up to `max`; a rate-limited (HTTP 429) response halves it,
+/// down to `min`. The current limit is published as `solana_tracker_rpc_concurrency_limit`.
+pub struct AdaptiveConcurrency {
+    semaphore: Arc<Semaphore>,
+    state: Mutex<ControllerState>,
+    min: usize,
+    max: usize,
+}
+
+struct ControllerState {
+    limit: usize,
+    successes: usize,
+    last_decrease: Option<Instant>,
+}
+
+impl AdaptiveConcurrency {
+    /// Create a controller that starts at `min` concurrent fetches and grows toward `max`.
+    pub fn new(min: usize, max: usize) -> Self {
+        let min = min.max(1);
+        let max = max.max(min);
+
+        metrics::RPC_CONCURRENCY_LIMIT.set(min as i64);
+
+        Self {
+            semaphore: Arc::new(Semaphore::new(min)),
+            state: Mutex::new(ControllerState {
+                limit: min,
+                successes: 0,
+                last_decrease: None,
+            }),
+            min,
+            max,
+        }
+    }
+
+    /// Wait for a fetch slot. The slot is released when the permit is dropped.
+    pub async fn acquire(&self) -> Result<OwnedSemaphorePermit, AppError> {
+        self.semaphore
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(AppError::LimiterClosed)
+    }
+
+    /// Record a fetch the RPC node served normally.
+    pub fn on_success(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        state.successes += 1;
+        if state.successes >= state.limit && state.limit < self.max {
+            state.successes = 0;
+            state.limit += 1;
+            self.semaphore.add_permits(1);
+
+            metrics::RPC_CONCURRENCY_LIMIT.set(state.limit as i64);
+            debug!(limit = state.limit, "Increased RPC fetch concurrency");
+        }
+    }
+
+    /// Record a fetch rejected by the RPC node's rate limiter.
+    pub fn on_rate_limited(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        if state
+            .last_decrease
+            .is_some_and(|at| at.elapsed() < DECREASE_COOLDOWN)
+        {
+            return;
+        }
+
+        let new_limit = (state.limit / 2).max(self.min);
+        let reduction = state.limit - new_limit;
+        state.limit = new_limit;
+        state.successes = 0;
+        state.last_decrease = Some(Instant::now());
+
+        metrics::RPC_CONCURRENCY_LIMIT.set(new_limit as i64);
+        warn!(limit = new_limit, "RPC rate limited, reducing fetch concurrency");
+
+        if reduction > 0 {
+            // Permits held by in-flight fetches can't be revoked, so retire them as they come back
+            let semaphore = self.semaphore.clone();
+            tokio::spawn(async move {
+                if let Ok(permits) = semaphore.acquire_many_owned(reduction as u32).await {
+                    permits.forget();
+                }
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limit(concurrency: &AdaptiveConcurrency) -> usize {
+        concurrency.state.lock().unwrap().limit
+    }
+
+    #[test]
+    fn clamps_bounds() {
+        let concurrency = AdaptiveConcurrency::new(0, 0);
+        assert_eq!((concurrency.min, concurrency.max), (1, 1));
+        assert_eq!(concurrency.semaphore.available_permits(), 1);
+    }
+
+    #[test]
+    fn grows_by_one_after_limit_successes() {
+        let concurrency = AdaptiveConcurrency::new(2, 3);
+
+        concurrency.on_success();
+        assert_eq!(limit(&concurrency), 2);
+        concurrency.on_success();
+        assert_eq!(limit(&concurrency), 3);
+        assert_eq!(concurrency.semaphore.available_permits(), 3);
+
+        for _ in 0..10 {
+            concurrency.on_success();
+        }
+        assert_eq!(limit(&concurrency), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn halves_on_rate_limit_down_to_min() {
+        let concurrency = AdaptiveConcurrency::new(2, 8);
+        concurrency.state.lock().unwrap().limit = 8;
+        concurrency.semaphore.add_permits(6);
+
+        concurrency.on_rate_limited();
+        assert_eq!(limit(&concurrency), 4);
+        tokio::task::yield_now().await;
+        assert_eq!(concurrency.semaphore.available_permits(), 4);
+
+        tokio::time::advance(DECREASE_COOLDOWN).await;
+        concurrency.on_rate_limited();
+        tokio::time::advance(DECREASE_COOLDOWN).await;
+        concurrency.on_rate_limited();
+        assert_eq!(limit(&concurrency), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn ignores_rate_limits_within_cooldown() {
+        let concurrency = AdaptiveConcurrency::new(1, 8);
+        concurrency.state.lock().unwrap().limit = 8;
+
+        concurrency.on_rate_limited();
+        concurrency.on_rate_limited();
+        assert_eq!(limit(&concurrency), 4);
+
+        tokio::time::advance(DECREASE_COOLDOWN).await;
+        concurrency.on_rate_limited();
+        assert_eq!(limit(&concurrency), 2);
+    }
+}
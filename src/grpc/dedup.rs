@@ -0,0 +1,167 @@
+use std::collections::{HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+
+/// Number of recent signatures remembered by default.
+/// 
+/// Redundant endpoints deliver the same transaction within seconds of each other,
+/// so a window this size covers minutes of traffic for a busy account.
+pub const DEFAULT_DEDUP_CAPACITY: usize = 100_000;
+
+/// Bounded set of recently seen signatures, shared by streams from redundant endpoints.
+/// 
+/// The first stream to deliver a signature fetches it; later copies from the other
+/// endpoints are skipped so each transaction costs one RPC fetch. A signature whose
+/// fetch fails is forgotten again, so another endpoint's copy can still carry it.
+/// Once full, the oldest signature is forgotten; a copy arriving after that is
+/// fetched again and absorbed by the database's `ON CONFLICT` handling.
+pub struct SignatureDedup {
+    state: Mutex<DedupState>,
+    capacity: usize,
+}
+
+struct DedupState {
+    seen: HashSet<String>,
+    order: VecDeque<String>,
+}
+
+impl SignatureDedup {
+    /// Create a set remembering up to `capacity` signatures.
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+
+        Self {
+            state: Mutex::new(DedupState {
+                seen: HashSet::with_capacity(capacity),
+                order: VecDeque::with_capacity(capacity),
+            }),
+            capacity,
+        }
+    }
+
+    /// Remember a signature, returning whether this is the first time it was seen.
+    pub fn first_seen(&self, signature: &str) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        if state.seen.contains(signature) {
+            return false;
+        }
+
+        if state.order.len() >= self.capacity {
+            if let Some(oldest) = state.order.pop_front() {
+                state.seen.remove(&oldest);
+            }
+        }
+
+        state.seen.insert(signature.to_string());
+        state.order.push_back(signature.to_string());
+
+        true
+    }
+
+    /// Claim a signature for this stream, or None when another stream already has it.
+    /// 
+    /// The signature is forgotten when the claim is dropped without `keep`, including
+    /// when the task holding it is aborted.
+    pub fn claim(self: &Arc<Self>, signature: &str) -> Option<DedupClaim> {
+        self.first_seen(signature).then(|| DedupClaim {
+            dedup: self.clone(),
+            signature: Some(signature.to_string()),
+        })
+    }
+
+    /// Forget a signature, so the next copy of it is treated as first seen.
+    pub fn forget(&self, signature: &str) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        if state.seen.remove(signature) {
+            if let Some(position) = state.order.iter().position(|seen| seen == signature) {
+                state.order.remove(position);
+            }
+        }
+    }
+}
+
+/// A signature claimed by one stream while it is being fetched and written.
+#[derive(Debug)]
+pub struct DedupClaim {
+    dedup: Arc<SignatureDedup>,
+    signature: Option<String>,
+}
+
+impl DedupClaim {
+    /// Keep the signature marked as seen, once it has been handled for good.
+    pub fn keep(mut self) {
+        self.signature = None;
+    }
+}
+
+impl Drop for DedupClaim {
+    fn drop(&mut self) {
+        if let Some(signature) = self.signature.take() {
+            self.dedup.forget(&signature);
+        }
+    }
+}
+
+impl std::fmt::Debug for SignatureDedup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SignatureDedup")
+            .field("capacity", &self.capacity)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeats_are_not_first_seen() {
+        let dedup = SignatureDedup::new(10);
+        assert!(dedup.first_seen("a"));
+        assert!(!dedup.first_seen("a"));
+        assert!(dedup.first_seen("b"));
+    }
+
+    #[test]
+    fn forgets_oldest_signature_at_capacity() {
+        let dedup = SignatureDedup::new(2);
+        assert!(dedup.first_seen("a"));
+        assert!(dedup.first_seen("b"));
+        assert!(dedup.first_seen("c"));
+
+        assert!(!dedup.first_seen("b"));
+        assert!(!dedup.first_seen("c"));
+        assert!(dedup.first_seen("a"));
+    }
+
+    #[test]
+    fn dropped_claim_is_forgotten() {
+        let dedup = Arc::new(SignatureDedup::new(10));
+
+        let claim = dedup.claim("a").unwrap();
+        assert!(dedup.claim("a").is_none());
+        drop(claim);
+
+        dedup.claim("a").unwrap().keep();
+        assert!(dedup.claim("a").is_none());
+    }
+
+    #[test]
+    fn forgotten_signature_frees_its_slot() {
+        let dedup = SignatureDedup::new(2);
+        assert!(dedup.first_seen("a"));
+        assert!(dedup.first_seen("b"));
+        dedup.forget("a");
+
+        assert!(dedup.first_seen("c"));
+        assert!(!dedup.first_seen("b"));
+    }
+
+    #[test]
+    fn zero_capacity_remembers_one_signature() {
+        let dedup = SignatureDedup::new(0);
+        assert!(dedup.first_seen("a"));
+        assert!(!dedup.first_seen("a"));
+    }
+}
@@ -0,0 +1,188 @@
+use crate::solana::models::ParsedTransaction;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// A fetched transaction waiting for its slot to finalize.
+#[derive(Debug, Clone)]
+pub struct PendingTransaction {
+    pub tx: ParsedTransaction,
+    /// Raw RPC response, kept when STORE_RAW_JSON is set
+    pub raw_json: Option<String>,
+}
+
+/// Holds transactions from a non-finalized stream until their slot is finalized.
+/// 
+/// Transactions are buffered by slot as they are fetched and released when a
+/// Finalized slot update arrives for that slot, so nothing that could still be
+/// rolled back is ever written. Finalized slots form a single chain, so when slot N
+/// finalizes, held slots below N that were never reported finalized were either on
+/// an abandoned fork or had their update missed across a reconnect. Those are handed
+/// back unsettled for the caller to re-check.
+#[derive(Debug, Default)]
+pub struct FinalityGate {
+    state: Mutex<GateState>,
+}
+
+#[derive(Debug, Default)]
+struct GateState {
+    pending: BTreeMap<u64, Vec<PendingTransaction>>,
+    held: usize,
+    last_finalized: Option<u64>,
+}
+
+impl FinalityGate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hold a transaction until its slot finalizes.
+    /// 
+    /// Returns the transaction back when its slot is at or below the last finalized
+    /// slot; RPC only serves such a transaction from the finalized fork, so it can be
+    /// written right away.
+    pub fn hold(&self, pending: PendingTransaction) -> Option<PendingTransaction> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        if state.last_finalized.is_some_and(|finalized| pending.tx.slot <= finalized) {
+            return Some(pending);
+        }
+
+        state.pending.entry(pending.tx.slot).or_default().push(pending);
+        state.held += 1;
+
+        None
+    }
+
+    /// Record that `slot` finalized, returning its held transactions and the held
+    /// transactions from lower slots that were never reported finalized.
+    pub fn finalize(&self, slot: u64) -> (Vec<PendingTransaction>, Vec<PendingTransaction>) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        // Slot updates can arrive out of order across reconnects; never move backwards
+        if state.last_finalized.is_some_and(|finalized| slot <= finalized) {
+            return (Vec::new(), Vec::new());
+        }
+        state.last_finalized = Some(slot);
+
+        let newer = state.pending.split_off(&(slot + 1));
+        let settled = std::mem::replace(&mut state.pending, newer);
+
+        let mut ready = Vec::new();
+        let mut unsettled = Vec::new();
+        for (held_slot, transactions) in settled {
+            if held_slot == slot {
+                ready = transactions;
+            } else {
+                unsettled.extend(transactions);
+            }
+        }
+
+        state.held -= ready.len() + unsettled.len();
+
+        (ready, unsettled)
+    }
+
+    /// Release every held transaction, lowest slot first, for settling when processing stops.
+    pub fn take_all(&self) -> Vec<PendingTransaction> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        state.held = 0;
+        std::mem::take(&mut state.pending).into_values().flatten().collect()
+    }
+
+    /// Number of transactions currently held.
+    pub fn held(&self) -> usize {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).held
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solana::parser::tests::parse_fixture;
+
+    fn pending(slot: u64) -> PendingTransaction {
+        let mut tx = parse_fixture("sol_transfer");
+        tx.slot = slot;
+        PendingTransaction { tx, raw_json: None }
+    }
+
+    fn slots(transactions: &[PendingTransaction]) -> Vec<u64> {
+        transactions.iter().map(|p| p.tx.slot).collect()
+    }
+
+    #[test]
+    fn finalize_releases_the_finalized_slot_only() {
+        let gate = FinalityGate::new();
+        assert!(gate.hold(pending(10)).is_none());
+        assert!(gate.hold(pending(10)).is_none());
+        assert!(gate.hold(pending(11)).is_none());
+        assert_eq!(gate.held(), 3);
+
+        let (ready, unsettled) = gate.finalize(10);
+        assert_eq!(slots(&ready), vec![10, 10]);
+        assert!(unsettled.is_empty());
+        assert_eq!(gate.held(), 1);
+    }
+
+    #[test]
+    fn finalize_hands_back_lower_slots_as_unsettled() {
+        let gate = FinalityGate::new();
+        gate.hold(pending(7));
+        gate.hold(pending(8));
+        gate.hold(pending(9));
+        gate.hold(pending(12));
+
+        let (ready, unsettled) = gate.finalize(9);
+        assert_eq!(slots(&ready), vec![9]);
+        assert_eq!(slots(&unsettled), vec![7, 8]);
+        assert_eq!(gate.held(), 1);
+    }
+
+    #[test]
+    fn finalize_without_held_slot_still_settles_lower_slots() {
+        let gate = FinalityGate::new();
+        gate.hold(pending(5));
+
+        let (ready, unsettled) = gate.finalize(6);
+        assert!(ready.is_empty());
+        assert_eq!(slots(&unsettled), vec![5]);
+        assert_eq!(gate.held(), 0);
+    }
+
+    #[test]
+    fn finalize_ignores_slots_at_or_below_last_finalized() {
+        let gate = FinalityGate::new();
+        gate.finalize(20);
+        gate.hold(pending(21));
+
+        for slot in [20, 19] {
+            let (ready, unsettled) = gate.finalize(slot);
+            assert!(ready.is_empty());
+            assert!(unsettled.is_empty());
+        }
+        assert_eq!(gate.held(), 1);
+    }
+
+    #[test]
+    fn take_all_empties_the_gate() {
+        let gate = FinalityGate::new();
+        gate.hold(pending(41));
+        gate.hold(pending(40));
+
+        assert_eq!(slots(&gate.take_all()), vec![40, 41]);
+        assert_eq!(gate.held(), 0);
+        assert!(gate.take_all().is_empty());
+    }
+
+    #[test]
+    fn hold_returns_transactions_from_finalized_slots() {
+        let gate = FinalityGate::new();
+        gate.finalize(30);
+
+        assert_eq!(gate.hold(pending(30)).map(|p| p.tx.slot), Some(30));
+        assert_eq!(gate.hold(pending(29)).map(|p| p.tx.slot), Some(29));
+        assert!(gate.hold(pending(31)).is_none());
+        assert_eq!(gate.held(), 1);
+    }
+}
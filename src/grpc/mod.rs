@@ -1,2 +1,9 @@
 pub mod client;
+pub mod concurrency;
+pub mod dedup;
+pub mod finality;
+pub mod preflight;
+pub mod reconcile;
+pub mod recording;
+pub mod source;
 pub mod stream_handler;
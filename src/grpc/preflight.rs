@@ -0,0 +1,118 @@
+use crate::config::Commitment;
+use crate::error::AppError;
+use crate::grpc::stream_handler::rpc_commitment;
+use solana_client::nonblocking::rpc_client::RpcClient as SolanaRpcClient;
+use solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_transaction_status::UiTransactionEncoding;
+use std::str::FromStr;
+use tracing::{info, warn};
+
+/// Check the RPC node before any stream starts.
+/// 
+/// Calls `getHealth`, then fetches the most recent signature of `account` and retrieves
+/// that transaction at `commitment`, the same round trip every streamed transaction
+/// makes. This catches nodes that report healthy but are behind or misconfigured.
+/// With `expect_program`, it also checks that `account` is an executable program.
+/// 
+/// With `strict`, a failed check aborts startup; otherwise it is logged and the
+/// indexer starts anyway.
+pub async fn run_preflight(
+    http_url: &str,
+    account: &str,
+    commitment: Commitment,
+    expect_program: bool,
+    strict: bool,
+) -> Result<(), AppError> {
+    let client = SolanaRpcClient::new(http_url.to_string());
+
+    match check_rpc(&client, account, commitment, expect_program).await {
+        Ok(()) => Ok(()),
+        Err(e) if strict => {
+            Err(AppError::Config(format!(
+                "RPC preflight failed (set PREFLIGHT_STRICT=false to start anyway): {}",
+                e
+            )))
+        }
+        Err(e) => {
+            warn!(error = %e, "RPC preflight failed, starting anyway");
+            Ok(())
+        }
+    }
+}
+
+/// Run the health check and the signature/transaction round trip.
+async fn check_rpc(
+    client: &SolanaRpcClient,
+    account: &str,
+    commitment: Commitment,
+    expect_program: bool,
+) -> Result<(), AppError> {
+    client
+        .get_health()
+        .await
+        .map_err(|e| AppError::solana_client("RPC health check failed", e))?;
+
+    let address = Pubkey::from_str(account)
+        .map_err(|e| AppError::Config(format!("Invalid account pubkey '{}': {}", account, e)))?;
+    let commitment = CommitmentConfig { commitment: rpc_commitment(commitment) };
+
+    // A wallet passed as a program would match transactions but never be invoked
+    if expect_program {
+        let target = client
+            .get_account_with_commitment(&address, commitment)
+            .await
+            .map_err(|e| AppError::solana_client("Failed to fetch the target account", e))?
+            .value;
+        if !target.is_some_and(|target| target.executable) {
+            return Err(AppError::Config(format!(
+                "TARGET_IS_PROGRAM is set but '{}' is not an executable program",
+                account
+            )));
+        }
+    }
+
+    let config = GetConfirmedSignaturesForAddress2Config {
+        before: None,
+        until: None,
+        limit: Some(1),
+        commitment: Some(commitment),
+    };
+
+    let statuses = client
+        .get_signatures_for_address_with_config(&address, config)
+        .await
+        .map_err(|e| AppError::solana_client("Failed to fetch the latest signature", e))?;
+
+    // A fresh account has nothing to fetch yet; the health check is all we can verify
+    let Some(latest) = statuses.into_iter().next() else {
+        info!(account = %account, "RPC preflight passed; account has no transactions to fetch yet");
+        return Ok(());
+    };
+
+    let signature = Signature::from_str(&latest.signature)
+        .map_err(|e| AppError::ParseError(format!("Invalid signature: {}", e)))?;
+
+    client
+        .get_transaction_with_config(
+            &signature,
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::Json),
+                commitment: Some(commitment),
+                max_supported_transaction_version: Some(0),
+            },
+        )
+        .await
+        .map_err(|e| AppError::solana_client("Failed to fetch the latest transaction", e))?;
+
+    info!(
+        account = %account,
+        signature = %latest.signature,
+        "RPC preflight passed"
+    );
+
+    Ok(())
+}
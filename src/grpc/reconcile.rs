@@ -0,0 +1,155 @@
+use crate::database::store::TransactionStore;
+use crate::error::AppError;
+use crate::grpc::concurrency::AdaptiveConcurrency;
+use crate::grpc::stream_handler::{fetch_and_process_transaction, ProcessOutcome, ProcessingOptions};
+use crate::metrics;
+use solana_client::nonblocking::rpc_client::RpcClient as SolanaRpcClient;
+use solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::time::Duration;
+use tracing::{error, info, warn};
+use yellowstone_grpc_proto::prelude::CommitmentLevel as StreamCommitment;
+
+/// Number of recent signatures compared per account on each run (the RPC maximum).
+const RECONCILE_SIGNATURE_LIMIT: usize = 1000;
+
+/// Settings for the periodic reconcile job.
+#[derive(Debug, Clone)]
+pub struct ReconcileSettings {
+    /// Accounts whose recent signatures are compared against the database
+    pub accounts: Vec<String>,
+    /// Time between runs
+    pub interval: Duration,
+    /// Fetch and persist missing signatures instead of only reporting them
+    pub backfill: bool,
+    /// Whether failed transactions are expected in the database
+    pub include_failed_transactions: bool,
+}
+
+/// Periodically compare recent on-chain signatures with the database.
+/// 
+/// Each run asks RPC for the latest finalized signatures of every monitored account
+/// and reports those missing from the transactions table through the
+/// `solana_tracker_missing_signatures` gauge. With backfill enabled, missing
+/// signatures are fetched and persisted through the normal processing path.
+/// 
+/// Transactions skipped on purpose (MIN_FEE_LAMPORTS, PROGRAM_ALLOWLIST, SAMPLE_RATE,
+/// SKIP_ZERO_BALANCE_CHANGE, STRICT_TARGET_ONLY) are never stored, so they keep showing up as missing
+/// while those filters are active.
+pub async fn run_reconciliation<S: TransactionStore>(
+    http_url: String,
+    repository: Arc<S>,
+    options: ProcessingOptions,
+    concurrency: Arc<AdaptiveConcurrency>,
+    settings: ReconcileSettings,
+) {
+    let http_client = SolanaRpcClient::new(http_url);
+    let mut interval = tokio::time::interval(settings.interval);
+
+    loop {
+        interval.tick().await;
+
+        match reconcile_once(&http_client, repository.as_ref(), &options, &concurrency, &settings).await {
+            Ok(missing) => {
+                metrics::MISSING_SIGNATURES.set(missing as i64);
+                if missing > 0 {
+                    warn!(
+                        missing_signatures = missing,
+                        backfill = settings.backfill,
+                        "Reconcile found on-chain signatures missing from the database"
+                    );
+                } else {
+                    info!("Reconcile found no missing signatures");
+                }
+            }
+            Err(e) => {
+                error!(error = %e, "Reconcile run failed");
+            }
+        }
+    }
+}
+
+/// Run one reconcile pass, returning how many signatures are still missing.
+async fn reconcile_once<S: TransactionStore>(
+    http_client: &SolanaRpcClient,
+    repository: &S,
+    options: &ProcessingOptions,
+    concurrency: &AdaptiveConcurrency,
+    settings: &ReconcileSettings,
+) -> Result<usize, AppError> {
+    let mut still_missing = 0;
+
+    for account in &settings.accounts {
+        let address = Pubkey::from_str(account)
+            .map_err(|e| AppError::Config(format!("Invalid account pubkey '{}': {}", account, e)))?;
+
+        let config = GetConfirmedSignaturesForAddress2Config {
+            before: None,
+            until: None,
+            limit: Some(RECONCILE_SIGNATURE_LIMIT),
+            commitment: Some(CommitmentConfig::finalized()),
+        };
+
+        let statuses = http_client
+            .get_signatures_for_address_with_config(&address, config)
+            .await
+            .map_err(|e| AppError::solana_client("Failed to fetch signatures", e))?;
+
+        // Failed transactions are never streamed when they are excluded
+        let signatures: Vec<String> = statuses
+            .into_iter()
+            .filter(|status| settings.include_failed_transactions || status.err.is_none())
+            .map(|status| status.signature)
+            .collect();
+
+        let existing = repository.find_existing_signatures(&signatures).await?;
+        let missing: Vec<&String> = signatures
+            .iter()
+            .filter(|signature| !existing.contains(*signature))
+            .collect();
+
+        info!(
+            account = %account,
+            checked = signatures.len(),
+            missing = missing.len(),
+            "Reconciled recent signatures"
+        );
+
+        if !settings.backfill {
+            still_missing += missing.len();
+            continue;
+        }
+
+        for signature in missing {
+            let _permit = concurrency.acquire().await?;
+
+            // Reconciled signatures are finalized, so persist them as a Finalized stream would
+            match fetch_and_process_transaction(
+                http_client,
+                signature,
+                repository,
+                StreamCommitment::Finalized,
+                options,
+                concurrency,
+            ).await {
+                Ok(ProcessOutcome::Persisted) => {
+                    info!(signature = %signature, "Backfilled missing transaction");
+                }
+                // Stored by the stream since the existence check
+                Ok(ProcessOutcome::Duplicate) => {}
+                Ok(_) => {
+                    still_missing += 1;
+                }
+                Err(e) => {
+                    still_missing += 1;
+                    warn!(signature = %signature, error = %e, "Failed to backfill missing transaction");
+                }
+            }
+        }
+    }
+
+    Ok(still_missing)
+}
@@ -0,0 +1,59 @@
+use crate::error::AppError;
+use prost::Message;
+use std::path::Path;
+use tokio::fs::{File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use yellowstone_grpc_proto::geyser::SubscribeUpdate;
+
+/// Appends raw gRPC updates to a file for later replay.
+/// 
+/// Each update is written as a length-delimited protobuf message, the same framing
+/// `prost` uses for `encode_length_delimited`, so a recording is simply a sequence
+/// of `SubscribeUpdate`s that can be read back with `read_recorded_updates`.
+pub struct UpdateRecorder {
+    file: File,
+}
+
+impl UpdateRecorder {
+    /// Open the recording file, creating it or appending to an existing recording.
+    pub async fn open(path: &Path) -> Result<Self, AppError> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await?;
+
+        Ok(Self { file })
+    }
+
+    /// Append a single update to the recording.
+    pub async fn record(&mut self, update: &SubscribeUpdate) -> Result<(), AppError> {
+        let buffer = update.encode_length_delimited_to_vec();
+        self.file.write_all(&buffer).await?;
+        Ok(())
+    }
+}
+
+/// Read every update from a recording made by `UpdateRecorder`.
+/// 
+/// Recordings are debugging artifacts of modest size, so the whole file is loaded
+/// into memory. A truncated or corrupt trailing message fails the read with the
+/// index of the update that could not be decoded.
+pub async fn read_recorded_updates(path: &Path) -> Result<Vec<SubscribeUpdate>, AppError> {
+    let data = tokio::fs::read(path).await?;
+    let mut buffer = data.as_slice();
+    let mut updates = Vec::new();
+
+    while !buffer.is_empty() {
+        let update = SubscribeUpdate::decode_length_delimited(&mut buffer).map_err(|e| {
+            AppError::ParseError(format!(
+                "Failed to decode recorded update {}: {}",
+                updates.len(),
+                e
+            ))
+        })?;
+        updates.push(update);
+    }
+
+    Ok(updates)
+}
@@ -0,0 +1,61 @@
+use crate::error::AppError;
+use futures::channel::mpsc;
+use futures::stream::BoxStream;
+use futures::{SinkExt, StreamExt};
+use std::future::Future;
+use tonic::Status;
+use yellowstone_grpc_proto::geyser::{SubscribeRequest, SubscribeRequestPing, SubscribeUpdate};
+use yellowstone_grpc_proto::prelude::CommitmentLevel;
+
+/// A source of subscription updates for the stream handler.
+/// 
+/// The handler only needs to open a subscription, read updates from it, and send
+/// keep-alive pings, so anything that can do that (the Yellowstone client, a
+/// recording, or a fake for tests) can drive the reconnect loop and processing path.
+pub trait StreamSource: Send + Sync {
+    /// Endpoint URL identifying this source in logs and metric labels.
+    fn endpoint(&self) -> &str;
+
+    /// Commitment level the subscription delivers updates at.
+    fn commitment(&self) -> CommitmentLevel;
+
+    /// Connect and subscribe, returning the live subscription.
+    /// 
+    /// Errors that retrying cannot fix should be returned as `AppError::FatalSubscription`
+    /// so the handler stops instead of reconnecting.
+    fn subscribe(&self) -> impl Future<Output = Result<Subscription<'_>, AppError>> + Send;
+}
+
+/// An open subscription: updates from the server and a channel for requests to it.
+pub struct Subscription<'a> {
+    updates: BoxStream<'a, Result<SubscribeUpdate, Status>>,
+    requests: mpsc::UnboundedSender<SubscribeRequest>,
+}
+
+impl<'a> Subscription<'a> {
+    /// Wrap a stream of updates and the sender feeding the server's request stream.
+    pub fn new(
+        updates: BoxStream<'a, Result<SubscribeUpdate, Status>>,
+        requests: mpsc::UnboundedSender<SubscribeRequest>,
+    ) -> Self {
+        Self { updates, requests }
+    }
+
+    /// Wait for the next update, or None once the server has ended the stream.
+    pub async fn next_update(&mut self) -> Option<Result<SubscribeUpdate, Status>> {
+        self.updates.next().await
+    }
+
+    /// Send a ping message to keep the stream alive.
+    pub async fn ping(&mut self) -> Result<(), AppError> {
+        let ping_request = SubscribeRequest {
+            ping: Some(SubscribeRequestPing { id: 1 }),
+            ..Default::default()
+        };
+
+        self.requests
+            .send(ping_request)
+            .await
+            .map_err(|e| AppError::grpc_stream("Failed to send ping", e))
+    }
+}
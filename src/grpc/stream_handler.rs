@@ -1,39 +1,215 @@
-use crate::database::repository::TransactionRepository;
-use crate::error::AppError;
-use crate::grpc::client::RpcClient;
+use crate::config::Commitment;
+use crate::database::store::TransactionStore;
+use crate::error::{is_rate_limited, is_retryable_status, AppError};
+use crate::grpc::concurrency::AdaptiveConcurrency;
+use crate::grpc::dedup::SignatureDedup;
+use crate::grpc::finality::{FinalityGate, PendingTransaction};
+use crate::grpc::recording::{read_recorded_updates, UpdateRecorder};
+use crate::grpc::source::StreamSource;
+use crate::labels::endpoint_label;
 use crate::metrics;
-use crate::solana::parser::parse_transaction;
-use futures::{SinkExt, StreamExt};
+use crate::solana::models::{BlockMeta, ParsedTransaction};
+use crate::solana::parser::{parse_transaction, ParseOptions};
+use prometheus::IntGauge;
+use solana_client::client_error::{ClientError, ClientErrorKind};
 use solana_client::nonblocking::rpc_client::RpcClient as SolanaRpcClient;
+use solana_client::rpc_request::RpcError;
 use solana_sdk::commitment_config::{CommitmentConfig, CommitmentLevel};
+use solana_sdk::signature::Signature;
 use solana_transaction_status::UiTransactionEncoding;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use tokio::time::{sleep, Duration};
+use tokio::task::JoinSet;
+use tokio::time::{sleep, sleep_until, timeout, timeout_at, Duration, Instant};
 use tracing::{debug, error, info, warn};
 use yellowstone_grpc_proto::geyser::subscribe_update::UpdateOneof;
+use yellowstone_grpc_proto::geyser::{SubscribeUpdate, SubscribeUpdateBlockMeta};
+use yellowstone_grpc_proto::prelude::CommitmentLevel as StreamCommitment;
+use yellowstone_grpc_proto::prelude::RewardType;
+
+/// Wait between Finalized fetches of a transaction that was not found yet.
+const NOT_FOUND_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// Options controlling how fetched transactions are processed before persistence.
+#[derive(Debug, Clone, Default)]
+pub struct ProcessingOptions {
+    /// Maximum number of balance changes stored per transaction (None = unlimited)
+    pub max_balance_changes: Option<usize>,
+    
+    /// Commitment for RPC fetches (None = match the stream commitment)
+    pub fetch_commitment: Option<Commitment>,
+    
+    /// Options passed through to the transaction parser
+    pub parse: ParseOptions,
+    
+    /// File to append raw gRPC updates to for later replay (None = no recording)
+    pub record_path: Option<PathBuf>,
+    
+    /// Transactions with a lower fee are not persisted (0 = keep everything)
+    pub min_fee_lamports: u64,
+    
+    /// Only transactions invoking one of these programs are persisted (None = keep everything)
+    pub program_allowlist: Option<HashSet<String>>,
+    
+    /// Wait this long after a signature is seen on the stream before fetching it (None = no wait)
+    pub fetch_delay: Option<Duration>,
+    
+    /// Give up on a transaction that takes longer than this to fetch and persist (None = no limit)
+    pub process_timeout: Option<Duration>,
+    
+    /// Log a warning for any transaction write slower than this (None = no logging)
+    pub slow_db_threshold: Option<Duration>,
+    
+    /// Fraction of transactions to fetch and persist, chosen by signature (None = keep everything)
+    pub sample_rate: Option<f64>,
+    
+    /// Skip transactions that produced no (kept) balance changes
+    pub skip_zero_balance_changes: bool,
+    
+    /// Also store the full RPC response of each persisted transaction in raw_transactions
+    pub store_raw_json: bool,
+    
+    /// Only persist transactions one of these accounts signed, changed balance in, or was invoked in as a program (None = keep everything)
+    pub strict_target_accounts: Option<HashSet<String>>,
+    
+    /// Abort fetches still in flight this long after processing stops (None = wait for all)
+    pub drain_timeout: Option<Duration>,
+    
+    /// Commitment whose slot updates mark the chain head for the slot processing gap (None = the stream's)
+    pub slot_commitment: Option<Commitment>,
+    
+    /// Finalized fetches answered "not found" this many times are tried once more at Confirmed (None = no retry)
+    pub confirmed_fallback_after: Option<u32>,
+    
+    /// Reconnect a stream that has been connected this long, even if healthy (None = keep it open)
+    pub max_connection_age: Option<Duration>,
+    
+    /// Stop with an error after this many consecutive failed reconnects (None = retry forever)
+    pub max_reconnect_attempts: Option<u32>,
+    
+    /// Signatures already delivered by another endpoint's stream are skipped (None = no dedup)
+    pub dedup: Option<Arc<SignatureDedup>>,
+    
+    /// Transactions from a non-finalized stream are held here until their slot finalizes (None = write immediately)
+    pub finality_gate: Option<Arc<FinalityGate>>,
+    
+    /// Metric label per monitored account, keyed by raw pubkey bytes (None = no per-account metrics)
+    pub account_metric_labels: Option<Arc<HashMap<Vec<u8>, String>>>,
+}
+
+/// Result of processing a single transaction signature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ProcessOutcome {
+    /// The transaction was fetched, parsed, and written to the database
+    Persisted,
+    /// The transaction was already stored, so nothing was written
+    Duplicate,
+    /// The transaction was fetched and parsed, and is held until its slot finalizes
+    Buffered,
+    /// RPC could not serve the transaction and it was recorded as missed
+    Missed,
+    /// The transaction was excluded by the target, fee, program, or zero-balance-change filters and not stored
+    Filtered,
+    /// RPC returned a different transaction than requested and it was not stored
+    SignatureMismatch,
+    /// The signature fell outside SAMPLE_RATE; it was counted but never fetched
+    SampledOut,
+}
 
 /// Process account transactions by subscribing to Yellowstone gRPC stream.
 /// 
 /// This function continuously monitors the target account via gRPC subscription,
 /// fetches full transaction details via RPC, parses them, and stores to the database.
 /// It implements reconnection logic with exponential backoff for transient failures.
-pub async fn process_account_stream(
-    rpc_client: RpcClient,
+/// 
+/// Subscription rejections that retrying cannot fix (authentication, permission, or
+/// an invalid filter) are returned as `AppError::FatalSubscription` so the process
+/// exits and the orchestrator restarts it or alerts, rather than looping forever.
+/// Other errors that `AppError::is_retryable` rejects stop the stream the same way,
+/// as does exceeding `max_reconnect_attempts` consecutive failures without the stream
+/// connecting. In-flight fetches are drained for up to `drain_timeout` before the
+/// error is returned.
+pub async fn process_account_stream<S: TransactionStore, C: StreamSource>(
+    rpc_client: C,
     http_url: &str,
-    repository: Arc<TransactionRepository>,
+    repository: Arc<S>,
+    options: ProcessingOptions,
+    concurrency: Arc<AdaptiveConcurrency>,
 ) -> Result<(), AppError> {
     let mut reconnect_attempts = 0;
+    // Unlike reconnect_attempts, reset whenever a connection is established
+    let mut consecutive_failures = 0;
     let max_reconnect_delay = Duration::from_secs(300); // 5 minutes
 
+    let connected = metrics::ENDPOINT_CONNECTED.with_label_values(&[
+        &endpoint_label(rpc_client.endpoint()),
+        &rpc_client.commitment().as_str_name().to_lowercase(),
+    ]);
+
+    // Create HTTP RPC client for fetching full transaction details
+    debug!("Creating HTTP RPC client for transaction fetching");
+    let context = Arc::new(FetchContext {
+        http_client: SolanaRpcClient::new(http_url.to_string()),
+        repository,
+        stream_commitment: rpc_client.commitment(),
+        options,
+        concurrency,
+        transaction_count: AtomicU64::new(0),
+        latest_slot: AtomicU64::new(0),
+    });
+
+    // Owned here rather than per connection so in-flight fetches survive reconnects
+    let mut fetches = JoinSet::new();
+
     loop {
-        match subscribe_and_process(&rpc_client, http_url, repository.clone()).await {
+        match subscribe_and_process(&rpc_client, &context, &mut fetches, &connected).await {
             Ok(()) => {
                 info!("Stream ended normally, reconnecting...");
                 reconnect_attempts = 0;
                 metrics::STREAM_CONNECTED.set(0);
+                connected.set(0);
                 metrics::STREAM_RECONNECTIONS.inc();
             }
+            Err(e) if !e.is_retryable() => {
+                metrics::STREAM_CONNECTED.set(0);
+                connected.set(0);
+                if matches!(e, AppError::FatalSubscription { .. }) {
+                    metrics::FATAL_SUBSCRIPTION_ERRORS.inc();
+                }
+                error!(
+                    error = %e,
+                    "Stream failed with a non-retryable error, stopping stream processing"
+                );
+                stop_processing(&context, &mut fetches).await;
+                return Err(e);
+            }
             Err(e) => {
+                // The connection gauge is still set if this attempt got as far as streaming
+                if connected.get() == 1 {
+                    consecutive_failures = 0;
+                }
+                consecutive_failures += 1;
+
+                if let Some(max) = context.options.max_reconnect_attempts {
+                    if consecutive_failures > max {
+                        metrics::STREAM_CONNECTED.set(0);
+                        connected.set(0);
+                        error!(
+                            error = %e,
+                            endpoint = %endpoint_label(rpc_client.endpoint()),
+                            max_reconnect_attempts = max,
+                            "Stream failed to reconnect after MAX_RECONNECT_ATTEMPTS consecutive attempts, giving up"
+                        );
+                        stop_processing(&context, &mut fetches).await;
+                        return Err(AppError::ReconnectLimitExceeded {
+                            attempts: max,
+                            source: Box::new(e),
+                        });
+                    }
+                }
+
                 reconnect_attempts += 1;
                 let delay = calculate_backoff_delay(reconnect_attempts, max_reconnect_delay);
                 
@@ -45,6 +221,7 @@ pub async fn process_account_stream(
                 );
 
                 metrics::STREAM_CONNECTED.set(0);
+                connected.set(0);
                 metrics::STREAM_RECONNECTIONS.inc();
                 sleep(delay).await;
             }
@@ -53,181 +230,1059 @@ pub async fn process_account_stream(
 }
 
 /// Subscribe to gRPC stream and process transaction updates.
-async fn subscribe_and_process(
-    rpc_client: &RpcClient,
-    http_url: &str,
-    repository: Arc<TransactionRepository>,
+/// 
+/// Returns `Ok(())` when the server ends the stream, or once the connection reaches
+/// `max_connection_age` so the caller reconnects on a fresh connection.
+async fn subscribe_and_process<S: TransactionStore, C: StreamSource>(
+    rpc_client: &C,
+    context: &Arc<FetchContext<S>>,
+    fetches: &mut JoinSet<()>,
+    connected: &IntGauge,
 ) -> Result<(), AppError> {
-    // Connect to Yellowstone gRPC
-    let mut geyser_client = rpc_client.connect().await?;
-    
-    // Create HTTP RPC client for fetching full transaction details
-    debug!("Creating HTTP RPC client for transaction fetching");
-    let http_client = SolanaRpcClient::new(http_url.to_string());
-
-    info!("Subscribing to Yellowstone gRPC stream");
-
-    // Create subscription request
-    let request = rpc_client.create_subscription_request();
-
-    // Subscribe to the stream
-    let (mut subscribe_tx, mut stream) = geyser_client
-        .subscribe()
-        .await
-        .map_err(|e| AppError::GrpcStream(format!("Failed to create subscription: {}", e)))?;
-
-    // Send the subscription request
-    subscribe_tx
-        .send(request)
-        .await
-        .map_err(|e| AppError::GrpcStream(format!("Failed to send subscription request: {}", e)))?;
+    // Connect and send the subscription request
+    let mut subscription = rpc_client.subscribe().await?;
 
     info!("Processing transaction updates from gRPC stream");
 
     // Mark stream as connected
     metrics::STREAM_CONNECTED.set(1);
+    connected.set(1);
 
-    let mut transaction_count = 0u64;
-    let mut last_ping = tokio::time::Instant::now();
+    let mut last_ping = Instant::now();
     let ping_interval = Duration::from_secs(30);
 
-    while let Some(message) = stream.next().await {
+    let mut recorder = match &context.options.record_path {
+        Some(path) => Some(UpdateRecorder::open(path).await?),
+        None => None,
+    };
+
+    // Recycled preventively, since long-lived connections can degrade without erroring
+    let recycle_at = context.options.max_connection_age.map(|age| Instant::now() + age);
+
+    loop {
+        let message = match recycle_at {
+            Some(recycle_at) => tokio::select! {
+                message = subscription.next_update() => message,
+                _ = sleep_until(recycle_at) => {
+                    info!(
+                        endpoint = %endpoint_label(rpc_client.endpoint()),
+                        "Connection reached GRPC_MAX_CONN_AGE_SECS, recycling"
+                    );
+                    return Ok(());
+                }
+            },
+            None => subscription.next_update().await,
+        };
+        let Some(message) = message else {
+            break;
+        };
+
         // Handle potential stream errors
-        let update = message
-            .map_err(|e| AppError::GrpcStream(format!("Stream error: {}", e)))?;
+        let update = message.map_err(|status| {
+            if !is_retryable_status(&status) {
+                AppError::fatal_subscription("Stream rejected", status)
+            } else {
+                AppError::grpc_stream("Stream error", status)
+            }
+        })?;
 
         // Send periodic pings to keep the connection alive
         if last_ping.elapsed() >= ping_interval {
-            send_ping(&mut subscribe_tx).await?;
-            last_ping = tokio::time::Instant::now();
-        }
-
-        // Process the update based on its type
-        match update.update_oneof {
-            Some(UpdateOneof::Transaction(transaction_update)) => {
-                // Extract transaction signature
-                let signature = if let Some(tx) = &transaction_update.transaction {
-                    if !tx.signature.is_empty() {
-                        bs58::encode(&tx.signature).into_string()
-                    } else {
-                        warn!("Transaction update missing signature");
-                        continue;
-                    }
-                } else {
-                    warn!("Transaction update missing transaction data");
-                    continue;
-                };
-
-                // Track processing time
-                let timer = metrics::TRANSACTION_PROCESSING_TIME.start_timer();
-
-                // Fetch and process full transaction details
-                match fetch_and_process_transaction(
-                    &http_client,
-                    &signature,
-                    &repository,
-                ).await {
-                    Ok(()) => {
-                        transaction_count += 1;
-                        metrics::TRANSACTIONS_PROCESSED.inc();
-                        metrics::LAST_TRANSACTION_TIMESTAMP.set(chrono::Utc::now().timestamp() as f64);
-                        timer.observe_duration();
-
-                        if transaction_count % 10 == 0 {
-                            info!(
-                                transactions_processed = transaction_count,
-                                "Processing transactions"
-                            );
-                        }
-                    }
-                    Err(e) => {
-                        metrics::TRANSACTIONS_FAILED.inc();
-                        timer.observe_duration();
-                        warn!(
-                            signature = %signature,
-                            error = %e,
-                            "Failed to process transaction"
-                        );
-                    }
+            // Count ping failures separately so they can be told apart from data errors
+            if let Err(e) = subscription.ping().await {
+                metrics::PING_FAILURES.inc();
+                return Err(e);
+            }
+            last_ping = Instant::now();
+        }
+
+        // Keep a copy of the raw update for later replay
+        if let Some(recorder) = recorder.as_mut() {
+            if let Err(e) = recorder.record(&update).await {
+                warn!(error = %e, "Failed to record gRPC update");
+            }
+        }
+
+        process_update(update, context, fetches).await?;
+    }
+
+    Ok(())
+}
+
+/// Shared state for fetching and persisting transactions from spawned tasks.
+struct FetchContext<S: TransactionStore> {
+    http_client: SolanaRpcClient,
+    repository: Arc<S>,
+    stream_commitment: StreamCommitment,
+    options: ProcessingOptions,
+    concurrency: Arc<AdaptiveConcurrency>,
+    transaction_count: AtomicU64,
+    /// Highest slot reported at the slot commitment (0 until the first slot update)
+    latest_slot: AtomicU64,
+}
+
+/// Decision for a single update from the gRPC stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum UpdateAction {
+    /// Fetch the full transaction with this signature over RPC and persist it.
+    /// Carries the raw account keys the update listed, including lookup table addresses
+    FetchTransaction {
+        signature: String,
+        account_keys: Vec<Vec<u8>>,
+        slot: u64,
+    },
+    /// Persist block metadata from the blocks_meta subscription
+    StoreBlockMeta(BlockMeta),
+    /// The transaction with this signature reached the stream's commitment level
+    TransactionStatus(String),
+    /// This slot reached the given status (a gRPC `CommitmentLevel`)
+    Slot { slot: u64, status: i32 },
+    /// A transaction or status update arrived without data it needs; holds the metric reason
+    Incomplete(&'static str),
+    /// Nothing to fetch (pongs and other update types)
+    Skip,
+}
+
+/// Decide what to do with a single update from the gRPC stream.
+/// 
+/// This performs no I/O so the decision logic can be exercised with synthetic
+/// updates; fetching and persistence are left to `process_update`. Incomplete
+/// transaction updates are returned as `Incomplete` for the caller to log and count.
+fn handle_update(update: SubscribeUpdate) -> UpdateAction {
+    match update.update_oneof {
+        Some(UpdateOneof::Transaction(transaction_update)) => {
+            // Extract transaction signature
+            let Some(tx) = transaction_update.transaction else {
+                return UpdateAction::Incomplete("missing_transaction");
+            };
+
+            if tx.signature.is_empty() {
+                return UpdateAction::Incomplete("missing_signature");
+            }
+
+            // Static keys plus addresses loaded from lookup tables, the full set the filter matched on
+            let mut account_keys = tx
+                .transaction
+                .and_then(|transaction| transaction.message)
+                .map(|message| message.account_keys)
+                .unwrap_or_default();
+            if let Some(meta) = tx.meta {
+                account_keys.extend(meta.loaded_writable_addresses);
+                account_keys.extend(meta.loaded_readonly_addresses);
+            }
+
+            UpdateAction::FetchTransaction {
+                signature: bs58::encode(&tx.signature).into_string(),
+                account_keys,
+                slot: transaction_update.slot,
+            }
+        }
+        Some(UpdateOneof::TransactionStatus(status_update)) => {
+            if status_update.signature.is_empty() {
+                return UpdateAction::Incomplete("missing_signature");
+            }
+
+            UpdateAction::TransactionStatus(bs58::encode(&status_update.signature).into_string())
+        }
+        Some(UpdateOneof::Slot(slot_update)) => {
+            debug!(
+                slot = slot_update.slot,
+                status = ?slot_update.status,
+                "Received slot update"
+            );
+            UpdateAction::Slot {
+                slot: slot_update.slot,
+                status: slot_update.status,
+            }
+        }
+        Some(UpdateOneof::BlockMeta(block_meta)) => {
+            UpdateAction::StoreBlockMeta(block_meta_from_update(block_meta))
+        }
+        Some(UpdateOneof::Pong(_)) => {
+            debug!("Received pong response");
+            UpdateAction::Skip
+        }
+        _ => {
+            // Ignore other update types (account, block, etc.)
+            UpdateAction::Skip
+        }
+    }
+}
+
+/// Convert a blocks_meta update into our block model.
+/// 
+/// The leader is not part of the update, so it is taken from the recipient of the
+/// block's fee reward, which always goes to the slot leader.
+fn block_meta_from_update(update: SubscribeUpdateBlockMeta) -> BlockMeta {
+    let rewards = update.rewards.map(|r| r.rewards).unwrap_or_default();
+
+    let leader = rewards
+        .iter()
+        .find(|reward| reward.reward_type == RewardType::Fee as i32)
+        .map(|reward| reward.pubkey.clone());
+
+    BlockMeta {
+        slot: update.slot,
+        blockhash: update.blockhash,
+        parent_slot: update.parent_slot,
+        block_height: update.block_height.map(|h| h.block_height),
+        block_time: update
+            .block_time
+            .and_then(|t| chrono::DateTime::from_timestamp(t.timestamp, 0)),
+        leader,
+        rewards_lamports: rewards.iter().map(|reward| reward.lamports).sum(),
+    }
+}
+
+/// Process a single update from the gRPC stream, live or replayed.
+/// 
+/// Transaction updates are fetched and persisted on a spawned task once a fetch slot
+/// is free, so up to the adaptive concurrency limit run at once. Waiting for the slot
+/// here applies backpressure to the stream when RPC falls behind.
+async fn process_update<S: TransactionStore>(
+    update: SubscribeUpdate,
+    context: &Arc<FetchContext<S>>,
+    fetches: &mut JoinSet<()>,
+) -> Result<(), AppError> {
+    // Reap finished fetches so the set doesn't grow without bound
+    while fetches.try_join_next().is_some() {}
+
+    let (signature, account_keys, slot) = match handle_update(update) {
+        UpdateAction::FetchTransaction { signature, account_keys, slot } => (signature, account_keys, slot),
+        UpdateAction::StoreBlockMeta(block) => {
+            // Blocks arrive once per slot, so they are stored inline rather than spawned
+            match context.repository.insert_block_meta(&block).await {
+                Ok(()) => metrics::BLOCKS_RECORDED.inc(),
+                Err(e) => warn!(slot = block.slot, error = %e, "Failed to store block metadata"),
+            }
+            return Ok(());
+        }
+        UpdateAction::TransactionStatus(signature) => {
+            // Only a Finalized status subscription says anything the stored row doesn't
+            if context.stream_commitment == StreamCommitment::Finalized {
+                match context.repository.mark_finalized(&signature).await {
+                    Ok(true) => metrics::TRANSACTIONS_FINALIZED.inc(),
+                    Ok(false) => debug!(signature = %signature, "Finalized transaction not stored or already marked"),
+                    Err(e) => warn!(signature = %signature, error = %e, "Failed to mark transaction finalized"),
                 }
             }
-            Some(UpdateOneof::Slot(slot_update)) => {
-                debug!(
-                    slot = slot_update.slot,
-                    status = ?slot_update.status,
-                    "Received slot update"
+            return Ok(());
+        }
+        UpdateAction::Slot { slot, status } => {
+            // The chain head as seen by this stream, for the slot processing gap. With a
+            // separate SLOT_COMMITMENT every status of every slot arrives, so only the
+            // status of interest advances the head
+            let head_commitment = context
+                .options
+                .slot_commitment
+                .map(StreamCommitment::from)
+                .unwrap_or(context.stream_commitment);
+            if status == head_commitment as i32 {
+                context.latest_slot.fetch_max(slot, Ordering::Relaxed);
+            }
+
+            if status == StreamCommitment::Finalized as i32 {
+                if let Some(gate) = &context.options.finality_gate {
+                    release_finalized(gate, slot, context, fetches);
+                }
+            }
+            return Ok(());
+        }
+        UpdateAction::Incomplete(reason) => {
+            warn!(reason = reason, "Skipping incomplete update");
+            metrics::INCOMPLETE_UPDATES.with_label_values(&[reason]).inc();
+            return Ok(());
+        }
+        UpdateAction::Skip => return Ok(()),
+    };
+
+    // A redundant endpoint's stream already delivered this signature
+    let claim = match &context.options.dedup {
+        Some(dedup) => match dedup.claim(&signature) {
+            Some(claim) => Some(claim),
+            None => {
+                metrics::DUPLICATE_UPDATES.inc();
+                return Ok(());
+            }
+        },
+        None => None,
+    };
+
+    // Monitored accounts the transaction involves, for per-account throughput
+    let accounts: Vec<String> = match &context.options.account_metric_labels {
+        Some(labels) => {
+            let mut accounts: Vec<String> = account_keys
+                .iter()
+                .filter_map(|key| labels.get(key).cloned())
+                .collect();
+            accounts.dedup();
+            accounts
+        }
+        None => Vec::new(),
+    };
+
+    // Received but not yet fully processed, including time spent waiting for a slot
+    metrics::STREAM_PROCESSING_BACKLOG.inc();
+
+    // Measured from receipt, so time spent waiting for a fetch slot counts toward the delay
+    let ready_at = context.options.fetch_delay.map(|delay| Instant::now() + delay);
+
+    // Tracked until the fetch slot is granted, exposing backpressure before updates pile up
+    let queued = metrics::QueuedUpdate::enqueue();
+    let permit = context.concurrency.acquire().await?;
+    queued.dequeue();
+    let context = context.clone();
+
+    fetches.spawn(async move {
+        // Give freshly confirmed transactions time to become available over RPC
+        if let Some(ready_at) = ready_at {
+            sleep_until(ready_at).await;
+        }
+
+        // Released on failure (or abort) so another endpoint's copy can still carry it
+        if process_transaction(&context, &signature, slot, &accounts).await {
+            if let Some(claim) = claim {
+                claim.keep();
+            }
+        }
+        drop(permit);
+        metrics::STREAM_PROCESSING_BACKLOG.dec();
+    });
+
+    Ok(())
+}
+
+/// Write the transactions held for a newly finalized slot.
+/// 
+/// The writes run on a spawned task, in the order the transactions were fetched, so
+/// the stream keeps reading while they complete. Held transactions from lower slots
+/// that were never reported finalized are re-checked first; see `settle_unfinalized`.
+fn release_finalized<S: TransactionStore>(
+    gate: &FinalityGate,
+    slot: u64,
+    context: &Arc<FetchContext<S>>,
+    fetches: &mut JoinSet<()>,
+) {
+    let (ready, unsettled) = gate.finalize(slot);
+    metrics::PENDING_FINALIZATION.set(gate.held() as i64);
+
+    if ready.is_empty() && unsettled.is_empty() {
+        return;
+    }
+
+    if !unsettled.is_empty() {
+        warn!(
+            slot = slot,
+            transactions = unsettled.len(),
+            "Re-checking held transactions from slots that were never reported finalized"
+        );
+    }
+    debug!(slot = slot, transactions = ready.len(), "Writing transactions for finalized slot");
+
+    let context = context.clone();
+    fetches.spawn(async move {
+        settle_unfinalized(&context, unsettled).await;
+        for pending in ready {
+            write_held(&context, &pending).await;
+        }
+    });
+}
+
+/// Largest number of signatures `getSignatureStatuses` accepts per request.
+const MAX_SIGNATURE_STATUSES: usize = 256;
+
+/// Settle held transactions whose slot was passed over by finalization.
+/// 
+/// The slot was either on an abandoned fork or its finalized update was missed
+/// across a reconnect, so each signature's status is fetched at Finalized. One
+/// finalized in the slot it was fetched from is written; the rest are counted in
+/// UNFINALIZED_DROPPED and recorded as missed with reason `unfinalized`.
+async fn settle_unfinalized<S: TransactionStore>(
+    context: &FetchContext<S>,
+    unsettled: Vec<PendingTransaction>,
+) {
+    for chunk in unsettled.chunks(MAX_SIGNATURE_STATUSES) {
+        // An unparseable signature looks up as unknown and is recorded as missed
+        let signatures: Vec<Signature> = chunk
+            .iter()
+            .map(|pending| pending.tx.signature.parse().unwrap_or_default())
+            .collect();
+
+        let (statuses, failure) = match context.http_client.get_signature_statuses(&signatures).await {
+            Ok(response) => (response.value, None),
+            Err(e) => {
+                warn!(
+                    transactions = chunk.len(),
+                    error = %e,
+                    "Failed to re-check unfinalized transactions, recording them as missed"
                 );
+                (Vec::new(), Some(e.to_string()))
             }
-            Some(UpdateOneof::Pong(_)) => {
-                debug!("Received pong response");
+        };
+
+        for (index, pending) in chunk.iter().enumerate() {
+            let status = statuses.get(index).cloned().flatten();
+            let finalized = status.as_ref().is_some_and(|status| {
+                status.slot == pending.tx.slot
+                    && status.satisfies_commitment(CommitmentConfig::finalized())
+            });
+            if finalized {
+                write_held(context, pending).await;
+                continue;
             }
-            _ => {
-                // Ignore other update types (account, block, etc.)
+
+            let message = match (&failure, &status) {
+                (Some(error), _) => format!("Status re-check failed: {}", error),
+                (None, Some(status)) => format!(
+                    "Slot {} was never finalized; status reported slot {}",
+                    pending.tx.slot, status.slot
+                ),
+                (None, None) => format!("Slot {} was never finalized", pending.tx.slot),
+            };
+            metrics::UNFINALIZED_DROPPED.inc();
+            if let Err(e) = context
+                .repository
+                .record_missed_transaction(&pending.tx.signature, "unfinalized", &message)
+                .await
+            {
+                warn!(signature = %pending.tx.signature, error = %e, "Failed to record unfinalized transaction");
             }
         }
     }
+}
 
-    Ok(())
+/// Write one transaction released by the finality gate.
+async fn write_held<S: TransactionStore>(context: &FetchContext<S>, pending: &PendingTransaction) {
+    match write_transaction(
+        context.repository.as_ref(),
+        &pending.tx,
+        pending.raw_json.as_deref(),
+        false,
+        &context.options,
+    ).await {
+        Ok(true) => metrics::LAST_TRANSACTION_TIMESTAMP.set(chrono::Utc::now().timestamp() as f64),
+        Ok(false) => {}
+        Err(e) => {
+            metrics::TRANSACTIONS_FAILED.inc();
+            warn!(signature = %pending.tx.signature, error = %e, "Failed to write finalized transaction");
+        }
+    }
 }
 
-/// Send a ping message to keep the stream alive.
-async fn send_ping<S>(subscribe_tx: &mut S) -> Result<(), AppError>
-where
-    S: SinkExt<yellowstone_grpc_proto::geyser::SubscribeRequest> + Unpin,
-    S::Error: std::fmt::Display,
-{
-    use yellowstone_grpc_proto::geyser::{SubscribeRequest, SubscribeRequestPing};
-    
-    let ping_request = SubscribeRequest {
-        ping: Some(SubscribeRequestPing { id: 1 }),
-        ..Default::default()
+/// Fetch and persist one transaction, recording metrics for the outcome.
+/// 
+/// Failures are logged and counted rather than returned so one bad transaction
+/// never interrupts the stream. `accounts` are the metric labels of the monitored
+/// accounts the transaction involves.
+/// 
+/// Returns whether the transaction was handled for good. It is false when the fetch
+/// failed, timed out, was recorded as missed, or returned the wrong transaction, so
+/// another copy of the signature is still worth fetching.
+async fn process_transaction<S: TransactionStore>(
+    context: &FetchContext<S>,
+    signature: &str,
+    slot: u64,
+    accounts: &[String],
+) -> bool {
+    // Track processing time
+    let timer = metrics::TRANSACTION_PROCESSING_TIME.start_timer();
+
+    // Fetch and process full transaction details
+    let processing = fetch_and_process_transaction(
+        &context.http_client,
+        signature,
+        context.repository.as_ref(),
+        context.stream_commitment,
+        &context.options,
+        &context.concurrency,
+    );
+
+    // Cap worst-case latency so one pathological transaction can't hold a fetch slot forever
+    let result = match context.options.process_timeout {
+        Some(limit) => match timeout(limit, processing).await {
+            Ok(result) => result,
+            Err(_) => {
+                metrics::PROCESSING_TIMEOUTS.inc();
+                timer.observe_duration();
+                warn!(
+                    signature = %signature,
+                    timeout_seconds = limit.as_secs(),
+                    "Transaction processing timed out, recording as missed"
+                );
+
+                let message = format!("Processing exceeded {} seconds", limit.as_secs());
+                if let Err(e) = context
+                    .repository
+                    .record_missed_transaction(signature, "processing_timeout", &message)
+                    .await
+                {
+                    warn!(signature = %signature, error = %e, "Failed to record timed-out transaction");
+                }
+                return false;
+            }
+        },
+        None => processing.await,
     };
 
-    subscribe_tx
-        .send(ping_request)
-        .await
-        .map_err(|e| AppError::GrpcStream(format!("Failed to send ping: {}", e)))?;
+    match result {
+        Ok(ProcessOutcome::Missed) | Ok(ProcessOutcome::SignatureMismatch) => {
+            timer.observe_duration();
+            false
+        }
+        Ok(ProcessOutcome::Duplicate) | Ok(ProcessOutcome::Filtered) => {
+            timer.observe_duration();
+            true
+        }
+        Ok(ProcessOutcome::SampledOut) => {
+            // Counted as processed so throughput stays comparable to unsampled runs
+            metrics::TRANSACTIONS_PROCESSED.inc();
+            metrics::record_processed_for_accounts(accounts);
+            timer.stop_and_discard();
+            true
+        }
+        Ok(outcome @ (ProcessOutcome::Persisted | ProcessOutcome::Buffered)) => {
+            let transaction_count = context.transaction_count.fetch_add(1, Ordering::Relaxed) + 1;
+            metrics::TRANSACTIONS_PROCESSED.inc();
+            metrics::record_processed_for_accounts(accounts);
+            // No gap until a slot update has reported the head; a transaction can also
+            // arrive before its own slot's update, which counts as no gap
+            let latest_slot = context.latest_slot.load(Ordering::Relaxed);
+            if latest_slot > 0 {
+                metrics::SLOT_PROCESSING_GAP.observe(latest_slot.saturating_sub(slot) as f64);
+            }
+            // Held transactions set it when they are written, once their slot finalizes
+            if outcome == ProcessOutcome::Persisted {
+                metrics::LAST_TRANSACTION_TIMESTAMP.set(chrono::Utc::now().timestamp() as f64);
+            }
+            timer.observe_duration();
+
+            if transaction_count.is_multiple_of(10) {
+                info!(
+                    transactions_processed = transaction_count,
+                    backlog = metrics::STREAM_PROCESSING_BACKLOG.get(),
+                    "Processing transactions"
+                );
+            }
+            true
+        }
+        Err(e) => {
+            metrics::TRANSACTIONS_FAILED.inc();
+            timer.observe_duration();
+            warn!(
+                signature = %signature,
+                error = %e,
+                retryable = e.is_retryable(),
+                "Failed to process transaction"
+            );
+            false
+        }
+    }
+}
+
+/// Replay updates recorded with `RECORD_UPDATES_FILE` through the normal handling path.
+/// 
+/// No gRPC connection is made; transactions are still fetched over RPC and written
+/// to the database, which makes processing bugs reproducible from a recording.
+pub async fn replay_from_file<S: TransactionStore>(
+    path: &Path,
+    http_url: &str,
+    repository: Arc<S>,
+    stream_commitment: StreamCommitment,
+    options: ProcessingOptions,
+    concurrency: Arc<AdaptiveConcurrency>,
+) -> Result<(), AppError> {
+    let updates = read_recorded_updates(path).await?;
+
+    info!(
+        path = %path.display(),
+        updates = updates.len(),
+        "Replaying recorded gRPC updates"
+    );
+
+    // Recorded transactions are long since fetchable, so skip the fetch delay
+    let context = Arc::new(FetchContext {
+        http_client: SolanaRpcClient::new(http_url.to_string()),
+        repository,
+        stream_commitment,
+        options: ProcessingOptions {
+            fetch_delay: None,
+            ..options
+        },
+        concurrency,
+        transaction_count: AtomicU64::new(0),
+        latest_slot: AtomicU64::new(0),
+    });
+
+    let mut fetches = JoinSet::new();
+    for update in updates {
+        process_update(update, &context, &mut fetches).await?;
+    }
+
+    // Wait for the remaining fetches before reporting
+    stop_processing(&context, &mut fetches).await;
+
+    info!(
+        transactions_processed = context.transaction_count.load(Ordering::Relaxed),
+        "Replay complete"
+    );
 
     Ok(())
 }
 
+/// Finish in-flight work before processing stops.
+/// 
+/// Fetches are drained first, since they can still add to the finality gate, and
+/// then any transactions the gate holds are settled.
+async fn stop_processing<S: TransactionStore>(context: &FetchContext<S>, fetches: &mut JoinSet<()>) {
+    drain_fetches(fetches, context.options.drain_timeout).await;
+    settle_held(context).await;
+}
+
+/// Settle the transactions still held by the finality gate when processing stops.
+/// 
+/// Their slots may never be reported finalized now, so each is re-checked like a
+/// transaction from a passed-over slot (see `settle_unfinalized`). The re-check is
+/// bounded by `drain_timeout`; transactions left when it runs out, counting the
+/// batch that was interrupted, are added to `solana_tracker_dropped_on_shutdown_total`.
+async fn settle_held<S: TransactionStore>(context: &FetchContext<S>) {
+    let Some(gate) = &context.options.finality_gate else {
+        return;
+    };
+
+    let held = gate.take_all();
+    metrics::PENDING_FINALIZATION.set(0);
+    if held.is_empty() {
+        return;
+    }
+
+    warn!(
+        held = held.len(),
+        "Processing stopped with transactions awaiting finalization, re-checking them"
+    );
+
+    let deadline = context.options.drain_timeout.map(|limit| Instant::now() + limit);
+    let mut remaining = held.len();
+    for chunk in held.chunks(MAX_SIGNATURE_STATUSES) {
+        let settle = settle_unfinalized(context, chunk.to_vec());
+        match deadline {
+            Some(deadline) => {
+                if timeout_at(deadline, settle).await.is_err() {
+                    break;
+                }
+            }
+            None => settle.await,
+        }
+        remaining -= chunk.len();
+    }
+
+    if remaining > 0 {
+        metrics::DROPPED_ON_SHUTDOWN.inc_by(remaining as u64);
+        warn!(
+            dropped = remaining,
+            "Drain timed out, dropping transactions awaiting finalization"
+        );
+    }
+}
+
+/// Wait for in-flight fetches to finish, aborting any still running after `limit`.
+/// 
+/// This bounds how long stopping can take. Aborted signatures are not persisted or
+/// recorded as missed, so their count is logged and added to
+/// `solana_tracker_dropped_on_shutdown_total` for a later reconcile or backfill.
+async fn drain_fetches(fetches: &mut JoinSet<()>, limit: Option<Duration>) {
+    if fetches.is_empty() {
+        return;
+    }
+
+    info!(in_flight = fetches.len(), "Draining in-flight transaction fetches");
+
+    let drain = async { while fetches.join_next().await.is_some() {} };
+    let Some(limit) = limit else {
+        drain.await;
+        return;
+    };
+
+    if timeout(limit, drain).await.is_err() {
+        let dropped = fetches.len();
+        fetches.abort_all();
+        metrics::DROPPED_ON_SHUTDOWN.inc_by(dropped as u64);
+        warn!(
+            dropped = dropped,
+            timeout_seconds = limit.as_secs(),
+            "Drain timed out, dropping in-flight transaction fetches"
+        );
+    }
+}
+
 /// Fetch transaction details and process into database.
-async fn fetch_and_process_transaction(
+/// 
+/// The transaction is fetched at the configured fetch commitment, or at the same
+/// commitment as the stream that delivered it when none is configured. Transactions from a Finalized stream are upserted so they correct any row written
+/// earlier by a Confirmed stream; all others are inserted idempotently.
+/// With a finality gate configured, transactions from other streams are held and
+/// reported as `Buffered` until a Finalized slot update releases them.
+/// 
+/// With `confirmed_fallback_after` set, a Finalized fetch that finds nothing is
+/// retried every `NOT_FOUND_RETRY_DELAY`, and after that many misses fetched once at
+/// Confirmed; such transactions are stored with `confirmed_only` set.
+pub(crate) async fn fetch_and_process_transaction<S: TransactionStore>(
     client: &SolanaRpcClient,
     signature: &str,
-    repository: &TransactionRepository,
-) -> Result<(), AppError> {
+    repository: &S,
+    stream_commitment: StreamCommitment,
+    options: &ProcessingOptions,
+    concurrency: &AdaptiveConcurrency,
+) -> Result<ProcessOutcome, AppError> {
     use solana_client::rpc_config::RpcTransactionConfig;
 
     // Fetch transaction with full details
-    let config = RpcTransactionConfig {
+    let fetch_commitment = options
+        .fetch_commitment
+        .map(rpc_commitment)
+        .unwrap_or_else(|| fetch_commitment_for(stream_commitment));
+    let mut config = RpcTransactionConfig {
         encoding: Some(UiTransactionEncoding::Json),
-        commitment: Some(CommitmentConfig {
-            commitment: CommitmentLevel::Confirmed,
-        }),
+        commitment: Some(CommitmentConfig { commitment: fetch_commitment }),
         max_supported_transaction_version: Some(0),
     };
 
     let sig = signature.parse()
         .map_err(|e| AppError::ParseError(format!("Invalid signature: {}", e)))?;
 
-    let transaction = client
-        .get_transaction_with_config(&sig, config)
-        .await
-        .map_err(|e| AppError::SolanaClient(format!("Failed to fetch transaction: {}", e)))?;
+    // Decided before fetching, so sampling also cuts RPC volume
+    if let Some(rate) = options.sample_rate {
+        if !is_sampled(&sig, rate) {
+            metrics::SAMPLED_OUT.inc();
+            debug!(signature = %signature, sample_rate = rate, "Skipping transaction outside the sample");
+            return Ok(ProcessOutcome::SampledOut);
+        }
+    }
+
+    // A transaction too recent to be finalized is retried, then tried once at Confirmed
+    let fallback_after = options
+        .confirmed_fallback_after
+        .filter(|_| fetch_commitment == CommitmentLevel::Finalized);
+    let mut finalized_misses = 0;
+    let mut confirmed_only = false;
+
+    let transaction = loop {
+        match client.get_transaction_with_config(&sig, config).await {
+            Ok(transaction) => {
+                concurrency.on_success();
+                break transaction;
+            }
+            Err(e) if is_beyond_retention(&e) => {
+                concurrency.on_success();
+
+                // The node no longer has this transaction; record it for a later archival backfill
+                warn!(
+                    signature = %signature,
+                    error = %e,
+                    "Transaction is beyond RPC history retention, recording as missed"
+                );
+                repository
+                    .record_missed_transaction(signature, "beyond_retention", &e.to_string())
+                    .await?;
+                metrics::MISSED_TRANSACTIONS.inc();
+                return Ok(ProcessOutcome::Missed);
+            }
+            Err(e) if !confirmed_only && fallback_after.is_some() && is_not_found(&e) => {
+                concurrency.on_success();
+                finalized_misses += 1;
+
+                if finalized_misses < fallback_after.unwrap_or_default() {
+                    sleep(NOT_FOUND_RETRY_DELAY).await;
+                    continue;
+                }
+
+                debug!(
+                    signature = %signature,
+                    finalized_misses = finalized_misses,
+                    "Transaction not found at finalized, fetching at confirmed"
+                );
+                metrics::CONFIRMED_FALLBACK_FETCHES.inc();
+                config.commitment = Some(CommitmentConfig::confirmed());
+                confirmed_only = true;
+            }
+            Err(e) => {
+                if is_rate_limited(&e) {
+                    concurrency.on_rate_limited();
+                }
+                return Err(AppError::solana_client("Failed to fetch transaction", e));
+            }
+        }
+    };
 
     // Parse the transaction
-    let parsed_tx = parse_transaction(&transaction)?;
+    let mut parsed_tx = parse_transaction(&transaction, &options.parse)?;
+    parsed_tx.confirmed_only = confirmed_only;
+
+    // Never associate data with the wrong signature if RPC or parsing got it mixed up
+    if is_signature_mismatch(signature, &parsed_tx) {
+        error!(
+            requested_signature = %signature,
+            parsed_signature = %parsed_tx.signature,
+            "Fetched transaction signature does not match the requested one, skipping"
+        );
+        metrics::SIGNATURE_MISMATCHES.inc();
+        return Ok(ProcessOutcome::SignatureMismatch);
+    }
+
+    // Drop transactions that merely reference a target account, e.g. as a read-only input.
+    // Checked before balance changes are truncated so a target change is never missed.
+    // A target program is involved when invoked, since programs neither sign nor hold balances
+    if let Some(targets) = &options.strict_target_accounts {
+        let involved = parsed_tx.signers.iter().any(|signer| targets.contains(signer))
+            || parsed_tx
+                .balance_changes
+                .iter()
+                .any(|change| targets.contains(&change.account_address))
+            || parsed_tx.program_ids.iter().any(|id| targets.contains(id));
+        if !involved {
+            debug!(
+                signature = %signature,
+                "Skipping transaction no target account signed, changed balance in, or was invoked in"
+            );
+            metrics::FILTERED_NON_TARGET.inc();
+            return Ok(ProcessOutcome::Filtered);
+        }
+    }
+
+    // Skip low-fee noise; the cheapest transactions are rarely MEV-relevant
+    if parsed_tx.fee < options.min_fee_lamports {
+        debug!(
+            signature = %signature,
+            fee = parsed_tx.fee,
+            min_fee_lamports = options.min_fee_lamports,
+            "Skipping transaction below minimum fee"
+        );
+        metrics::FILTERED_BY_FEE.inc();
+        return Ok(ProcessOutcome::Filtered);
+    }
+
+    // Keep only transactions touching the allow-listed programs (e.g. specific DEXes)
+    if let Some(allowlist) = &options.program_allowlist {
+        if !parsed_tx.program_ids.iter().any(|id| allowlist.contains(id)) {
+            debug!(
+                signature = %signature,
+                "Skipping transaction that invokes no allow-listed program"
+            );
+            metrics::FILTERED_BY_PROGRAM.inc();
+            return Ok(ProcessOutcome::Filtered);
+        }
+    }
+
+    // Drop no-ops and transactions that failed before moving any balance
+    if options.skip_zero_balance_changes && parsed_tx.balance_changes.is_empty() {
+        debug!(
+            signature = %signature,
+            "Skipping transaction without balance changes"
+        );
+        metrics::FILTERED_ZERO_BALANCE_CHANGES.inc();
+        return Ok(ProcessOutcome::Filtered);
+    }
+
+    // Observed before truncation so the distribution shows the real fanout
+    metrics::BALANCE_CHANGES_PER_TX.observe(parsed_tx.balance_changes.len() as f64);
+
+    // Cap pathological transactions that touch a very large number of accounts
+    if let Some(max) = options.max_balance_changes {
+        let total = parsed_tx.balance_changes.len();
+        if total > max {
+            warn!(
+                signature = %signature,
+                balance_changes = total,
+                max_balance_changes = max,
+                "Truncating balance changes exceeding configured cap"
+            );
+            metrics::TRUNCATED_BALANCE_CHANGES.inc_by((total - max) as u64);
+            parsed_tx.balance_changes.truncate(max);
+        }
+    }
+
+    // Serialized now, while the RPC response is at hand, even if the write is held
+    let raw_json = if options.store_raw_json {
+        match serde_json::to_string(&transaction) {
+            Ok(raw_json) => Some(raw_json),
+            Err(e) => {
+                warn!(signature = %signature, error = %e, "Failed to serialize raw transaction JSON");
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    // Hold transactions that could still be rolled back until their slot finalizes
+    if stream_commitment != StreamCommitment::Finalized {
+        if let Some(gate) = &options.finality_gate {
+            let held = gate.hold(PendingTransaction { tx: parsed_tx, raw_json });
+            metrics::PENDING_FINALIZATION.set(gate.held() as i64);
+
+            // Already finalized by the time it was fetched, so there is nothing to wait for
+            let Some(PendingTransaction { tx, raw_json }) = held else {
+                return Ok(ProcessOutcome::Buffered);
+            };
+            let written = write_transaction(repository, &tx, raw_json.as_deref(), false, options).await?;
+            return Ok(written_outcome(written));
+        }
+    }
+
+    let written = write_transaction(
+        repository,
+        &parsed_tx,
+        raw_json.as_deref(),
+        stream_commitment == StreamCommitment::Finalized,
+        options,
+    ).await?;
+
+    Ok(written_outcome(written))
+}
+
+/// Check whether a fetched transaction is not the one that was requested.
+fn is_signature_mismatch(requested_signature: &str, parsed_tx: &ParsedTransaction) -> bool {
+    parsed_tx.signature != requested_signature
+}
+
+/// Outcome of a transaction `write_transaction` did or did not write.
+fn written_outcome(written: bool) -> ProcessOutcome {
+    if written {
+        ProcessOutcome::Persisted
+    } else {
+        ProcessOutcome::Duplicate
+    }
+}
+
+/// Write a parsed transaction to the database and record its metrics.
+/// 
+/// `upsert` overwrites an existing row instead of leaving it, for transactions from a
+/// Finalized stream. `raw_json` is stored alongside when STORE_RAW_JSON is set.
+/// 
+/// Returns false when the signature was already stored, whether an insert left the
+/// row alone or an upsert corrected it in place. It was counted when first written,
+/// so no transaction metrics are recorded again.
+async fn write_transaction<S: TransactionStore>(
+    repository: &S,
+    parsed_tx: &ParsedTransaction,
+    raw_json: Option<&str>,
+    upsert: bool,
+    options: &ProcessingOptions,
+) -> Result<bool, AppError> {
+    let signature = parsed_tx.signature.as_str();
 
     // Store in database with timing
     let timer = metrics::DATABASE_OPERATION_TIME.start_timer();
-    repository.insert_complete_transaction(&parsed_tx).await?;
-    timer.observe_duration();
+    let balance_changes_written = if upsert {
+        if !repository.upsert_complete_transaction(parsed_tx).await? {
+            timer.observe_duration();
+            debug!(signature = %signature, "Transaction already stored, corrected in place");
+            return Ok(false);
+        }
+        parsed_tx.balance_changes.len()
+    } else {
+        match repository.insert_complete_transaction_detailed(parsed_tx).await? {
+            Some((_, balance_changes_written)) => balance_changes_written,
+            None => {
+                timer.observe_duration();
+                debug!(signature = %signature, "Transaction already stored, skipping");
+                return Ok(false);
+            }
+        }
+    };
+    let elapsed = Duration::from_secs_f64(timer.stop_and_record());
+
+    // The histogram hides which writes were slow; name them when they cross the threshold
+    if let Some(threshold) = options.slow_db_threshold {
+        if elapsed > threshold {
+            warn!(
+                signature = %signature,
+                duration_ms = elapsed.as_millis() as u64,
+                threshold_ms = threshold.as_millis() as u64,
+                balance_changes = parsed_tx.balance_changes.len(),
+                "Slow database write"
+            );
+        }
+    }
+
+    // Kept for audit and reprocessing; the parsed row is already stored, so a failure only warns
+    if let Some(raw_json) = raw_json {
+        if let Err(e) = repository.insert_raw_transaction(signature, raw_json).await {
+            warn!(signature = %signature, error = %e, "Failed to store raw transaction JSON");
+        }
+    }
 
     // Track balance changes
-    metrics::BALANCE_CHANGES_RECORDED.inc_by(parsed_tx.balance_changes.len() as u64);
+    metrics::BALANCE_CHANGES_RECORDED.inc_by(balance_changes_written as u64);
+    metrics::record_accounts_seen(
+        parsed_tx
+            .balance_changes
+            .iter()
+            .map(|change| change.account_address.as_str()),
+    );
 
-    Ok(())
+    // Track fee distribution
+    metrics::TRANSACTION_FEE_LAMPORTS.observe(parsed_tx.fee as f64);
+
+    Ok(true)
+}
+
+/// Check whether an RPC error means the node no longer retains the transaction.
+/// 
+/// Public RPC nodes only keep recent history. Requests for older transactions fail
+/// with one of these server errors rather than a transient failure, so retrying
+/// against the same node will never succeed.
+fn is_beyond_retention(error: &ClientError) -> bool {
+    use solana_client::rpc_custom_error::{
+        JSON_RPC_SERVER_ERROR_BLOCK_CLEANED_UP,
+        JSON_RPC_SERVER_ERROR_LONG_TERM_STORAGE_SLOT_SKIPPED,
+        JSON_RPC_SERVER_ERROR_TRANSACTION_HISTORY_NOT_AVAILABLE,
+    };
+
+    matches!(
+        error.kind(),
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. })
+            if *code == JSON_RPC_SERVER_ERROR_TRANSACTION_HISTORY_NOT_AVAILABLE
+                || *code == JSON_RPC_SERVER_ERROR_BLOCK_CLEANED_UP
+                || *code == JSON_RPC_SERVER_ERROR_LONG_TERM_STORAGE_SLOT_SKIPPED
+    )
+}
+
+/// Check whether an RPC error means the node has no such transaction (yet).
+/// 
+/// `getTransaction` answers null for a signature it doesn't have at the requested
+/// commitment, which the client reports as a failure to deserialize the response.
+fn is_not_found(error: &ClientError) -> bool {
+    matches!(error.kind(), ClientErrorKind::SerdeJson(_))
+}
+
+/// Decide whether a signature falls within the sample.
+/// 
+/// Ed25519 signatures are uniformly distributed, so their first 8 bytes already act
+/// as a hash: the same transaction is kept or dropped on every run and after restarts.
+fn is_sampled(signature: &Signature, rate: f64) -> bool {
+    let mut prefix = [0u8; 8];
+    prefix.copy_from_slice(&signature.as_ref()[..8]);
+    (u64::from_le_bytes(prefix) as f64 / u64::MAX as f64) < rate
+}
+
+/// Map a stream commitment level to the commitment used for RPC fetches.
+/// 
+/// `getTransaction` does not accept Processed, so Processed streams fetch at Confirmed.
+fn fetch_commitment_for(stream_commitment: StreamCommitment) -> CommitmentLevel {
+    match stream_commitment {
+        StreamCommitment::Finalized => CommitmentLevel::Finalized,
+        StreamCommitment::Confirmed | StreamCommitment::Processed => CommitmentLevel::Confirmed,
+    }
+}
+
+/// Convert a configured commitment to the RPC commitment type.
+/// 
+/// Processed is rejected for fetches at config load, so it only maps to Confirmed defensively.
+pub(crate) fn rpc_commitment(commitment: Commitment) -> CommitmentLevel {
+    match commitment {
+        Commitment::Finalized => CommitmentLevel::Finalized,
+        Commitment::Confirmed | Commitment::Processed => CommitmentLevel::Confirmed,
+    }
 }
 
 /// Calculate exponential backoff delay for reconnection attempts.
@@ -236,3 +1291,473 @@ fn calculate_backoff_delay(attempt: u32, max_delay: Duration) -> Duration {
     let exponential_delay = base_delay * 2u32.saturating_pow(attempt.min(10));
     exponential_delay.min(max_delay)
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::database::sqlite::SqliteRepository;
+    use crate::grpc::source::Subscription;
+    use crate::solana::parser::tests::{fixture_json, load_fixture, parse_fixture, PAYER};
+    use chrono::DateTime;
+    use futures::channel::mpsc;
+    use futures::stream::{self, StreamExt};
+    use solana_client::rpc_request::RpcRequest;
+    use std::collections::VecDeque;
+    use yellowstone_grpc_proto::prelude::{
+        Message, SubscribeUpdatePing, SubscribeUpdatePong, SubscribeUpdateSlot,
+        SubscribeUpdateTransaction, SubscribeUpdateTransactionInfo, Transaction,
+        TransactionStatusMeta,
+    };
+
+    const SOL_TRANSFER_SIGNATURE: &str =
+        "5dvLf2MYJmxLUkqR13XQU2DtPG9Rhj2WMce2FuM7ZbGxUfCjAaxqiM1m8ceikKCiZZaaF58C33mYjJ7SrxexhVAx";
+
+    /// Signature of the transaction the mock RPC client returns when nothing else is mocked.
+    const MOCK_TRANSACTION_SIGNATURE: &str =
+        "3AsdoALgZFuq2oUVWrDYhg2pNeaLJKPLf8hU2mQ6U8qJxeJ6hsrPVpMn9ma39DtfYCrDQSvngWRP8NnTpEhezJpE";
+
+    /// A fetch context whose RPC client answers one getTransaction with the named fixture.
+    fn fixture_context<S: TransactionStore>(
+        repository: Arc<S>,
+        fixture: &str,
+        options: ProcessingOptions,
+    ) -> FetchContext<S> {
+        let mocks = HashMap::from([(RpcRequest::GetTransaction, fixture_json(fixture))]);
+        FetchContext {
+            http_client: SolanaRpcClient::new_mock_with_mocks("succeeds".to_string(), mocks),
+            repository,
+            stream_commitment: StreamCommitment::Confirmed,
+            options,
+            concurrency: Arc::new(AdaptiveConcurrency::new(1, 1)),
+            transaction_count: AtomicU64::new(0),
+            latest_slot: AtomicU64::new(0),
+        }
+    }
+
+    fn memory_repository() -> Arc<SqliteRepository> {
+        Arc::new(SqliteRepository::open(":memory:").expect("open in-memory database"))
+    }
+
+    pub(crate) fn update(update_oneof: UpdateOneof) -> SubscribeUpdate {
+        SubscribeUpdate {
+            filters: vec!["transactions".to_string()],
+            update_oneof: Some(update_oneof),
+        }
+    }
+
+    fn transaction_update(signature: Vec<u8>) -> SubscribeUpdate {
+        update(UpdateOneof::Transaction(SubscribeUpdateTransaction {
+            transaction: Some(SubscribeUpdateTransactionInfo {
+                signature,
+                transaction: Some(Transaction {
+                    message: Some(Message {
+                        account_keys: vec![vec![1; 32], vec![2; 32]],
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }),
+                meta: Some(TransactionStatusMeta {
+                    loaded_writable_addresses: vec![vec![3; 32]],
+                    loaded_readonly_addresses: vec![vec![4; 32]],
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            slot: 42,
+        }))
+    }
+
+    #[test]
+    fn transaction_update_fetches_with_all_account_keys() {
+        let signature = vec![7; 64];
+
+        let action = handle_update(transaction_update(signature.clone()));
+
+        assert_eq!(
+            action,
+            UpdateAction::FetchTransaction {
+                signature: bs58::encode(&signature).into_string(),
+                account_keys: vec![vec![1; 32], vec![2; 32], vec![3; 32], vec![4; 32]],
+                slot: 42,
+            }
+        );
+    }
+
+    #[test]
+    fn transaction_update_without_signature_is_incomplete() {
+        assert_eq!(
+            handle_update(transaction_update(Vec::new())),
+            UpdateAction::Incomplete("missing_signature")
+        );
+    }
+
+    #[test]
+    fn transaction_update_without_transaction_is_incomplete() {
+        let update = update(UpdateOneof::Transaction(SubscribeUpdateTransaction {
+            transaction: None,
+            slot: 42,
+        }));
+
+        assert_eq!(handle_update(update), UpdateAction::Incomplete("missing_transaction"));
+    }
+
+    #[test]
+    fn slot_update_reports_slot_and_status() {
+        let update = update(UpdateOneof::Slot(SubscribeUpdateSlot {
+            slot: 100,
+            parent: Some(99),
+            status: StreamCommitment::Finalized as i32,
+        }));
+
+        assert_eq!(
+            handle_update(update),
+            UpdateAction::Slot { slot: 100, status: StreamCommitment::Finalized as i32 }
+        );
+    }
+
+    #[test]
+    fn ping_and_pong_are_skipped() {
+        assert_eq!(handle_update(update(UpdateOneof::Ping(SubscribeUpdatePing {}))), UpdateAction::Skip);
+        assert_eq!(
+            handle_update(update(UpdateOneof::Pong(SubscribeUpdatePong { id: 1 }))),
+            UpdateAction::Skip
+        );
+    }
+
+    #[test]
+    fn backoff_starts_at_one_second() {
+        assert_eq!(calculate_backoff_delay(0, Duration::from_secs(300)), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn backoff_doubles_each_attempt() {
+        let max_delay = Duration::from_secs(3600);
+        for attempt in 0..10 {
+            assert_eq!(
+                calculate_backoff_delay(attempt + 1, max_delay),
+                calculate_backoff_delay(attempt, max_delay) * 2
+            );
+        }
+    }
+
+    #[test]
+    fn backoff_never_exceeds_max_delay() {
+        let max_delay = Duration::from_secs(300);
+        for attempt in 0..64 {
+            assert!(calculate_backoff_delay(attempt, max_delay) <= max_delay);
+        }
+        assert_eq!(calculate_backoff_delay(9, max_delay), max_delay);
+    }
+
+    #[test]
+    fn backoff_does_not_overflow_on_huge_attempts() {
+        assert_eq!(calculate_backoff_delay(u32::MAX, Duration::from_secs(300)), Duration::from_secs(300));
+        assert_eq!(calculate_backoff_delay(u32::MAX, Duration::MAX), Duration::from_secs(1024));
+    }
+
+    #[tokio::test]
+    async fn duplicate_insert_is_not_counted() {
+        let _metrics = metrics::reset_metrics().await;
+        let repository = memory_repository();
+        let fees_observed = metrics::TRANSACTION_FEE_LAMPORTS.get_sample_count();
+
+        // Inserted at Confirmed, skipped as a duplicate, then corrected by a Finalized stream's upsert
+        for stream_commitment in [StreamCommitment::Confirmed, StreamCommitment::Confirmed, StreamCommitment::Finalized] {
+            let mut context = fixture_context(repository.clone(), "sol_transfer", ProcessingOptions::default());
+            context.stream_commitment = stream_commitment;
+            process_transaction(&context, SOL_TRANSFER_SIGNATURE, 250_000_000, &[]).await;
+        }
+
+        assert_eq!(metrics::TRANSACTION_FEE_LAMPORTS.get_sample_count() - fees_observed, 1);
+        assert_eq!(metrics::TRANSACTIONS_PROCESSED.get(), 1);
+        assert_eq!(metrics::BALANCE_CHANGES_RECORDED.get(), 2);
+        assert_eq!(metrics::TRANSACTIONS_FAILED.get(), 0);
+        assert_eq!(repository.count_by_success(DateTime::UNIX_EPOCH).await.unwrap(), (1, 0));
+        let payer_history = repository.balance_history(PAYER, None, DateTime::UNIX_EPOCH).await.unwrap();
+        assert_eq!(payer_history.len(), 1);
+    }
+
+    #[test]
+    fn signature_mismatch_compares_parsed_signature() {
+        let parsed_tx = parse_transaction(&load_fixture("sol_transfer"), &ParseOptions::default()).unwrap();
+
+        assert!(!is_signature_mismatch(SOL_TRANSFER_SIGNATURE, &parsed_tx));
+        assert!(is_signature_mismatch("1111111111111111111111111111111111111111111111111111111111111111", &parsed_tx));
+    }
+
+    #[tokio::test]
+    async fn mismatched_transaction_is_not_persisted() {
+        let _metrics = metrics::reset_metrics().await;
+        let repository = memory_repository();
+        // RPC answers with a different transaction than the one requested
+        let context = fixture_context(repository.clone(), "token_transfer", ProcessingOptions::default());
+
+        let outcome = fetch_and_process_transaction(
+            &context.http_client,
+            SOL_TRANSFER_SIGNATURE,
+            repository.as_ref(),
+            StreamCommitment::Confirmed,
+            &context.options,
+            &context.concurrency,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(outcome, ProcessOutcome::SignatureMismatch);
+        assert_eq!(metrics::SIGNATURE_MISMATCHES.get(), 1);
+        assert_eq!(metrics::BALANCE_CHANGES_RECORDED.get(), 0);
+        assert_eq!(repository.count_by_success(DateTime::UNIX_EPOCH).await.unwrap(), (0, 0));
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_confirmed_when_not_finalized() {
+        let _metrics = metrics::reset_metrics().await;
+        let repository = memory_repository();
+        // Null (not found) at finalized once, then the mock client's built-in transaction
+        let mocks = HashMap::from([(RpcRequest::GetTransaction, serde_json::Value::Null)]);
+        let client = SolanaRpcClient::new_mock_with_mocks("succeeds".to_string(), mocks);
+        let options = ProcessingOptions {
+            confirmed_fallback_after: Some(1),
+            ..Default::default()
+        };
+
+        let outcome = fetch_and_process_transaction(
+            &client,
+            MOCK_TRANSACTION_SIGNATURE,
+            repository.as_ref(),
+            StreamCommitment::Finalized,
+            &options,
+            &AdaptiveConcurrency::new(1, 1),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(outcome, ProcessOutcome::Persisted);
+        assert_eq!(metrics::CONFIRMED_FALLBACK_FETCHES.get(), 1);
+        assert!(repository.query_value::<bool>("SELECT confirmed_only FROM transactions"));
+    }
+
+    #[tokio::test]
+    async fn unsettled_transactions_are_written_only_when_finalized_in_their_slot() {
+        let _metrics = metrics::reset_metrics().await;
+        let repository = memory_repository();
+        let finalized = parse_fixture("sol_transfer");
+        let abandoned = parse_fixture("token_transfer");
+        let statuses = serde_json::json!({
+            "context": { "slot": finalized.slot },
+            "value": [
+                {
+                    "slot": finalized.slot,
+                    "confirmations": null,
+                    "err": null,
+                    "status": { "Ok": null },
+                    "confirmationStatus": "finalized"
+                },
+                null
+            ]
+        });
+        let mocks = HashMap::from([(RpcRequest::GetSignatureStatuses, statuses)]);
+        let mut context = fixture_context(repository.clone(), "sol_transfer", ProcessingOptions::default());
+        context.http_client = SolanaRpcClient::new_mock_with_mocks("succeeds".to_string(), mocks);
+
+        let gate = FinalityGate::new();
+        for tx in [finalized.clone(), abandoned.clone()] {
+            assert!(gate.hold(PendingTransaction { tx, raw_json: None }).is_none());
+        }
+        let mut fetches = JoinSet::new();
+        release_finalized(&gate, finalized.slot + 1, &Arc::new(context), &mut fetches);
+        while fetches.join_next().await.is_some() {}
+
+        assert_eq!(gate.held(), 0);
+        assert_eq!(metrics::UNFINALIZED_DROPPED.get(), 1);
+        assert_eq!(
+            repository.query_value::<String>("SELECT signature FROM transactions"),
+            finalized.signature
+        );
+        assert_eq!(
+            repository.query_value::<String>("SELECT signature FROM missed_transactions WHERE reason = 'unfinalized'"),
+            abandoned.signature
+        );
+    }
+
+    /// Outcome of one subscribe call: the updates the connection delivers, or its error.
+    pub(crate) type ScriptedAttempt = Result<Vec<SubscribeUpdate>, AppError>;
+
+    /// A stream source that plays back a scripted outcome for each subscribe call.
+    /// 
+    /// Clones share the script, so a test can keep one to see how much was played.
+    #[derive(Clone)]
+    pub(crate) struct ScriptedSource {
+        attempts: Arc<std::sync::Mutex<VecDeque<ScriptedAttempt>>>,
+    }
+
+    impl ScriptedSource {
+        pub(crate) fn new(attempts: Vec<ScriptedAttempt>) -> Self {
+            Self {
+                attempts: Arc::new(std::sync::Mutex::new(attempts.into())),
+            }
+        }
+
+        /// Number of scripted subscribe outcomes not yet played.
+        pub(crate) fn remaining(&self) -> usize {
+            self.attempts.lock().unwrap().len()
+        }
+    }
+
+    impl StreamSource for ScriptedSource {
+        fn endpoint(&self) -> &str {
+            "http://scripted:10000"
+        }
+
+        fn commitment(&self) -> StreamCommitment {
+            StreamCommitment::Confirmed
+        }
+
+        async fn subscribe(&self) -> Result<Subscription<'_>, AppError> {
+            let updates = self
+                .attempts
+                .lock()
+                .unwrap()
+                .pop_front()
+                .expect("subscribe called more often than scripted")?;
+            let (requests, _) = mpsc::unbounded();
+            Ok(Subscription::new(
+                stream::iter(updates.into_iter().map(Ok)).boxed(),
+                requests,
+            ))
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn reconnects_after_stream_error_with_backoff() {
+        let _metrics = metrics::reset_metrics().await;
+        let source = ScriptedSource::new(vec![
+            Err(AppError::grpc_stream("Stream error", tonic::Status::unavailable("connection reset"))),
+            Ok(vec![update(UpdateOneof::Slot(SubscribeUpdateSlot {
+                slot: 100,
+                parent: Some(99),
+                status: StreamCommitment::Confirmed as i32,
+            }))]),
+            Err(AppError::fatal_subscription("Stream rejected", tonic::Status::unauthenticated("bad token"))),
+        ]);
+        let started = Instant::now();
+
+        let result = process_account_stream(
+            source,
+            "http://127.0.0.1:8899",
+            memory_repository(),
+            ProcessingOptions::default(),
+            Arc::new(AdaptiveConcurrency::new(1, 1)),
+        )
+        .await;
+
+        assert!(matches!(result, Err(AppError::FatalSubscription { .. })));
+        // Once after the error, once after the stream ended
+        assert_eq!(metrics::STREAM_RECONNECTIONS.get(), 2);
+        // Only the failed attempt backs off; a stream that ends reconnects right away
+        let backoff = calculate_backoff_delay(1, Duration::from_secs(300));
+        assert!(started.elapsed() >= backoff);
+        assert!(started.elapsed() < backoff + Duration::from_secs(1));
+    }
+
+    /// A signature whose first 8 bytes are `prefix` in little-endian order.
+    fn signature_with_prefix(prefix: u64) -> Signature {
+        let mut bytes = [7u8; 64];
+        bytes[..8].copy_from_slice(&prefix.to_le_bytes());
+        Signature::from(bytes)
+    }
+
+    #[test]
+    fn sampling_is_decided_by_signature_prefix() {
+        assert!(is_sampled(&signature_with_prefix(0), 0.01));
+        assert!(!is_sampled(&signature_with_prefix(0), 0.0));
+        assert!(!is_sampled(&signature_with_prefix(u64::MAX), 0.99));
+        assert!(is_sampled(&signature_with_prefix(u64::MAX / 4), 0.5));
+        assert!(!is_sampled(&signature_with_prefix(u64::MAX / 4 * 3), 0.5));
+    }
+
+    #[test]
+    fn sampling_keeps_the_rate() {
+        // Evenly spread prefixes stand in for uniformly distributed signatures
+        let step = u64::MAX / 10_000;
+        let kept = (0..10_000)
+            .filter(|i| is_sampled(&signature_with_prefix(i * step), 0.25))
+            .count();
+        // Give or take the prefix that lands on the boundary
+        assert!((2_499..=2_501).contains(&kept), "kept {}", kept);
+    }
+
+    #[tokio::test]
+    async fn failed_fetch_leaves_signature_to_other_endpoints() {
+        let _metrics = metrics::reset_metrics().await;
+        let repository = memory_repository();
+        let options = ProcessingOptions {
+            dedup: Some(Arc::new(SignatureDedup::new(10))),
+            ..Default::default()
+        };
+        let signature = bs58::decode(SOL_TRANSFER_SIGNATURE).into_vec().unwrap();
+
+        let mut failing = fixture_context(repository.clone(), "sol_transfer", options.clone());
+        failing.http_client = SolanaRpcClient::new_mock("fails".to_string());
+        let healthy = fixture_context(repository.clone(), "sol_transfer", options);
+
+        for context in [failing, healthy] {
+            let mut fetches = JoinSet::new();
+            process_update(transaction_update(signature.clone()), &Arc::new(context), &mut fetches)
+                .await
+                .unwrap();
+            while fetches.join_next().await.is_some() {}
+        }
+
+        assert_eq!(metrics::TRANSACTIONS_FAILED.get(), 1);
+        assert_eq!(metrics::DUPLICATE_UPDATES.get(), 0);
+        assert_eq!(repository.count_by_success(DateTime::UNIX_EPOCH).await.unwrap(), (1, 0));
+    }
+
+    #[tokio::test]
+    async fn held_transactions_are_settled_when_processing_stops() {
+        let _metrics = metrics::reset_metrics().await;
+        let repository = memory_repository();
+        let finalized = parse_fixture("sol_transfer");
+        let pending = parse_fixture("token_transfer");
+        let statuses = serde_json::json!({
+            "context": { "slot": finalized.slot },
+            "value": [
+                {
+                    "slot": finalized.slot,
+                    "confirmations": null,
+                    "err": null,
+                    "status": { "Ok": null },
+                    "confirmationStatus": "finalized"
+                },
+                null
+            ]
+        });
+        let gate = Arc::new(FinalityGate::new());
+        let options = ProcessingOptions {
+            finality_gate: Some(gate.clone()),
+            ..Default::default()
+        };
+        let mut context = fixture_context(repository.clone(), "sol_transfer", options);
+        context.http_client = SolanaRpcClient::new_mock_with_mocks(
+            "succeeds".to_string(),
+            HashMap::from([(RpcRequest::GetSignatureStatuses, statuses)]),
+        );
+        for tx in [finalized.clone(), pending.clone()] {
+            assert!(gate.hold(PendingTransaction { tx, raw_json: None }).is_none());
+        }
+
+        stop_processing(&context, &mut JoinSet::new()).await;
+
+        assert_eq!(gate.held(), 0);
+        assert_eq!(metrics::DROPPED_ON_SHUTDOWN.get(), 0);
+        assert_eq!(
+            repository.query_value::<String>("SELECT signature FROM transactions"),
+            finalized.signature
+        );
+        assert_eq!(
+            repository.query_value::<String>("SELECT signature FROM missed_transactions WHERE reason = 'unfinalized'"),
+            pending.signature
+        );
+    }
+}
@@ -0,0 +1,95 @@
+use crate::error::AppError;
+use crate::metrics;
+use rdkafka::config::ClientConfig;
+use rdkafka::error::{KafkaError, RDKafkaErrorCode};
+use rdkafka::producer::{BaseRecord, DeliveryResult, Producer, ProducerContext, ThreadedProducer};
+use rdkafka::ClientContext;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::{info, warn};
+
+/// Messages librdkafka buffers while brokers are slow or unreachable.
+/// 
+/// Once full, new transactions are dropped and counted instead of waiting, so an
+/// unavailable broker never holds up the stream.
+const KAFKA_QUEUE_MESSAGES: &str = "100000";
+
+/// How long librdkafka keeps retrying a message before reporting it undelivered.
+const KAFKA_MESSAGE_TIMEOUT_MS: &str = "60000";
+
+/// Counts messages librdkafka gave up delivering.
+struct DeliveryCounter;
+
+impl ClientContext for DeliveryCounter {}
+
+impl ProducerContext for DeliveryCounter {
+    type DeliveryOpaque = ();
+
+    fn delivery(&self, result: &DeliveryResult<'_>, _: Self::DeliveryOpaque) {
+        match result {
+            Ok(_) => metrics::KAFKA_PUBLISHED.inc(),
+            Err((e, _)) => {
+                warn!(error = %e, "Failed to deliver transaction to Kafka");
+                metrics::KAFKA_DROPPED.with_label_values(&["delivery_failed"]).inc();
+            }
+        }
+    }
+}
+
+/// Publishes newly inserted transactions from the feed to a Kafka topic.
+/// 
+/// The database stays the source of truth: only transactions already written are
+/// published, and a transaction that can't be queued is dropped rather than retried.
+/// Drops are counted in `solana_tracker_kafka_dropped_total` by reason.
+pub struct KafkaPublisher {
+    producer: ThreadedProducer<DeliveryCounter>,
+    topic: String,
+}
+
+impl KafkaPublisher {
+    /// Create a producer for `brokers` (comma-separated host:port list) publishing to `topic`.
+    /// 
+    /// Brokers are contacted lazily, so this succeeds even while they are unreachable.
+    pub fn new(brokers: &str, topic: String) -> Result<Self, AppError> {
+        let producer = ClientConfig::new()
+            .set("bootstrap.servers", brokers)
+            .set("queue.buffering.max.messages", KAFKA_QUEUE_MESSAGES)
+            .set("message.timeout.ms", KAFKA_MESSAGE_TIMEOUT_MS)
+            .create_with_context(DeliveryCounter)
+            .map_err(|e| AppError::Config(format!("Failed to create Kafka producer: {}", e)))?;
+
+        Ok(Self { producer, topic })
+    }
+
+    /// Forward every transaction received from the feed until it closes.
+    pub async fn run(self, mut receiver: broadcast::Receiver<Arc<str>>) {
+        info!(topic = %self.topic, "Publishing inserted transactions to Kafka");
+
+        loop {
+            let json = match receiver.recv().await {
+                Ok(json) => json,
+                // The feed outran the producer; those transactions are gone from the channel
+                Err(RecvError::Lagged(skipped)) => {
+                    metrics::KAFKA_DROPPED.with_label_values(&["lagged"]).inc_by(skipped);
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+
+            let record: BaseRecord<'_, (), str> = BaseRecord::to(&self.topic).payload(json.as_ref());
+            if let Err((e, _)) = self.producer.send(record) {
+                let reason = match e {
+                    KafkaError::MessageProduction(RDKafkaErrorCode::QueueFull) => "queue_full",
+                    _ => "produce_failed",
+                };
+                metrics::KAFKA_DROPPED.with_label_values(&[reason]).inc();
+            }
+        }
+
+        // Give queued messages a last chance before the producer is dropped
+        if let Err(e) = self.producer.flush(Duration::from_secs(10)) {
+            warn!(error = %e, "Failed to flush Kafka producer");
+        }
+    }
+}
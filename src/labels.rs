@@ -0,0 +1,64 @@
+use crate::error::AppError;
+use std::collections::HashMap;
+
+/// Human-readable names for well-known accounts, used to annotate log output.
+/// 
+/// Labels are configured as a JSON object mapping base58 pubkeys to names,
+/// e.g. `{"MEViEnscUm6tsQRoGd9h6nLQaQspKj7DB2M5FwM3Xvz": "mev-bot"}`.
+#[derive(Debug, Clone, Default)]
+pub struct AccountLabels {
+    labels: HashMap<String, String>,
+}
+
+impl AccountLabels {
+    /// Parse a JSON object of pubkey -> name pairs.
+    /// 
+    /// Every key must be a valid base58 string so typos surface at startup
+    /// rather than as silently unlabeled accounts.
+    pub fn from_json(json: &str) -> Result<Self, AppError> {
+        let labels: HashMap<String, String> = serde_json::from_str(json)
+            .map_err(|e| AppError::Config(format!("Invalid ACCOUNT_LABELS JSON: {}", e)))?;
+
+        for pubkey in labels.keys() {
+            bs58::decode(pubkey).into_vec().map_err(|e| {
+                AppError::Config(format!("Invalid base58 address in ACCOUNT_LABELS '{}': {}", pubkey, e))
+            })?;
+        }
+
+        Ok(Self { labels })
+    }
+
+    /// Return the configured name for a pubkey, or a truncated form of the pubkey.
+    /// 
+    /// Truncated pubkeys keep the first and last four characters (`MEVi…3Xvz`),
+    /// which is enough to tell accounts apart when scanning logs.
+    pub fn label_for(&self, pubkey: &str) -> String {
+        if let Some(name) = self.labels.get(pubkey) {
+            return name.clone();
+        }
+
+        truncate_pubkey(pubkey)
+    }
+}
+
+/// Identify a gRPC endpoint by host and port, for metric labels and logs.
+/// 
+/// The scheme and path are dropped because some providers embed an API key in the path.
+pub fn endpoint_label(url: &str) -> String {
+    let without_scheme = url.split_once("://").map(|(_, rest)| rest).unwrap_or(url);
+
+    without_scheme
+        .split(['/', '?'])
+        .next()
+        .unwrap_or(without_scheme)
+        .to_string()
+}
+
+/// Shorten a pubkey to its first and last four characters (`MEVi…3Xvz`).
+pub fn truncate_pubkey(pubkey: &str) -> String {
+    if pubkey.len() <= 8 || !pubkey.is_ascii() {
+        return pubkey.to_string();
+    }
+
+    format!("{}…{}", &pubkey[..4], &pubkey[pubkey.len() - 4..])
+}
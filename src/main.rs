@@ -2,55 +2,186 @@ mod config;
 mod database;
 mod error;
 mod grpc;
+#[cfg(feature = "kafka")]
+mod kafka;
+mod labels;
 mod metrics;
 mod metrics_server;
 mod solana;
 mod telemetry;
 
-use crate::config::AppConfig;
-use crate::database::{connection, repository::TransactionRepository};
+use crate::config::{AppConfig, Commitment};
+use crate::database::{
+    connection, feed::{TransactionFeed, DEFAULT_FEED_CAPACITY}, maintenance,
+    repository::TransactionRepository, sqlite::SqliteRepository, stats::SuccessStatsCache, store::TransactionStore,
+};
 use crate::error::AppError;
 use crate::grpc::client::RpcClient;
-use crate::grpc::stream_handler::process_account_stream;
+use crate::grpc::concurrency::AdaptiveConcurrency;
+use crate::grpc::dedup::{SignatureDedup, DEFAULT_DEDUP_CAPACITY};
+use crate::grpc::finality::FinalityGate;
+use crate::grpc::preflight::run_preflight;
+use crate::grpc::reconcile::{run_reconciliation, ReconcileSettings};
+use crate::grpc::source::StreamSource;
+use crate::grpc::stream_handler::{process_account_stream, replay_from_file, ProcessingOptions};
+use crate::labels::endpoint_label;
+use crate::solana::parser::ParseOptions;
+use futures::stream::{FuturesUnordered, StreamExt};
+use std::path::PathBuf;
 use std::sync::Arc;
 use tracing::info;
+use yellowstone_grpc_proto::prelude::CommitmentLevel;
+
+/// How the indexer was invoked from the command line.
+enum RunMode {
+    /// Subscribe to the live gRPC stream (default)
+    Stream,
+    /// Process updates previously recorded with RECORD_UPDATES_FILE (`replay --file <path>`)
+    Replay(PathBuf),
+}
+
+impl RunMode {
+    /// Parse the run mode from command-line arguments.
+    fn from_args() -> Result<Self, AppError> {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+
+        match args.as_slice() {
+            [] => Ok(RunMode::Stream),
+            [command, flag, path] if command == "replay" && flag == "--file" => {
+                Ok(RunMode::Replay(PathBuf::from(path)))
+            }
+            _ => Err(AppError::Config(format!(
+                "Unrecognized arguments: {}. Usage: mev-burn-indexer [replay --file <path>]",
+                args.join(" ")
+            ))),
+        }
+    }
+}
 
 #[tokio::main]
 async fn main() -> Result<(), AppError> {
     // Load environment variables from .env file if present
     dotenvy::dotenv().ok();
 
+    let mode = RunMode::from_args()?;
+
     // Load and validate configuration
     let config = AppConfig::from_env()?;
 
     // Initialize telemetry (structured logging)
-    telemetry::init_telemetry(&config.log_level);
+    telemetry::init_telemetry(&config.log_level, config.tokio_console);
+
+    // Provider-specific transient errors the built-in classification misses
+    error::set_retryable_patterns(config.retryable_error_patterns.clone());
 
     // Initialize metrics with proper error handling
+    metrics::set_timing_buckets(
+        config.processing_time_buckets.clone(),
+        config.db_time_buckets.clone(),
+    );
     metrics::init_metrics()?;
 
     info!("Starting Solana Bot Transaction Tracker");
     info!(
-        target_account = %config.target_account,
-        grpc_endpoint = %config.grpc_endpoint,
+        grpc_endpoints = %config.grpc_endpoints.join(", "),
         "Configuration loaded"
     );
 
-    // Establish database connection
-    let mut db_client = connection::create_client(&config.database_url).await?;
+    // Newly inserted transactions, streamed to /stream subscribers
+    let feed = Arc::new(TransactionFeed::new(DEFAULT_FEED_CAPACITY));
+
+    // Select the storage backend from the DATABASE_URL scheme
+    if let Some(path) = config.database_url.strip_prefix("sqlite://") {
+        if config.balance_changes_jsonb {
+            return Err(AppError::Config(
+                "BALANCE_CHANGES_STORAGE=jsonb requires PostgreSQL".to_string(),
+            ));
+        }
+
+        // SQLite applies its own embedded migrations when opened
+        let repository = Arc::new(SqliteRepository::open(path)?.with_feed(feed.clone()));
+        run_indexer(config, mode, repository, feed).await
+    } else {
+        // Establish database connection
+        let mut db_client = connection::create_client(
+            &config.database_url,
+            &config.db_application_name,
+            config.db_connect_attempts,
+            std::time::Duration::from_millis(config.db_connect_retry_delay_ms),
+        ).await?;
+
+        // Run database migrations using refinery
+        connection::run_migrations(&mut db_client).await?;
+
+        // Only after migrations, which may run longer than the timeout allows
+        connection::set_statement_timeout(&db_client, config.db_statement_timeout_ms).await?;
+
+        // Inserts must target the partition-aware unique key once the opt-in partitioning script has run
+        let partitioned = connection::is_transactions_partitioned(&db_client).await?;
+        if partitioned {
+            info!("transactions table is partitioned by fee_payer");
+        }
+
+        // Its migration skips the unique index rather than deleting existing duplicates
+        if !connection::has_unique_balance_changes(&db_client).await? {
+            tracing::warn!("account_balance_changes has duplicate rows and no unique index; run migrations_optional/dedupe_balance_changes.sql");
+        }
+
+        // Create repository for database operations
+        let mut repository = TransactionRepository::new(db_client)
+            .with_partitioned_transactions(partitioned)
+            .with_balance_changes_jsonb(config.balance_changes_jsonb)
+            .with_feed(feed.clone());
+
+        // VACUUM can outlast the statement timeout, so maintenance gets a session without one
+        if config.db_maintenance_interval_secs > 0 {
+            let maintenance_client = connection::create_client(
+                &config.database_url,
+                &format!("{}-maintenance", config.db_application_name),
+                config.db_connect_attempts,
+                std::time::Duration::from_millis(config.db_connect_retry_delay_ms),
+            ).await?;
+            repository = repository.with_maintenance_client(maintenance_client);
+        }
+
+        run_indexer(config, mode, Arc::new(repository), feed).await
+    }
+}
 
-    // Run database migrations using refinery
-    connection::run_migrations(&mut db_client).await?;
+/// Start the background tasks and stream processing against the given store.
+async fn run_indexer<S: TransactionStore>(
+    config: AppConfig,
+    mode: RunMode,
+    repository: Arc<S>,
+    feed: Arc<TransactionFeed>,
+) -> Result<(), AppError> {
+    let target_accounts = resolve_target_accounts(&config, repository.as_ref()).await?;
+    metrics::set_build_info(&target_accounts, config.stream_commitment.as_str());
 
-    // Create repository for database operations
-    let repository = Arc::new(TransactionRepository::new(db_client));
+    for account in &target_accounts {
+        info!(
+            target_account = %account,
+            target_label = %config.account_labels.label_for(account),
+            "Monitoring account"
+        );
+    }
 
-    // Create RPC client for Yellowstone gRPC subscription
-    let rpc_client = RpcClient::new(
-        config.grpc_endpoint.clone(),
-        config.grpc_token.clone(),
-        &config.target_account,
-        config.include_failed_transactions,
+    // Verify the RPC node can serve transactions at the commitment they will be fetched at
+    run_preflight(
+        &config.rpc_http_url,
+        &target_accounts[0],
+        config.fetch_commitment.unwrap_or(config.stream_commitment),
+        config.target_is_program,
+        config.preflight_strict,
+    ).await?;
+
+    // Create an RPC client per Yellowstone gRPC endpoint
+    let rpc_clients = create_clients(
+        &config,
+        &target_accounts,
+        config.stream_commitment.into(),
+        config.subscribe_blocks_meta,
+        false,
     )?;
 
     if config.include_failed_transactions {
@@ -61,10 +192,34 @@ async fn main() -> Result<(), AppError> {
 
     info!("All systems initialized, starting stream processing");
 
+    // Persisted success/failure counts over the last 24 hours, cached for 30 seconds
+    let stats = Arc::new(SuccessStatsCache::new(
+        repository.clone(),
+        std::time::Duration::from_secs(24 * 60 * 60),
+        std::time::Duration::from_secs(30),
+    ));
+
+    // Publish inserted transactions to Kafka; subscribed before streaming starts so none are missed
+    if let (Some(brokers), Some(topic)) = (&config.kafka_brokers, &config.kafka_topic) {
+        #[cfg(feature = "kafka")]
+        {
+            let publisher = kafka::KafkaPublisher::new(brokers, topic.clone())?;
+            info!(brokers = %brokers, topic = %topic, "Kafka publishing enabled");
+            tokio::spawn(publisher.run(feed.subscribe()));
+        }
+        #[cfg(not(feature = "kafka"))]
+        tracing::warn!(
+            brokers = %brokers,
+            topic = %topic,
+            "KAFKA_BROKERS is set but this binary was built without the kafka feature"
+        );
+    }
+
     // Start metrics server in background
     let metrics_port = config.metrics_port;
+    let export_repository = repository.clone();
     tokio::spawn(async move {
-        if let Err(e) = metrics_server::start_metrics_server(metrics_port).await {
+        if let Err(e) = metrics_server::start_metrics_server(metrics_port, stats, export_repository, feed).await {
             tracing::error!("Metrics server error: {}", e);
         }
     });
@@ -78,12 +233,383 @@ async fn main() -> Result<(), AppError> {
         }
     });
 
+    // Periodic summary from the metrics, for environments without Prometheus
+    if config.self_report_interval_secs > 0 {
+        let report_interval = tokio::time::Duration::from_secs(config.self_report_interval_secs);
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(report_interval).await;
+
+                let snapshot = metrics::snapshot();
+                // None until the first transaction is persisted
+                let last_tx_age_seconds = (snapshot.last_transaction_timestamp > 0.0)
+                    .then(|| chrono::Utc::now().timestamp() as f64 - snapshot.last_transaction_timestamp);
+
+                info!(
+                    processed = snapshot.transactions_processed,
+                    failed = snapshot.transactions_failed,
+                    reconnections = snapshot.stream_reconnections,
+                    connected = snapshot.stream_connected,
+                    last_tx_age_seconds = ?last_tx_age_seconds,
+                    "Self-report"
+                );
+            }
+        });
+    }
+
+    // Push metrics for environments where the indexer can't be scraped
+    if let Some(url) = config.metrics_push_url.clone() {
+        let push_interval = tokio::time::Duration::from_secs(config.metrics_push_interval_secs);
+        info!(url = %url, interval_secs = config.metrics_push_interval_secs, "Pushing metrics");
+        tokio::spawn(metrics::push_metrics(url, push_interval));
+    }
+
+    let options = ProcessingOptions {
+        max_balance_changes: config.max_balance_changes,
+        fetch_commitment: config.fetch_commitment,
+        slot_commitment: config.slot_commitment,
+        confirmed_fallback_after: (config.confirmed_fallback_after > 0)
+            .then_some(config.confirmed_fallback_after),
+        parse: ParseOptions {
+            balance_change_accounts: config
+                .balance_changes_target_only
+                .then(|| target_accounts.iter().cloned().collect()),
+            ui_amounts: config.store_ui_amounts,
+            min_token_change: config.min_token_change.clone(),
+            mint_allowlist: (!config.mint_allowlist.is_empty())
+                .then(|| config.mint_allowlist.iter().cloned().collect()),
+            skip_sol_changes: !config.store_sol_balance_changes,
+            burn_rate_percent: config.burn_rate_percent,
+        },
+        record_path: config.record_updates_file.clone(),
+        min_fee_lamports: config.min_fee_lamports,
+        program_allowlist: (!config.program_allowlist.is_empty())
+            .then(|| config.program_allowlist.iter().cloned().collect()),
+        fetch_delay: (config.fetch_delay_ms > 0)
+            .then(|| std::time::Duration::from_millis(config.fetch_delay_ms)),
+        process_timeout: (config.process_timeout_secs > 0)
+            .then(|| std::time::Duration::from_secs(config.process_timeout_secs)),
+        slow_db_threshold: (config.slow_db_log_ms > 0)
+            .then(|| std::time::Duration::from_millis(config.slow_db_log_ms)),
+        sample_rate: (config.sample_rate < 1.0).then_some(config.sample_rate),
+        skip_zero_balance_changes: config.skip_zero_balance_change,
+        store_raw_json: config.store_raw_json,
+        strict_target_accounts: config
+            .strict_target_only
+            .then(|| target_accounts.iter().cloned().collect()),
+        drain_timeout: (config.drain_timeout_secs > 0)
+            .then(|| std::time::Duration::from_secs(config.drain_timeout_secs)),
+        max_reconnect_attempts: (config.max_reconnect_attempts > 0).then_some(config.max_reconnect_attempts),
+        max_connection_age: (config.grpc_max_conn_age_secs > 0)
+            .then(|| std::time::Duration::from_secs(config.grpc_max_conn_age_secs)),
+        dedup: None,
+        // A Finalized stream only ever delivers finalized transactions, so it needs no gate
+        finality_gate: (config.persist_only_finalized && config.stream_commitment != Commitment::Finalized)
+            .then(|| Arc::new(FinalityGate::new())),
+        // Labeled by ACCOUNT_LABELS name or truncated pubkey; accounts were validated as base58 on load
+        account_metric_labels: config.per_account_metrics.then(|| {
+            Arc::new(
+                target_accounts
+                    .iter()
+                    .filter_map(|account| {
+                        let key = bs58::decode(account).into_vec().ok()?;
+                        Some((key, config.account_labels.label_for(account)))
+                    })
+                    .collect(),
+            )
+        }),
+    };
+
+    // RPC fetch concurrency adapts to rate limiting; shared so both streams back off together
+    let concurrency = Arc::new(AdaptiveConcurrency::new(1, config.rpc_max_concurrency));
+
+    if let RunMode::Replay(path) = mode {
+        // Replayed updates are handled as if delivered by a stream at the configured commitment
+        return replay_from_file(
+            &path,
+            &config.rpc_http_url,
+            repository,
+            config.stream_commitment.into(),
+            options,
+            concurrency,
+        ).await;
+    }
+
+    // Periodically compare recent on-chain signatures with what the streams stored
+    if config.reconcile_interval_secs > 0 {
+        let settings = ReconcileSettings {
+            accounts: target_accounts.clone(),
+            interval: std::time::Duration::from_secs(config.reconcile_interval_secs),
+            backfill: config.reconcile_backfill,
+            include_failed_transactions: config.include_failed_transactions,
+        };
+        tokio::spawn(run_reconciliation(
+            config.rpc_http_url.clone(),
+            repository.clone(),
+            options.clone(),
+            concurrency.clone(),
+            settings,
+        ));
+    }
+
+    // Keep planner statistics fresh where autovacuum isn't tuned for the insert rate
+    if config.db_maintenance_interval_secs > 0 {
+        tokio::spawn(maintenance::run_maintenance(
+            repository.clone(),
+            std::time::Duration::from_secs(config.db_maintenance_interval_secs),
+            config.db_maintenance_vacuum,
+        ));
+    }
+
+    // Finalization is learned from a lightweight status subscription instead of RPC polling
+    if config.track_finalization {
+        let status_clients = create_clients(
+            &config,
+            &target_accounts,
+            CommitmentLevel::Finalized,
+            false,
+            true,
+        )?;
+
+        info!("Finalization tracking enabled, subscribing to Finalized transaction status updates");
+
+        let http_url = config.rpc_http_url.clone();
+        let status_options = ProcessingOptions {
+            record_path: None,
+            ..options.clone()
+        };
+        let repository = repository.clone();
+        let concurrency = concurrency.clone();
+        tokio::spawn(async move {
+            if let Err(e) = process_streams(
+                status_clients,
+                &http_url,
+                repository,
+                status_options,
+                concurrency,
+            ).await {
+                tracing::error!(error = %e, "Transaction status stream stopped");
+            }
+        });
+    }
+
     // Start processing the account stream (runs indefinitely with auto-reconnection)
-    process_account_stream(
-        rpc_client,
-        &config.rpc_http_url,
-        repository
-    ).await?;
+    if config.dual_commitment {
+        // A second Finalized stream corrects rows first written at Confirmed
+        let finalized_clients = create_clients(
+            &config,
+            &target_accounts,
+            CommitmentLevel::Finalized,
+            // Blocks come from the primary stream only, to avoid a second copy of every slot
+            false,
+            false,
+        )?;
+
+        info!("Dual commitment enabled, running Confirmed and Finalized streams");
+
+        // The Finalized stream always fetches at Finalized, regardless of FETCH_COMMITMENT,
+        // and only the primary stream is recorded so the two streams don't interleave in one file
+        let finalized_options = ProcessingOptions {
+            fetch_commitment: None,
+            record_path: None,
+            ..options.clone()
+        };
+
+        tokio::try_join!(
+            process_streams(
+                rpc_clients,
+                &config.rpc_http_url,
+                repository.clone(),
+                options,
+                concurrency.clone(),
+            ),
+            process_streams(
+                finalized_clients,
+                &config.rpc_http_url,
+                repository,
+                finalized_options,
+                concurrency,
+            ),
+        )?;
+    } else {
+        process_streams(
+            rpc_clients,
+            &config.rpc_http_url,
+            repository,
+            options,
+            concurrency,
+        ).await?;
+    }
 
     Ok(())
 }
+
+/// Create a Yellowstone client for every configured gRPC endpoint.
+fn create_clients(
+    config: &AppConfig,
+    target_accounts: &[String],
+    commitment: CommitmentLevel,
+    subscribe_blocks_meta: bool,
+    status_only: bool,
+) -> Result<Vec<RpcClient>, AppError> {
+    config
+        .grpc_endpoints
+        .iter()
+        .zip(&config.grpc_tokens)
+        .map(|(endpoint, token)| {
+            RpcClient::new(
+                endpoint.clone(),
+                token.clone(),
+                target_accounts,
+                config.include_failed_transactions,
+                commitment,
+                subscribe_blocks_meta,
+                status_only,
+            )
+            .map(|client| {
+                client
+                    .with_gzip_compression(config.grpc_compression)
+                    .with_account_data_slice(config.account_data_slice.clone())
+                    // Slot updates carry one request-wide commitment, so a different slot
+                    // commitment is picked out of the updates for every status
+                    .with_all_slot_statuses(
+                        config.persist_only_finalized
+                            || config
+                                .slot_commitment
+                                .is_some_and(|slot_commitment| CommitmentLevel::from(slot_commitment) != commitment),
+                    )
+                    .with_target_is_program(config.target_is_program)
+                    .with_account_required(config.account_required.clone())
+            })
+        })
+        .collect()
+}
+
+/// Process one stream per client, all writing into the same store.
+/// 
+/// With several endpoints the streams share a signature dedup set, so a transaction
+/// delivered by more than one endpoint is fetched once. Only the first endpoint's
+/// updates are recorded, keeping duplicates out of RECORD_UPDATES_FILE.
+/// 
+/// Endpoints run independently: one that stops with an error, even a fatal or
+/// reconnect-limit one, leaves the others streaming. The first error is returned
+/// once every endpoint has stopped, so a failure shared by all of them (such as a
+/// bad token) still ends the process.
+async fn process_streams<S: TransactionStore, C: StreamSource>(
+    clients: Vec<C>,
+    http_url: &str,
+    repository: Arc<S>,
+    options: ProcessingOptions,
+    concurrency: Arc<AdaptiveConcurrency>,
+) -> Result<(), AppError> {
+    let dedup = (clients.len() > 1)
+        .then(|| Arc::new(SignatureDedup::new(DEFAULT_DEDUP_CAPACITY)));
+
+    let mut streams: FuturesUnordered<_> = clients
+        .into_iter()
+        .enumerate()
+        .map(|(index, client)| {
+            let endpoint = endpoint_label(client.endpoint());
+            let options = ProcessingOptions {
+                record_path: options.record_path.clone().filter(|_| index == 0),
+                dedup: dedup.clone(),
+                ..options.clone()
+            };
+            let stream = process_account_stream(client, http_url, repository.clone(), options, concurrency.clone());
+            async move { (endpoint, stream.await) }
+        })
+        .collect();
+
+    let mut first_error = None;
+    while let Some((endpoint, result)) = streams.next().await {
+        let Err(e) = result else {
+            continue;
+        };
+
+        if !streams.is_empty() {
+            tracing::error!(
+                endpoint = %endpoint,
+                error = %e,
+                remaining_endpoints = streams.len(),
+                "Endpoint stopped, continuing on the remaining endpoints"
+            );
+        }
+        first_error.get_or_insert(e);
+    }
+
+    first_error.map_or(Ok(()), Err)
+}
+
+/// Determine which accounts to monitor.
+/// 
+/// TARGET_ACCOUNT wins when set; otherwise the watched_accounts table supplies the
+/// watchlist. Having neither is a configuration error.
+async fn resolve_target_accounts<S: TransactionStore>(
+    config: &AppConfig,
+    repository: &S,
+) -> Result<Vec<String>, AppError> {
+    if let Some(target_account) = &config.target_account {
+        return Ok(vec![target_account.clone()]);
+    }
+
+    let accounts = repository.list_watched_accounts().await?;
+    if accounts.is_empty() {
+        return Err(AppError::Config(
+            "TARGET_ACCOUNT not set and the watched_accounts table is empty".to_string(),
+        ));
+    }
+
+    info!(
+        accounts = accounts.len(),
+        "TARGET_ACCOUNT not set, monitoring accounts from the watched_accounts table"
+    );
+
+    Ok(accounts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::sqlite::SqliteRepository;
+    use crate::grpc::stream_handler::tests::{update, ScriptedSource};
+    use yellowstone_grpc_proto::geyser::subscribe_update::UpdateOneof;
+    use yellowstone_grpc_proto::prelude::SubscribeUpdateSlot;
+
+    fn slot_update(slot: u64) -> yellowstone_grpc_proto::geyser::SubscribeUpdate {
+        update(UpdateOneof::Slot(SubscribeUpdateSlot {
+            slot,
+            parent: Some(slot - 1),
+            status: CommitmentLevel::Confirmed as i32,
+        }))
+    }
+
+    fn fatal() -> AppError {
+        AppError::fatal_subscription("Stream rejected", tonic::Status::unauthenticated("bad token"))
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn one_endpoint_failing_leaves_the_others_streaming() {
+        let failing = ScriptedSource::new(vec![Err(fatal())]);
+        let healthy = ScriptedSource::new(vec![
+            Ok(vec![slot_update(100)]),
+            Ok(vec![slot_update(101)]),
+            Ok(vec![slot_update(102)]),
+            Err(fatal()),
+        ]);
+        let repository = Arc::new(SqliteRepository::open(":memory:").unwrap());
+
+        let result = process_streams(
+            vec![failing.clone(), healthy.clone()],
+            "http://127.0.0.1:8899",
+            repository,
+            ProcessingOptions::default(),
+            Arc::new(AdaptiveConcurrency::new(1, 1)),
+        )
+        .await;
+
+        // The healthy endpoint played out its whole script after the other one failed
+        assert_eq!(failing.remaining(), 0);
+        assert_eq!(healthy.remaining(), 0);
+        assert!(matches!(result, Err(AppError::FatalSubscription { .. })));
+    }
+}
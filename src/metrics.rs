@@ -1,15 +1,42 @@
 use crate::error::AppError;
+use crate::labels::truncate_pubkey;
 use lazy_static::lazy_static;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tracing::{debug, warn};
 use prometheus::{
-    Counter, Gauge, Histogram, HistogramOpts, IntCounter, IntGauge, Opts, Registry,
+    Counter, Gauge, Histogram, HistogramOpts, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts,
+    Registry,
 };
+use prometheus::proto::{MetricFamily, MetricType};
 
 lazy_static! {
     pub static ref REGISTRY: Registry = Registry::new();
 }
 
+const DEFAULT_PROCESSING_TIME_BUCKETS: &[f64] = &[0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0];
+const DEFAULT_DB_TIME_BUCKETS: &[f64] = &[0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0];
+
+/// Bucket overrides for the timing histograms, as (processing time, database time).
+type TimingBuckets = (Option<Vec<f64>>, Option<Vec<f64>>);
+
+static TIMING_BUCKETS: OnceLock<TimingBuckets> = OnceLock::new();
+
+/// Upper bound on accounts remembered for `solana_tracker_distinct_accounts`.
+/// 
+/// Caps memory at a few megabytes; past it the gauge stops growing and reads as a lower bound.
+const MAX_TRACKED_ACCOUNTS: usize = 100_000;
+
+/// Accounts seen in persisted balance changes, for the distinct accounts gauge.
+static SEEN_ACCOUNTS: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+
+/// Receipt times of stream updates currently waiting for a fetch slot.
+static PENDING_UPDATES: OnceLock<Mutex<Vec<Instant>>> = OnceLock::new();
+
 // Create metrics with Result returns to enable proper error handling
-fn create_transaction_metrics() -> Result<(IntCounter, IntCounter, IntCounter), AppError> {
+fn create_transaction_metrics() -> Result<(IntCounter, IntCounter, IntCounter, IntCounter), AppError> {
     let processed = IntCounter::new(
         "solana_tracker_transactions_processed_total",
         "Total number of transactions processed"
@@ -25,10 +52,32 @@ fn create_transaction_metrics() -> Result<(IntCounter, IntCounter, IntCounter),
         "Total number of balance changes recorded"
     ).map_err(|e| AppError::Config(format!("Failed to create balance_changes metric: {}", e)))?;
 
-    Ok((processed, failed, balance_changes))
+    let truncated_balance_changes = IntCounter::new(
+        "solana_tracker_truncated_balance_changes_total",
+        "Total number of balance changes dropped by the per-transaction cap"
+    ).map_err(|e| AppError::Config(format!("Failed to create truncated_balance_changes metric: {}", e)))?;
+
+    Ok((processed, failed, balance_changes, truncated_balance_changes))
 }
 
-fn create_stream_metrics() -> Result<(IntCounter, IntGauge), AppError> {
+fn create_balance_change_error_metrics() -> Result<IntCounter, AppError> {
+    IntCounter::new(
+        "solana_tracker_balance_change_insert_failures_total",
+        "Total number of balance changes that failed to insert for a reason other than a duplicate"
+    ).map_err(|e| AppError::Config(format!("Failed to create balance_change_insert_failures metric: {}", e)))
+}
+
+fn create_account_metrics() -> Result<IntCounterVec, AppError> {
+    IntCounterVec::new(
+        Opts::new(
+            "solana_tracker_account_transactions_processed_total",
+            "Total number of transactions processed per monitored account"
+        ),
+        &["account"]
+    ).map_err(|e| AppError::Config(format!("Failed to create account_transactions_processed metric: {}", e)))
+}
+
+fn create_stream_metrics() -> Result<(IntCounter, IntGauge, IntCounter, IntCounter, IntGauge), AppError> {
     let reconnections = IntCounter::new(
         "solana_tracker_stream_reconnections_total",
         "Total number of stream reconnection attempts"
@@ -39,27 +88,258 @@ fn create_stream_metrics() -> Result<(IntCounter, IntGauge), AppError> {
         "Stream connection status (1=connected, 0=disconnected)"
     ).map_err(|e| AppError::Config(format!("Failed to create stream_connected metric: {}", e)))?;
 
-    Ok((reconnections, connected))
+    let ping_failures = IntCounter::new(
+        "solana_tracker_ping_failures_total",
+        "Total number of keep-alive pings that failed to send"
+    ).map_err(|e| AppError::Config(format!("Failed to create ping_failures metric: {}", e)))?;
+
+    let fatal_subscription_errors = IntCounter::new(
+        "solana_tracker_fatal_subscription_errors_total",
+        "Total number of subscription errors treated as fatal (auth, permission, bad filter)"
+    ).map_err(|e| AppError::Config(format!("Failed to create fatal_subscription_errors metric: {}", e)))?;
+
+    let processing_backlog = IntGauge::new(
+        "solana_tracker_stream_processing_backlog",
+        "Transaction updates received from the stream but not yet fully processed"
+    ).map_err(|e| AppError::Config(format!("Failed to create stream_processing_backlog metric: {}", e)))?;
+
+    Ok((reconnections, connected, ping_failures, fatal_subscription_errors, processing_backlog))
 }
 
 fn create_timing_metrics() -> Result<(Histogram, Histogram), AppError> {
+    let (processing_buckets, db_buckets) = match TIMING_BUCKETS.get() {
+        Some((processing, db)) => (processing.clone(), db.clone()),
+        None => (None, None),
+    };
+
     let processing_time = Histogram::with_opts(
         HistogramOpts::new(
             "solana_tracker_transaction_processing_seconds",
             "Time taken to process a transaction"
-        ).buckets(vec![0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0])
+        ).buckets(processing_buckets.unwrap_or_else(|| DEFAULT_PROCESSING_TIME_BUCKETS.to_vec()))
     ).map_err(|e| AppError::Config(format!("Failed to create transaction_processing_time metric: {}", e)))?;
 
     let db_time = Histogram::with_opts(
         HistogramOpts::new(
             "solana_tracker_database_operation_seconds",
             "Time taken for database operations"
-        ).buckets(vec![0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0])
+        ).buckets(db_buckets.unwrap_or_else(|| DEFAULT_DB_TIME_BUCKETS.to_vec()))
     ).map_err(|e| AppError::Config(format!("Failed to create database_operation_time metric: {}", e)))?;
 
     Ok((processing_time, db_time))
 }
 
+fn create_missed_transaction_metrics() -> Result<(IntCounter, IntCounter), AppError> {
+    let missed = IntCounter::new(
+        "solana_tracker_missed_transactions_total",
+        "Total number of transactions recorded as missed because RPC could not serve them"
+    ).map_err(|e| AppError::Config(format!("Failed to create missed_transactions metric: {}", e)))?;
+
+    let timeouts = IntCounter::new(
+        "solana_tracker_processing_timeouts_total",
+        "Total number of transactions that exceeded PROCESS_TIMEOUT_SECS and were recorded as missed"
+    ).map_err(|e| AppError::Config(format!("Failed to create processing_timeouts metric: {}", e)))?;
+
+    Ok((missed, timeouts))
+}
+
+fn create_filter_metrics() -> Result<(IntCounter, IntCounter, IntCounter, IntCounter, IntCounter), AppError> {
+    let filtered_by_fee = IntCounter::new(
+        "solana_tracker_filtered_by_fee_total",
+        "Total number of transactions not persisted because their fee was below MIN_FEE_LAMPORTS"
+    ).map_err(|e| AppError::Config(format!("Failed to create filtered_by_fee metric: {}", e)))?;
+
+    let filtered_by_program = IntCounter::new(
+        "solana_tracker_filtered_by_program_total",
+        "Total number of transactions not persisted because they invoked no PROGRAM_ALLOWLIST program"
+    ).map_err(|e| AppError::Config(format!("Failed to create filtered_by_program metric: {}", e)))?;
+
+    let sampled_out = IntCounter::new(
+        "solana_tracker_sampled_out_total",
+        "Total number of transactions skipped because they fell outside SAMPLE_RATE"
+    ).map_err(|e| AppError::Config(format!("Failed to create sampled_out metric: {}", e)))?;
+
+    let filtered_zero_balance_changes = IntCounter::new(
+        "solana_tracker_filtered_zero_balance_changes_total",
+        "Total number of transactions skipped because they produced no balance changes"
+    ).map_err(|e| AppError::Config(format!("Failed to create filtered_zero_balance_changes metric: {}", e)))?;
+
+    let filtered_non_target = IntCounter::new(
+        "solana_tracker_filtered_non_target_total",
+        "Total number of transactions skipped by STRICT_TARGET_ONLY because no target account signed, changed balance, or was invoked"
+    ).map_err(|e| AppError::Config(format!("Failed to create filtered_non_target metric: {}", e)))?;
+
+    Ok((filtered_by_fee, filtered_by_program, sampled_out, filtered_zero_balance_changes, filtered_non_target))
+}
+
+fn create_incomplete_update_metrics() -> Result<IntCounterVec, AppError> {
+    IntCounterVec::new(
+        Opts::new(
+            "solana_tracker_incomplete_updates_total",
+            "Total number of stream transaction updates skipped for missing data, by reason"
+        ),
+        &["reason"]
+    ).map_err(|e| AppError::Config(format!("Failed to create incomplete_updates metric: {}", e)))
+}
+
+fn create_kafka_metrics() -> Result<(IntCounter, IntCounterVec), AppError> {
+    let published = IntCounter::new(
+        "solana_tracker_kafka_published_total",
+        "Total number of inserted transactions delivered to the Kafka topic"
+    ).map_err(|e| AppError::Config(format!("Failed to create kafka_published metric: {}", e)))?;
+
+    let dropped = IntCounterVec::new(
+        Opts::new(
+            "solana_tracker_kafka_dropped_total",
+            "Total number of inserted transactions not delivered to the Kafka topic, by reason"
+        ),
+        &["reason"]
+    ).map_err(|e| AppError::Config(format!("Failed to create kafka_dropped metric: {}", e)))?;
+
+    Ok((published, dropped))
+}
+
+fn create_integrity_metrics() -> Result<IntCounter, AppError> {
+    IntCounter::new(
+        "solana_tracker_signature_mismatch_total",
+        "Total number of fetched transactions whose signature differed from the requested one"
+    ).map_err(|e| AppError::Config(format!("Failed to create signature_mismatch metric: {}", e)))
+}
+
+fn create_block_metrics() -> Result<IntCounter, AppError> {
+    IntCounter::new(
+        "solana_tracker_blocks_recorded_total",
+        "Total number of blocks stored from the blocks_meta subscription"
+    ).map_err(|e| AppError::Config(format!("Failed to create blocks_recorded metric: {}", e)))
+}
+
+fn create_finalization_metrics() -> Result<IntCounter, AppError> {
+    IntCounter::new(
+        "solana_tracker_transactions_finalized_total",
+        "Total number of stored transactions marked finalized from transaction status updates"
+    ).map_err(|e| AppError::Config(format!("Failed to create transactions_finalized metric: {}", e)))
+}
+
+fn create_finality_gate_metrics() -> Result<(IntGauge, IntCounter), AppError> {
+    let pending = IntGauge::new(
+        "solana_tracker_pending_finalization",
+        "Fetched transactions held until their slot is finalized (PERSIST_ONLY_FINALIZED)"
+    ).map_err(|e| AppError::Config(format!("Failed to create pending_finalization metric: {}", e)))?;
+
+    let dropped = IntCounter::new(
+        "solana_tracker_unfinalized_dropped_total",
+        "Total number of held transactions dropped because their slot was never finalized"
+    ).map_err(|e| AppError::Config(format!("Failed to create unfinalized_dropped metric: {}", e)))?;
+
+    Ok((pending, dropped))
+}
+
+fn create_commitment_fallback_metrics() -> Result<IntCounter, AppError> {
+    IntCounter::new(
+        "solana_tracker_confirmed_fallback_fetches_total",
+        "Total number of transactions fetched at Confirmed after repeatedly not being found at Finalized"
+    ).map_err(|e| AppError::Config(format!("Failed to create confirmed_fallback_fetches metric: {}", e)))
+}
+
+fn create_shutdown_metrics() -> Result<IntCounter, AppError> {
+    IntCounter::new(
+        "solana_tracker_dropped_on_shutdown_total",
+        "Total number of in-flight transaction fetches and held transactions dropped when the drain timed out on stop"
+    ).map_err(|e| AppError::Config(format!("Failed to create dropped_on_shutdown metric: {}", e)))
+}
+
+fn create_coverage_metrics() -> Result<IntGauge, AppError> {
+    IntGauge::new(
+        "solana_tracker_distinct_accounts",
+        "Approximate number of distinct accounts seen in balance changes since startup"
+    ).map_err(|e| AppError::Config(format!("Failed to create distinct_accounts metric: {}", e)))
+}
+
+fn create_endpoint_metrics() -> Result<(IntGaugeVec, IntCounter), AppError> {
+    let connected = IntGaugeVec::new(
+        Opts::new(
+            "solana_tracker_endpoint_connected",
+            "Stream connection status per gRPC endpoint and commitment (1=connected, 0=disconnected)"
+        ),
+        &["endpoint", "commitment"],
+    ).map_err(|e| AppError::Config(format!("Failed to create endpoint_connected metric: {}", e)))?;
+
+    let duplicates = IntCounter::new(
+        "solana_tracker_duplicate_updates_total",
+        "Total number of transaction updates skipped because another endpoint delivered them first"
+    ).map_err(|e| AppError::Config(format!("Failed to create duplicate_updates metric: {}", e)))?;
+
+    Ok((connected, duplicates))
+}
+
+fn create_concurrency_metrics() -> Result<IntGauge, AppError> {
+    IntGauge::new(
+        "solana_tracker_rpc_concurrency_limit",
+        "Current limit on concurrent RPC transaction fetches"
+    ).map_err(|e| AppError::Config(format!("Failed to create rpc_concurrency_limit metric: {}", e)))
+}
+
+fn create_reconcile_metrics() -> Result<IntGauge, AppError> {
+    IntGauge::new(
+        "solana_tracker_missing_signatures",
+        "On-chain signatures missing from the database at the last reconcile run"
+    ).map_err(|e| AppError::Config(format!("Failed to create missing_signatures metric: {}", e)))
+}
+
+fn create_feed_metrics() -> Result<IntCounter, AppError> {
+    IntCounter::new(
+        "solana_tracker_feed_lagged_events_total",
+        "Transactions skipped by /stream subscribers that fell behind the feed"
+    ).map_err(|e| AppError::Config(format!("Failed to create feed_lagged_events metric: {}", e)))
+}
+
+fn create_queue_metrics() -> Result<(Histogram, Gauge), AppError> {
+    let wait = Histogram::with_opts(
+        HistogramOpts::new(
+            "solana_tracker_queue_wait_seconds",
+            "Time stream transaction updates waited for a fetch slot"
+        ).buckets(vec![0.001, 0.01, 0.1, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0])
+    ).map_err(|e| AppError::Config(format!("Failed to create queue_wait metric: {}", e)))?;
+
+    let oldest_pending = Gauge::new(
+        "solana_tracker_queue_oldest_pending_seconds",
+        "Age of the oldest stream transaction update still waiting for a fetch slot"
+    ).map_err(|e| AppError::Config(format!("Failed to create queue_oldest_pending metric: {}", e)))?;
+
+    Ok((wait, oldest_pending))
+}
+
+fn create_fee_metrics() -> Result<Histogram, AppError> {
+    // Spans the 5000 lamport base fee up to heavy priority fees, roughly log-spaced
+    Histogram::with_opts(
+        HistogramOpts::new(
+            "solana_tracker_transaction_fee_lamports",
+            "Distribution of transaction fees in lamports"
+        ).buckets(vec![
+            5_000.0, 10_000.0, 25_000.0, 50_000.0, 100_000.0, 250_000.0, 500_000.0,
+            1_000_000.0, 2_500_000.0, 5_000_000.0, 10_000_000.0, 25_000_000.0, 50_000_000.0,
+        ])
+    ).map_err(|e| AppError::Config(format!("Failed to create transaction_fee metric: {}", e)))
+}
+
+fn create_fanout_metrics() -> Result<Histogram, AppError> {
+    Histogram::with_opts(
+        HistogramOpts::new(
+            "solana_tracker_balance_changes_per_tx",
+            "Distribution of balance changes per transaction, before MAX_BALANCE_CHANGES truncation"
+        ).buckets(vec![0.0, 1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 100.0])
+    ).map_err(|e| AppError::Config(format!("Failed to create balance_changes_per_tx metric: {}", e)))
+}
+
+fn create_slot_gap_metrics() -> Result<Histogram, AppError> {
+    Histogram::with_opts(
+        HistogramOpts::new(
+            "solana_tracker_slot_processing_gap",
+            "Slots between the latest slot update and the slot of each transaction when it finished processing"
+        ).buckets(vec![0.0, 1.0, 2.0, 4.0, 8.0, 16.0, 32.0, 64.0, 128.0, 256.0, 512.0])
+    ).map_err(|e| AppError::Config(format!("Failed to create slot_processing_gap metric: {}", e)))
+}
+
 fn create_health_metrics() -> Result<(Gauge, Gauge, IntGauge), AppError> {
     let uptime = Gauge::new(
         "solana_tracker_uptime_seconds",
@@ -79,6 +359,30 @@ fn create_health_metrics() -> Result<(Gauge, Gauge, IntGauge), AppError> {
     Ok((uptime, last_tx, db_connections))
 }
 
+fn create_db_stats_metrics() -> Result<(IntGauge, IntGauge), AppError> {
+    let success = IntGauge::new(
+        "solana_tracker_db_transactions_success",
+        "Successful transactions persisted in the stats window"
+    ).map_err(|e| AppError::Config(format!("Failed to create db_transactions_success metric: {}", e)))?;
+
+    let failed = IntGauge::new(
+        "solana_tracker_db_transactions_failed",
+        "Failed transactions persisted in the stats window"
+    ).map_err(|e| AppError::Config(format!("Failed to create db_transactions_failed metric: {}", e)))?;
+
+    Ok((success, failed))
+}
+
+fn create_build_info_metric() -> Result<IntGaugeVec, AppError> {
+    IntGaugeVec::new(
+        Opts::new(
+            "solana_tracker_build_info",
+            "Build and configuration information, always 1"
+        ),
+        &["version", "git_sha", "target_account", "commitment"]
+    ).map_err(|e| AppError::Config(format!("Failed to create build_info metric: {}", e)))
+}
+
 fn create_error_metrics() -> Result<Counter, AppError> {
     Counter::with_opts(
         Opts::new(
@@ -98,18 +402,120 @@ lazy_static! {
     pub static ref BALANCE_CHANGES_RECORDED: IntCounter = create_transaction_metrics().ok().map(|m| m.2).unwrap_or_else(|| {
         IntCounter::new("fallback_balance_changes", "Fallback metric").unwrap()
     });
+    pub static ref TRUNCATED_BALANCE_CHANGES: IntCounter = create_transaction_metrics().ok().map(|m| m.3).unwrap_or_else(|| {
+        IntCounter::new("fallback_truncated_balance_changes", "Fallback metric").unwrap()
+    });
+    pub static ref ACCOUNT_TRANSACTIONS_PROCESSED: IntCounterVec = create_account_metrics().ok().unwrap_or_else(|| {
+        IntCounterVec::new(Opts::new("fallback_account_transactions_processed", "Fallback metric"), &["account"]).unwrap()
+    });
+    pub static ref BALANCE_CHANGE_INSERT_FAILURES: IntCounter = create_balance_change_error_metrics().ok().unwrap_or_else(|| {
+        IntCounter::new("fallback_balance_change_insert_failures", "Fallback metric").unwrap()
+    });
+    pub static ref MISSED_TRANSACTIONS: IntCounter = create_missed_transaction_metrics().ok().map(|m| m.0).unwrap_or_else(|| {
+        IntCounter::new("fallback_missed_transactions", "Fallback metric").unwrap()
+    });
+    pub static ref PROCESSING_TIMEOUTS: IntCounter = create_missed_transaction_metrics().ok().map(|m| m.1).unwrap_or_else(|| {
+        IntCounter::new("fallback_processing_timeouts", "Fallback metric").unwrap()
+    });
+    pub static ref FILTERED_BY_FEE: IntCounter = create_filter_metrics().ok().map(|m| m.0).unwrap_or_else(|| {
+        IntCounter::new("fallback_filtered_by_fee", "Fallback metric").unwrap()
+    });
+    pub static ref FILTERED_BY_PROGRAM: IntCounter = create_filter_metrics().ok().map(|m| m.1).unwrap_or_else(|| {
+        IntCounter::new("fallback_filtered_by_program", "Fallback metric").unwrap()
+    });
+    pub static ref SAMPLED_OUT: IntCounter = create_filter_metrics().ok().map(|m| m.2).unwrap_or_else(|| {
+        IntCounter::new("fallback_sampled_out", "Fallback metric").unwrap()
+    });
+    pub static ref FILTERED_ZERO_BALANCE_CHANGES: IntCounter = create_filter_metrics().ok().map(|m| m.3).unwrap_or_else(|| {
+        IntCounter::new("fallback_filtered_zero_balance_changes", "Fallback metric").unwrap()
+    });
+    pub static ref FILTERED_NON_TARGET: IntCounter = create_filter_metrics().ok().map(|m| m.4).unwrap_or_else(|| {
+        IntCounter::new("fallback_filtered_non_target", "Fallback metric").unwrap()
+    });
+    pub static ref INCOMPLETE_UPDATES: IntCounterVec = create_incomplete_update_metrics().ok().unwrap_or_else(|| {
+        IntCounterVec::new(Opts::new("fallback_incomplete_updates", "Fallback metric"), &["reason"]).unwrap()
+    });
+    pub static ref SIGNATURE_MISMATCHES: IntCounter = create_integrity_metrics().ok().unwrap_or_else(|| {
+        IntCounter::new("fallback_signature_mismatch", "Fallback metric").unwrap()
+    });
+    pub static ref BLOCKS_RECORDED: IntCounter = create_block_metrics().ok().unwrap_or_else(|| {
+        IntCounter::new("fallback_blocks_recorded", "Fallback metric").unwrap()
+    });
+    pub static ref TRANSACTIONS_FINALIZED: IntCounter = create_finalization_metrics().ok().unwrap_or_else(|| {
+        IntCounter::new("fallback_transactions_finalized", "Fallback metric").unwrap()
+    });
+    pub static ref PENDING_FINALIZATION: IntGauge = create_finality_gate_metrics().ok().map(|m| m.0).unwrap_or_else(|| {
+        IntGauge::new("fallback_pending_finalization", "Fallback metric").unwrap()
+    });
+    pub static ref UNFINALIZED_DROPPED: IntCounter = create_finality_gate_metrics().ok().map(|m| m.1).unwrap_or_else(|| {
+        IntCounter::new("fallback_unfinalized_dropped", "Fallback metric").unwrap()
+    });
+    pub static ref CONFIRMED_FALLBACK_FETCHES: IntCounter = create_commitment_fallback_metrics().ok().unwrap_or_else(|| {
+        IntCounter::new("fallback_confirmed_fallback_fetches", "Fallback metric").unwrap()
+    });
+    pub static ref DROPPED_ON_SHUTDOWN: IntCounter = create_shutdown_metrics().ok().unwrap_or_else(|| {
+        IntCounter::new("fallback_dropped_on_shutdown", "Fallback metric").unwrap()
+    });
+    pub static ref DISTINCT_ACCOUNTS: IntGauge = create_coverage_metrics().ok().unwrap_or_else(|| {
+        IntGauge::new("fallback_distinct_accounts", "Fallback metric").unwrap()
+    });
+    pub static ref ENDPOINT_CONNECTED: IntGaugeVec = create_endpoint_metrics().ok().map(|m| m.0).unwrap_or_else(|| {
+        IntGaugeVec::new(Opts::new("fallback_endpoint_connected", "Fallback metric"), &["endpoint", "commitment"]).unwrap()
+    });
+    pub static ref DUPLICATE_UPDATES: IntCounter = create_endpoint_metrics().ok().map(|m| m.1).unwrap_or_else(|| {
+        IntCounter::new("fallback_duplicate_updates", "Fallback metric").unwrap()
+    });
+    pub static ref KAFKA_PUBLISHED: IntCounter = create_kafka_metrics().ok().map(|m| m.0).unwrap_or_else(|| {
+        IntCounter::new("fallback_kafka_published", "Fallback metric").unwrap()
+    });
+    pub static ref KAFKA_DROPPED: IntCounterVec = create_kafka_metrics().ok().map(|m| m.1).unwrap_or_else(|| {
+        IntCounterVec::new(Opts::new("fallback_kafka_dropped", "Fallback metric"), &["reason"]).unwrap()
+    });
+    pub static ref FEED_LAGGED_EVENTS: IntCounter = create_feed_metrics().ok().unwrap_or_else(|| {
+        IntCounter::new("fallback_feed_lagged_events", "Fallback metric").unwrap()
+    });
+    pub static ref RPC_CONCURRENCY_LIMIT: IntGauge = create_concurrency_metrics().ok().unwrap_or_else(|| {
+        IntGauge::new("fallback_rpc_concurrency_limit", "Fallback metric").unwrap()
+    });
+    pub static ref MISSING_SIGNATURES: IntGauge = create_reconcile_metrics().ok().unwrap_or_else(|| {
+        IntGauge::new("fallback_missing_signatures", "Fallback metric").unwrap()
+    });
     pub static ref STREAM_RECONNECTIONS: IntCounter = create_stream_metrics().ok().map(|m| m.0).unwrap_or_else(|| {
         IntCounter::new("fallback_stream_reconnections", "Fallback metric").unwrap()
     });
     pub static ref STREAM_CONNECTED: IntGauge = create_stream_metrics().ok().map(|m| m.1).unwrap_or_else(|| {
         IntGauge::new("fallback_stream_connected", "Fallback metric").unwrap()
     });
+    pub static ref PING_FAILURES: IntCounter = create_stream_metrics().ok().map(|m| m.2).unwrap_or_else(|| {
+        IntCounter::new("fallback_ping_failures", "Fallback metric").unwrap()
+    });
+    pub static ref FATAL_SUBSCRIPTION_ERRORS: IntCounter = create_stream_metrics().ok().map(|m| m.3).unwrap_or_else(|| {
+        IntCounter::new("fallback_fatal_subscription_errors", "Fallback metric").unwrap()
+    });
+    pub static ref STREAM_PROCESSING_BACKLOG: IntGauge = create_stream_metrics().ok().map(|m| m.4).unwrap_or_else(|| {
+        IntGauge::new("fallback_stream_processing_backlog", "Fallback metric").unwrap()
+    });
     pub static ref TRANSACTION_PROCESSING_TIME: Histogram = create_timing_metrics().ok().map(|m| m.0).unwrap_or_else(|| {
         Histogram::with_opts(HistogramOpts::new("fallback_processing_time", "Fallback metric")).unwrap()
     });
     pub static ref DATABASE_OPERATION_TIME: Histogram = create_timing_metrics().ok().map(|m| m.1).unwrap_or_else(|| {
         Histogram::with_opts(HistogramOpts::new("fallback_db_time", "Fallback metric")).unwrap()
     });
+    pub static ref SLOT_PROCESSING_GAP: Histogram = create_slot_gap_metrics().ok().unwrap_or_else(|| {
+        Histogram::with_opts(HistogramOpts::new("fallback_slot_processing_gap", "Fallback metric")).unwrap()
+    });
+    pub static ref BALANCE_CHANGES_PER_TX: Histogram = create_fanout_metrics().ok().unwrap_or_else(|| {
+        Histogram::with_opts(HistogramOpts::new("fallback_balance_changes_per_tx", "Fallback metric")).unwrap()
+    });
+    pub static ref QUEUE_WAIT_SECONDS: Histogram = create_queue_metrics().ok().map(|m| m.0).unwrap_or_else(|| {
+        Histogram::with_opts(HistogramOpts::new("fallback_queue_wait", "Fallback metric")).unwrap()
+    });
+    pub static ref QUEUE_OLDEST_PENDING_SECONDS: Gauge = create_queue_metrics().ok().map(|m| m.1).unwrap_or_else(|| {
+        Gauge::new("fallback_queue_oldest_pending", "Fallback metric").unwrap()
+    });
+    pub static ref TRANSACTION_FEE_LAMPORTS: Histogram = create_fee_metrics().ok().unwrap_or_else(|| {
+        Histogram::with_opts(HistogramOpts::new("fallback_transaction_fee", "Fallback metric")).unwrap()
+    });
     pub static ref APP_UPTIME: Gauge = create_health_metrics().ok().map(|m| m.0).unwrap_or_else(|| {
         Gauge::new("fallback_uptime", "Fallback metric").unwrap()
     });
@@ -119,11 +525,76 @@ lazy_static! {
     pub static ref DATABASE_CONNECTIONS_ACTIVE: IntGauge = create_health_metrics().ok().map(|m| m.2).unwrap_or_else(|| {
         IntGauge::new("fallback_db_connections", "Fallback metric").unwrap()
     });
+    pub static ref DB_TRANSACTIONS_SUCCESS: IntGauge = create_db_stats_metrics().ok().map(|m| m.0).unwrap_or_else(|| {
+        IntGauge::new("fallback_db_transactions_success", "Fallback metric").unwrap()
+    });
+    pub static ref DB_TRANSACTIONS_FAILED: IntGauge = create_db_stats_metrics().ok().map(|m| m.1).unwrap_or_else(|| {
+        IntGauge::new("fallback_db_transactions_failed", "Fallback metric").unwrap()
+    });
+    pub static ref BUILD_INFO: IntGaugeVec = create_build_info_metric().ok().unwrap_or_else(|| {
+        IntGaugeVec::new(Opts::new("fallback_build_info", "Fallback metric"), &["version", "git_sha", "target_account", "commitment"]).unwrap()
+    });
     pub static ref ERRORS_TOTAL: Counter = create_error_metrics().ok().unwrap_or_else(|| {
         Counter::with_opts(Opts::new("fallback_errors", "Fallback metric")).unwrap()
     });
 }
 
+/// Serializes tests that assert on the process-wide metrics.
+#[cfg(test)]
+static TEST_METRICS: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// Reset every counter to zero and hold the metrics for one test.
+/// 
+/// Metrics are global, so a test asserting on them keeps the returned guard until it
+/// is done; other tests calling this wait for it.
+#[cfg(test)]
+pub async fn reset_metrics() -> tokio::sync::MutexGuard<'static, ()> {
+    let guard = TEST_METRICS.lock().await;
+
+    let counters = [
+        &*TRANSACTIONS_PROCESSED,
+        &*TRANSACTIONS_FAILED,
+        &*BALANCE_CHANGES_RECORDED,
+        &*TRUNCATED_BALANCE_CHANGES,
+        &*BALANCE_CHANGE_INSERT_FAILURES,
+        &*MISSED_TRANSACTIONS,
+        &*PROCESSING_TIMEOUTS,
+        &*FILTERED_BY_FEE,
+        &*FILTERED_BY_PROGRAM,
+        &*SAMPLED_OUT,
+        &*FILTERED_ZERO_BALANCE_CHANGES,
+        &*FILTERED_NON_TARGET,
+        &*SIGNATURE_MISMATCHES,
+        &*BLOCKS_RECORDED,
+        &*TRANSACTIONS_FINALIZED,
+        &*UNFINALIZED_DROPPED,
+        &*CONFIRMED_FALLBACK_FETCHES,
+        &*DROPPED_ON_SHUTDOWN,
+        &*DUPLICATE_UPDATES,
+        &*KAFKA_PUBLISHED,
+        &*FEED_LAGGED_EVENTS,
+        &*STREAM_RECONNECTIONS,
+        &*PING_FAILURES,
+        &*FATAL_SUBSCRIPTION_ERRORS,
+    ];
+    for counter in counters {
+        counter.reset();
+    }
+    for counter_vec in [&*ACCOUNT_TRANSACTIONS_PROCESSED, &*INCOMPLETE_UPDATES, &*KAFKA_DROPPED] {
+        counter_vec.reset();
+    }
+
+    guard
+}
+
+/// Override the bucket layout of the processing-time and database-time histograms.
+/// 
+/// Must be called before `init_metrics`, since the histograms are created on first
+/// use; `None` keeps the built-in buckets. Later calls are ignored.
+pub fn set_timing_buckets(processing_time: Option<Vec<f64>>, db_time: Option<Vec<f64>>) {
+    let _ = TIMING_BUCKETS.set((processing_time, db_time));
+}
+
 /// Initialize the metrics registry with all metrics.
 /// 
 /// Returns an error if any metric fails to register with the Prometheus registry.
@@ -139,18 +610,120 @@ pub fn init_metrics() -> Result<(), AppError> {
     REGISTRY.register(Box::new(BALANCE_CHANGES_RECORDED.clone()))
         .map_err(|e| AppError::Config(format!("Failed to register balance_changes: {}", e)))?;
     
+    REGISTRY.register(Box::new(TRUNCATED_BALANCE_CHANGES.clone()))
+        .map_err(|e| AppError::Config(format!("Failed to register truncated_balance_changes: {}", e)))?;
+    
+    REGISTRY.register(Box::new(ACCOUNT_TRANSACTIONS_PROCESSED.clone()))
+        .map_err(|e| AppError::Config(format!("Failed to register account_transactions_processed: {}", e)))?;
+    
+    REGISTRY.register(Box::new(BALANCE_CHANGE_INSERT_FAILURES.clone()))
+        .map_err(|e| AppError::Config(format!("Failed to register balance_change_insert_failures: {}", e)))?;
+    
+    REGISTRY.register(Box::new(MISSED_TRANSACTIONS.clone()))
+        .map_err(|e| AppError::Config(format!("Failed to register missed_transactions: {}", e)))?;
+    
+    REGISTRY.register(Box::new(PROCESSING_TIMEOUTS.clone()))
+        .map_err(|e| AppError::Config(format!("Failed to register processing_timeouts: {}", e)))?;
+    
+    REGISTRY.register(Box::new(FILTERED_BY_FEE.clone()))
+        .map_err(|e| AppError::Config(format!("Failed to register filtered_by_fee: {}", e)))?;
+    
+    REGISTRY.register(Box::new(FILTERED_BY_PROGRAM.clone()))
+        .map_err(|e| AppError::Config(format!("Failed to register filtered_by_program: {}", e)))?;
+    
+    REGISTRY.register(Box::new(SAMPLED_OUT.clone()))
+        .map_err(|e| AppError::Config(format!("Failed to register sampled_out: {}", e)))?;
+    
+    REGISTRY.register(Box::new(FILTERED_ZERO_BALANCE_CHANGES.clone()))
+        .map_err(|e| AppError::Config(format!("Failed to register filtered_zero_balance_changes: {}", e)))?;
+    
+    REGISTRY.register(Box::new(FILTERED_NON_TARGET.clone()))
+        .map_err(|e| AppError::Config(format!("Failed to register filtered_non_target: {}", e)))?;
+    
+    REGISTRY.register(Box::new(INCOMPLETE_UPDATES.clone()))
+        .map_err(|e| AppError::Config(format!("Failed to register incomplete_updates: {}", e)))?;
+    
+    REGISTRY.register(Box::new(SIGNATURE_MISMATCHES.clone()))
+        .map_err(|e| AppError::Config(format!("Failed to register signature_mismatch: {}", e)))?;
+    
+    REGISTRY.register(Box::new(BLOCKS_RECORDED.clone()))
+        .map_err(|e| AppError::Config(format!("Failed to register blocks_recorded: {}", e)))?;
+    
+    REGISTRY.register(Box::new(TRANSACTIONS_FINALIZED.clone()))
+        .map_err(|e| AppError::Config(format!("Failed to register transactions_finalized: {}", e)))?;
+    
+    REGISTRY.register(Box::new(PENDING_FINALIZATION.clone()))
+        .map_err(|e| AppError::Config(format!("Failed to register pending_finalization: {}", e)))?;
+    
+    REGISTRY.register(Box::new(UNFINALIZED_DROPPED.clone()))
+        .map_err(|e| AppError::Config(format!("Failed to register unfinalized_dropped: {}", e)))?;
+    
+    REGISTRY.register(Box::new(CONFIRMED_FALLBACK_FETCHES.clone()))
+        .map_err(|e| AppError::Config(format!("Failed to register confirmed_fallback_fetches: {}", e)))?;
+    
+    REGISTRY.register(Box::new(DROPPED_ON_SHUTDOWN.clone()))
+        .map_err(|e| AppError::Config(format!("Failed to register dropped_on_shutdown: {}", e)))?;
+    
+    REGISTRY.register(Box::new(DISTINCT_ACCOUNTS.clone()))
+        .map_err(|e| AppError::Config(format!("Failed to register distinct_accounts: {}", e)))?;
+    
+    REGISTRY.register(Box::new(ENDPOINT_CONNECTED.clone()))
+        .map_err(|e| AppError::Config(format!("Failed to register endpoint_connected: {}", e)))?;
+    
+    REGISTRY.register(Box::new(DUPLICATE_UPDATES.clone()))
+        .map_err(|e| AppError::Config(format!("Failed to register duplicate_updates: {}", e)))?;
+    
+    REGISTRY.register(Box::new(FEED_LAGGED_EVENTS.clone()))
+        .map_err(|e| AppError::Config(format!("Failed to register feed_lagged_events: {}", e)))?;
+    
+    REGISTRY.register(Box::new(KAFKA_PUBLISHED.clone()))
+        .map_err(|e| AppError::Config(format!("Failed to register kafka_published: {}", e)))?;
+    
+    REGISTRY.register(Box::new(KAFKA_DROPPED.clone()))
+        .map_err(|e| AppError::Config(format!("Failed to register kafka_dropped: {}", e)))?;
+    
+    REGISTRY.register(Box::new(RPC_CONCURRENCY_LIMIT.clone()))
+        .map_err(|e| AppError::Config(format!("Failed to register rpc_concurrency_limit: {}", e)))?;
+    
+    REGISTRY.register(Box::new(MISSING_SIGNATURES.clone()))
+        .map_err(|e| AppError::Config(format!("Failed to register missing_signatures: {}", e)))?;
+    
     REGISTRY.register(Box::new(STREAM_RECONNECTIONS.clone()))
         .map_err(|e| AppError::Config(format!("Failed to register stream_reconnections: {}", e)))?;
     
     REGISTRY.register(Box::new(STREAM_CONNECTED.clone()))
         .map_err(|e| AppError::Config(format!("Failed to register stream_connected: {}", e)))?;
     
+    REGISTRY.register(Box::new(PING_FAILURES.clone()))
+        .map_err(|e| AppError::Config(format!("Failed to register ping_failures: {}", e)))?;
+    
+    REGISTRY.register(Box::new(FATAL_SUBSCRIPTION_ERRORS.clone()))
+        .map_err(|e| AppError::Config(format!("Failed to register fatal_subscription_errors: {}", e)))?;
+    
+    REGISTRY.register(Box::new(STREAM_PROCESSING_BACKLOG.clone()))
+        .map_err(|e| AppError::Config(format!("Failed to register stream_processing_backlog: {}", e)))?;
+    
     REGISTRY.register(Box::new(TRANSACTION_PROCESSING_TIME.clone()))
         .map_err(|e| AppError::Config(format!("Failed to register transaction_processing_time: {}", e)))?;
     
     REGISTRY.register(Box::new(DATABASE_OPERATION_TIME.clone()))
         .map_err(|e| AppError::Config(format!("Failed to register database_operation_time: {}", e)))?;
     
+    REGISTRY.register(Box::new(SLOT_PROCESSING_GAP.clone()))
+        .map_err(|e| AppError::Config(format!("Failed to register slot_processing_gap: {}", e)))?;
+    
+    REGISTRY.register(Box::new(BALANCE_CHANGES_PER_TX.clone()))
+        .map_err(|e| AppError::Config(format!("Failed to register balance_changes_per_tx: {}", e)))?;
+    
+    REGISTRY.register(Box::new(QUEUE_WAIT_SECONDS.clone()))
+        .map_err(|e| AppError::Config(format!("Failed to register queue_wait: {}", e)))?;
+    
+    REGISTRY.register(Box::new(QUEUE_OLDEST_PENDING_SECONDS.clone()))
+        .map_err(|e| AppError::Config(format!("Failed to register queue_oldest_pending: {}", e)))?;
+    
+    REGISTRY.register(Box::new(TRANSACTION_FEE_LAMPORTS.clone()))
+        .map_err(|e| AppError::Config(format!("Failed to register transaction_fee: {}", e)))?;
+    
     REGISTRY.register(Box::new(APP_UPTIME.clone()))
         .map_err(|e| AppError::Config(format!("Failed to register app_uptime: {}", e)))?;
     
@@ -160,12 +733,114 @@ pub fn init_metrics() -> Result<(), AppError> {
     REGISTRY.register(Box::new(DATABASE_CONNECTIONS_ACTIVE.clone()))
         .map_err(|e| AppError::Config(format!("Failed to register database_connections: {}", e)))?;
     
+    REGISTRY.register(Box::new(DB_TRANSACTIONS_SUCCESS.clone()))
+        .map_err(|e| AppError::Config(format!("Failed to register db_transactions_success: {}", e)))?;
+    
+    REGISTRY.register(Box::new(DB_TRANSACTIONS_FAILED.clone()))
+        .map_err(|e| AppError::Config(format!("Failed to register db_transactions_failed: {}", e)))?;
+    
     REGISTRY.register(Box::new(ERRORS_TOTAL.clone()))
         .map_err(|e| AppError::Config(format!("Failed to register errors_total: {}", e)))?;
+    
+    REGISTRY.register(Box::new(BUILD_INFO.clone()))
+        .map_err(|e| AppError::Config(format!("Failed to register build_info: {}", e)))?;
 
     Ok(())
 }
 
+/// Count a processed transaction under each monitored account it involves.
+/// 
+/// Labels come only from the configured account set, which bounds the series count;
+/// a transaction involving several monitored accounts counts once for each.
+pub fn record_processed_for_accounts(accounts: &[String]) {
+    for account in accounts {
+        ACCOUNT_TRANSACTIONS_PROCESSED.with_label_values(&[account]).inc();
+    }
+}
+
+/// Publish the build_info series for this process.
+/// 
+/// Follows the standard `*_build_info` pattern: a single series set to 1 whose
+/// labels carry the crate version, git commit, (truncated) target account, and
+/// stream commitment, so dashboards can join other series against them. When
+/// several accounts are monitored the label reads e.g. `3 accounts`.
+pub fn set_build_info(target_accounts: &[String], commitment: &str) {
+    let target_account = match target_accounts {
+        [account] => truncate_pubkey(account),
+        accounts => format!("{} accounts", accounts.len()),
+    };
+
+    BUILD_INFO
+        .with_label_values(&[
+            env!("CARGO_PKG_VERSION"),
+            env!("GIT_SHA"),
+            &target_account,
+            commitment,
+        ])
+        .set(1);
+}
+
+/// Remember the accounts of a persisted transaction's balance changes.
+/// 
+/// Updates `solana_tracker_distinct_accounts`. Accounts are kept in a set bounded by
+/// `MAX_TRACKED_ACCOUNTS`, so once it fills new accounts are no longer counted.
+pub fn record_accounts_seen<'a>(accounts: impl IntoIterator<Item = &'a str>) {
+    let mut seen = SEEN_ACCOUNTS
+        .get_or_init(|| Mutex::new(HashSet::new()))
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+
+    for account in accounts {
+        if seen.len() >= MAX_TRACKED_ACCOUNTS {
+            break;
+        }
+        if !seen.contains(account) {
+            seen.insert(account.to_string());
+        }
+    }
+
+    DISTINCT_ACCOUNTS.set(seen.len() as i64);
+}
+
+/// A stream transaction update waiting for a fetch slot.
+/// 
+/// While alive it counts toward `solana_tracker_queue_oldest_pending_seconds`;
+/// `dequeue` records the total wait in `solana_tracker_queue_wait_seconds`.
+pub struct QueuedUpdate {
+    received_at: Instant,
+}
+
+impl QueuedUpdate {
+    /// Start tracking an update received now.
+    pub fn enqueue() -> Self {
+        let received_at = Instant::now();
+        pending_updates().push(received_at);
+
+        Self { received_at }
+    }
+
+    /// Stop tracking the update once it holds a fetch slot, observing how long it waited.
+    pub fn dequeue(self) {
+        QUEUE_WAIT_SECONDS.observe(self.received_at.elapsed().as_secs_f64());
+    }
+}
+
+impl Drop for QueuedUpdate {
+    fn drop(&mut self) {
+        let mut pending = pending_updates();
+        if let Some(index) = pending.iter().position(|at| *at == self.received_at) {
+            pending.swap_remove(index);
+        }
+    }
+}
+
+fn pending_updates() -> MutexGuard<'static, Vec<Instant>> {
+    PENDING_UPDATES
+        .get_or_init(|| Mutex::new(Vec::new()))
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
 /// Get the metrics in Prometheus exposition format.
 /// 
 /// Returns a Result containing the metrics text or an error if encoding fails.
@@ -173,7 +848,8 @@ pub fn init_metrics() -> Result<(), AppError> {
 pub fn gather_metrics() -> Result<String, AppError> {
     use prometheus::Encoder;
     let encoder = prometheus::TextEncoder::new();
-    let metric_families = REGISTRY.gather();
+
+    let metric_families = gather_families();
     let mut buffer = vec![];
     
     encoder.encode(&metric_families, &mut buffer)
@@ -182,3 +858,246 @@ pub fn gather_metrics() -> Result<String, AppError> {
     String::from_utf8(buffer)
         .map_err(|e| AppError::Config(format!("Failed to convert metrics to UTF-8: {}", e)))
 }
+
+/// Gather the registry's metric families.
+fn gather_families() -> Vec<MetricFamily> {
+    // The oldest update keeps aging between scrapes, so its age is taken at scrape time
+    let oldest_pending = pending_updates()
+        .iter()
+        .min()
+        .map(|received_at| received_at.elapsed().as_secs_f64())
+        .unwrap_or(0.0);
+    QUEUE_OLDEST_PENDING_SECONDS.set(oldest_pending);
+
+    REGISTRY.gather()
+}
+
+/// Periodically push the gathered registry to an OTLP/HTTP metrics endpoint.
+/// 
+/// Each push POSTs every family as OTLP JSON to `url`, typically a collector's
+/// `/v1/metrics` (for example `http://otel-collector:4318/v1/metrics`), for
+/// environments where the indexer can't be scraped. A failed push is logged and
+/// retried at the next interval; it never stops the indexer.
+pub async fn push_metrics(url: String, interval: Duration) {
+    let client = reqwest::Client::new();
+    let mut ticker = tokio::time::interval(interval);
+    // Counters and histograms are cumulative from when pushing started
+    let started_at = SystemTime::now();
+
+    loop {
+        ticker.tick().await;
+
+        let body = encode_otlp(&gather_families(), started_at, SystemTime::now());
+
+        let result = client
+            .post(&url)
+            .timeout(interval)
+            .json(&body)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+
+        match result {
+            Ok(_) => debug!(url = %url, "Pushed metrics"),
+            Err(e) => warn!(url = %url, error = %e, "Failed to push metrics"),
+        }
+    }
+}
+
+/// Encode metric families as an OTLP `ExportMetricsServiceRequest` in its JSON form.
+/// 
+/// Counters become monotonic cumulative sums, gauges and untyped metrics gauges, and
+/// histograms and summaries their OTLP counterparts. Prometheus histogram buckets are
+/// cumulative while OTLP's are not, so each bucket is reported less the one below it,
+/// with the count above the largest bound as the final bucket.
+fn encode_otlp(families: &[MetricFamily], started_at: SystemTime, now: SystemTime) -> serde_json::Value {
+    use serde_json::json;
+
+    // 64-bit integers are strings in OTLP JSON
+    let unix_nanos = |time: SystemTime| {
+        time.duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos().to_string()
+    };
+    let start_time = unix_nanos(started_at);
+    let time = unix_nanos(now);
+
+    let metrics: Vec<_> = families
+        .iter()
+        .map(|family| {
+            let data_points = family.get_metric().iter().map(|metric| {
+                let attributes: Vec<_> = metric
+                    .get_label()
+                    .iter()
+                    .map(|label| json!({ "key": label.get_name(), "value": { "stringValue": label.get_value() } }))
+                    .collect();
+
+                match family.get_field_type() {
+                    MetricType::COUNTER => json!({
+                        "attributes": attributes,
+                        "startTimeUnixNano": start_time,
+                        "timeUnixNano": time,
+                        "asDouble": metric.get_counter().get_value(),
+                    }),
+                    MetricType::GAUGE => json!({
+                        "attributes": attributes,
+                        "timeUnixNano": time,
+                        "asDouble": metric.get_gauge().get_value(),
+                    }),
+                    MetricType::UNTYPED => json!({
+                        "attributes": attributes,
+                        "timeUnixNano": time,
+                        "asDouble": metric.get_untyped().get_value(),
+                    }),
+                    MetricType::HISTOGRAM => {
+                        let histogram = metric.get_histogram();
+                        let mut below = 0;
+                        let mut bucket_counts = Vec::new();
+                        let mut explicit_bounds = Vec::new();
+                        for bucket in histogram.get_bucket() {
+                            bucket_counts.push((bucket.get_cumulative_count() - below).to_string());
+                            explicit_bounds.push(bucket.get_upper_bound());
+                            below = bucket.get_cumulative_count();
+                        }
+                        bucket_counts.push(histogram.get_sample_count().saturating_sub(below).to_string());
+
+                        json!({
+                            "attributes": attributes,
+                            "startTimeUnixNano": start_time,
+                            "timeUnixNano": time,
+                            "count": histogram.get_sample_count().to_string(),
+                            "sum": histogram.get_sample_sum(),
+                            "bucketCounts": bucket_counts,
+                            "explicitBounds": explicit_bounds,
+                        })
+                    }
+                    MetricType::SUMMARY => {
+                        let summary = metric.get_summary();
+                        let quantiles: Vec<_> = summary
+                            .get_quantile()
+                            .iter()
+                            .map(|q| json!({ "quantile": q.get_quantile(), "value": q.get_value() }))
+                            .collect();
+
+                        json!({
+                            "attributes": attributes,
+                            "startTimeUnixNano": start_time,
+                            "timeUnixNano": time,
+                            "count": summary.get_sample_count().to_string(),
+                            "sum": summary.get_sample_sum(),
+                            "quantileValues": quantiles,
+                        })
+                    }
+                }
+            });
+            let data_points: Vec<_> = data_points.collect();
+
+            let mut metric = json!({ "name": family.get_name(), "description": family.get_help() });
+            let (kind, data) = match family.get_field_type() {
+                // 2 is AGGREGATION_TEMPORALITY_CUMULATIVE
+                MetricType::COUNTER => ("sum", json!({ "dataPoints": data_points, "aggregationTemporality": 2, "isMonotonic": true })),
+                MetricType::GAUGE | MetricType::UNTYPED => ("gauge", json!({ "dataPoints": data_points })),
+                MetricType::HISTOGRAM => ("histogram", json!({ "dataPoints": data_points, "aggregationTemporality": 2 })),
+                MetricType::SUMMARY => ("summary", json!({ "dataPoints": data_points })),
+            };
+            metric[kind] = data;
+            metric
+        })
+        .collect();
+
+    json!({
+        "resourceMetrics": [{
+            "resource": {
+                "attributes": [{ "key": "service.name", "value": { "stringValue": env!("CARGO_PKG_NAME") } }]
+            },
+            "scopeMetrics": [{
+                "scope": { "name": env!("CARGO_PKG_NAME"), "version": env!("CARGO_PKG_VERSION") },
+                "metrics": metrics,
+            }],
+        }]
+    })
+}
+
+/// JSON-friendly snapshot of the main counters and gauges.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MetricsSnapshot {
+    pub transactions_processed: u64,
+    pub transactions_failed: u64,
+    pub stream_reconnections: u64,
+    pub stream_connected: bool,
+    pub uptime_seconds: f64,
+    pub last_transaction_timestamp: f64,
+}
+
+/// Build a snapshot of the main metrics from the registry's gathered families.
+/// 
+/// This backs the `/metrics/custom` endpoint for tooling that can't parse the
+/// Prometheus text format. Metrics missing from the registry read as zero.
+pub fn snapshot() -> MetricsSnapshot {
+    let mut snapshot = MetricsSnapshot::default();
+
+    for family in REGISTRY.gather() {
+        let Some(metric) = family.get_metric().first() else {
+            continue;
+        };
+
+        match family.get_name() {
+            "solana_tracker_transactions_processed_total" => {
+                snapshot.transactions_processed = metric.get_counter().get_value() as u64;
+            }
+            "solana_tracker_transactions_failed_total" => {
+                snapshot.transactions_failed = metric.get_counter().get_value() as u64;
+            }
+            "solana_tracker_stream_reconnections_total" => {
+                snapshot.stream_reconnections = metric.get_counter().get_value() as u64;
+            }
+            "solana_tracker_stream_connected" => {
+                snapshot.stream_connected = metric.get_gauge().get_value() > 0.0;
+            }
+            "solana_tracker_uptime_seconds" => {
+                snapshot.uptime_seconds = metric.get_gauge().get_value();
+            }
+            "solana_tracker_last_transaction_timestamp" => {
+                snapshot.last_transaction_timestamp = metric.get_gauge().get_value();
+            }
+            _ => {}
+        }
+    }
+
+    snapshot
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_families_as_otlp_json() {
+        let registry = Registry::new();
+        let counter = IntCounterVec::new(Opts::new("pushed_total", "Pushed"), &["endpoint"]).unwrap();
+        let histogram = Histogram::with_opts(HistogramOpts::new("push_seconds", "Push time").buckets(vec![1.0, 2.0])).unwrap();
+        registry.register(Box::new(counter.clone())).unwrap();
+        registry.register(Box::new(histogram.clone())).unwrap();
+        counter.with_label_values(&["a"]).inc_by(3);
+        for value in [0.5, 1.5, 3.0] {
+            histogram.observe(value);
+        }
+
+        let started_at = UNIX_EPOCH + Duration::from_secs(10);
+        let body = encode_otlp(&registry.gather(), started_at, started_at + Duration::from_secs(5));
+        // Gathered families are sorted by name
+        let metrics = &body["resourceMetrics"][0]["scopeMetrics"][0]["metrics"];
+
+        let sum = &metrics[1]["sum"];
+        assert_eq!(metrics[1]["name"], "pushed_total");
+        assert_eq!(sum["isMonotonic"], true);
+        assert_eq!(sum["dataPoints"][0]["asDouble"], 3.0);
+        assert_eq!(sum["dataPoints"][0]["attributes"][0]["value"]["stringValue"], "a");
+        assert_eq!(sum["dataPoints"][0]["startTimeUnixNano"], "10000000000");
+        assert_eq!(sum["dataPoints"][0]["timeUnixNano"], "15000000000");
+
+        // One observation per bucket, the last above the largest bound
+        let point = &metrics[0]["histogram"]["dataPoints"][0];
+        assert_eq!(point["count"], "3");
+        assert_eq!(point["bucketCounts"], serde_json::json!(["1", "1", "1"]));
+        assert_eq!(point["explicitBounds"], serde_json::json!([1.0, 2.0]));
+    }
+}
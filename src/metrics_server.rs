@@ -1,16 +1,35 @@
+use crate::database::export::{self, CSV_HEADER};
+use crate::database::feed::TransactionFeed;
+use crate::database::stats::SuccessStatsCache;
+use crate::database::store::TransactionStore;
 use crate::error::AppError;
 use crate::metrics;
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
 use std::net::SocketAddr;
-use tokio::net::TcpListener;
+use std::sync::Arc;
+use tokio::net::{TcpListener, TcpStream};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tracing::{error, info};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, error, info};
+
+/// Buffered CSV bytes written as one chunk of an export response.
+const EXPORT_CHUNK_BYTES: usize = 16 * 1024;
+
+/// Largest request head (request line plus headers) read before routing.
+const MAX_REQUEST_HEAD_BYTES: usize = 16 * 1024;
 
 /// Start the metrics HTTP server with automatic port fallback.
 /// 
 /// Attempts to bind to the requested port first. If that port is already in use,
 /// automatically tries alternate ports (up to 10 attempts) to ensure the metrics
 /// server can start even if the default port is occupied by another process.
-pub async fn start_metrics_server(port: u16) -> Result<(), AppError> {
+pub async fn start_metrics_server<S: TransactionStore>(
+    port: u16,
+    stats: Arc<SuccessStatsCache<S>>,
+    repository: Arc<S>,
+    feed: Arc<TransactionFeed>,
+) -> Result<(), AppError> {
     const MAX_PORT_ATTEMPTS: u16 = 10;
     
     let mut last_error = None;
@@ -33,7 +52,7 @@ pub async fn start_metrics_server(port: u16) -> Result<(), AppError> {
                 }
                 
                 // Successfully bound, start serving
-                return serve_metrics(listener).await;
+                return serve_metrics(listener, stats, repository, feed).await;
             }
             Err(e) => {
                 last_error = Some((try_port, e));
@@ -55,23 +74,55 @@ pub async fn start_metrics_server(port: u16) -> Result<(), AppError> {
 }
 
 /// Serve metrics on the bound listener.
-async fn serve_metrics(listener: TcpListener) -> Result<(), AppError> {
+async fn serve_metrics<S: TransactionStore>(
+    listener: TcpListener,
+    stats: Arc<SuccessStatsCache<S>>,
+    repository: Arc<S>,
+    feed: Arc<TransactionFeed>,
+) -> Result<(), AppError> {
     loop {
         match listener.accept().await {
             Ok((mut socket, _)) => {
+                let stats = stats.clone();
+                let repository = repository.clone();
+                let feed = feed.clone();
                 tokio::spawn(async move {
-                    let mut buffer = [0; 1024];
-                    
                     // Read the request
-                    if let Err(e) = socket.read(&mut buffer).await {
-                        error!("Failed to read from socket: {}", e);
-                        return;
-                    }
+                    let buffer = match read_request_head(&mut socket).await {
+                        Ok(buffer) => buffer,
+                        Err(e) => {
+                            error!("Failed to read from socket: {}", e);
+                            return;
+                        }
+                    };
 
                     // Parse the request to check if it's for /metrics
                     let request = String::from_utf8_lossy(&buffer);
                     
-                    if request.starts_with("GET /metrics") {
+                    // Checked before /metrics, which would otherwise match as a prefix
+                    if request.starts_with("GET /metrics/custom") {
+                        // JSON snapshot of the main metrics for tooling without a Prometheus parser
+                        let response = match serde_json::to_string(&metrics::snapshot()) {
+                            Ok(body) => format!(
+                                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                                body.len(),
+                                body
+                            ),
+                            Err(e) => {
+                                error!("Failed to serialize metrics snapshot: {}", e);
+                                "HTTP/1.1 500 Internal Server Error\r\nContent-Length: 20\r\n\r\nMetrics unavailable\n".to_string()
+                            }
+                        };
+
+                        if let Err(e) = socket.write_all(response.as_bytes()).await {
+                            error!("Failed to write to socket: {}", e);
+                        }
+                    } else if request.starts_with("GET /metrics") {
+                        // Refresh the persisted-state gauges (cached, so scrapes don't hit the DB every time)
+                        if let Err(e) = stats.get().await {
+                            error!("Failed to refresh transaction stats: {}", e);
+                        }
+
                         // Gather metrics
                         match metrics::gather_metrics() {
                             Ok(metrics_output) => {
@@ -95,6 +146,123 @@ async fn serve_metrics(listener: TcpListener) -> Result<(), AppError> {
                                 }
                             }
                         }
+                    } else if request.starts_with("GET /transactions/stats") {
+                        // Success/failure counts of persisted transactions
+                        let response = match stats.get().await.and_then(|stats| {
+                            serde_json::to_string(&stats)
+                                .map_err(|e| AppError::Config(format!("Failed to serialize stats: {}", e)))
+                        }) {
+                            Ok(body) => format!(
+                                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                                body.len(),
+                                body
+                            ),
+                            Err(e) => {
+                                error!("Failed to load transaction stats: {}", e);
+                                "HTTP/1.1 500 Internal Server Error\r\nContent-Length: 18\r\n\r\nStats unavailable\n".to_string()
+                            }
+                        };
+
+                        if let Err(e) = socket.write_all(response.as_bytes()).await {
+                            error!("Failed to write to socket: {}", e);
+                        }
+                    } else if request.starts_with("GET /transactions/") {
+                        // Slot leader of one stored transaction
+                        let target = request.split_whitespace().nth(1).unwrap_or_default();
+                        let signature = target
+                            .strip_prefix("/transactions/")
+                            .and_then(|rest| rest.strip_suffix("/leader"))
+                            .unwrap_or_default();
+
+                        let response = match repository.transaction_leader(signature).await {
+                            Ok(Some((slot, leader))) => {
+                                let body = serde_json::json!({
+                                    "signature": signature,
+                                    "slot": slot,
+                                    "leader": leader,
+                                })
+                                .to_string();
+                                format!(
+                                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                                    body.len(),
+                                    body
+                                )
+                            }
+                            Ok(None) => "HTTP/1.1 404 Not Found\r\nContent-Length: 9\r\n\r\nNot Found".to_string(),
+                            Err(e) => {
+                                error!("Failed to look up transaction leader: {}", e);
+                                "HTTP/1.1 500 Internal Server Error\r\nContent-Length: 19\r\n\r\nLeader unavailable\n".to_string()
+                            }
+                        };
+
+                        if let Err(e) = socket.write_all(response.as_bytes()).await {
+                            error!("Failed to write to socket: {}", e);
+                        }
+                    } else if request.starts_with("GET /export") {
+                        // CSV export of stored transactions, streamed page by page
+                        let target = request.split_whitespace().nth(1).unwrap_or_default();
+                        match parse_export_query(target) {
+                            Ok(since) => {
+                                if let Err(e) = stream_export(&mut socket, repository, since).await {
+                                    error!("Transaction export failed: {}", e);
+                                }
+                            }
+                            Err(message) => {
+                                let response = format!(
+                                    "HTTP/1.1 400 Bad Request\r\nContent-Type: text/plain\r\nContent-Length: {}\r\n\r\n{}",
+                                    message.len(),
+                                    message
+                                );
+
+                                if let Err(e) = socket.write_all(response.as_bytes()).await {
+                                    error!("Failed to write to socket: {}", e);
+                                }
+                            }
+                        }
+                    } else if request.starts_with("GET /accounts/") {
+                        // Post-balance history of one account and mint
+                        let target = request.split_whitespace().nth(1).unwrap_or_default();
+                        let response = match parse_history_request(target) {
+                            Ok((account, mint, since)) => {
+                                match repository.balance_history(&account, mint.as_deref(), since).await {
+                                    Ok(history) => {
+                                        let points: Vec<_> = history
+                                            .into_iter()
+                                            .map(|(block_time, post_balance)| {
+                                                serde_json::json!({
+                                                    "block_time": block_time.to_rfc3339(),
+                                                    "post_balance": post_balance,
+                                                })
+                                            })
+                                            .collect();
+                                        let body = serde_json::Value::Array(points).to_string();
+                                        format!(
+                                            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                                            body.len(),
+                                            body
+                                        )
+                                    }
+                                    Err(e) => {
+                                        error!("Failed to load balance history: {}", e);
+                                        "HTTP/1.1 500 Internal Server Error\r\nContent-Length: 20\r\n\r\nHistory unavailable\n".to_string()
+                                    }
+                                }
+                            }
+                            Err(message) => format!(
+                                "HTTP/1.1 400 Bad Request\r\nContent-Type: text/plain\r\nContent-Length: {}\r\n\r\n{}",
+                                message.len(),
+                                message
+                            ),
+                        };
+
+                        if let Err(e) = socket.write_all(response.as_bytes()).await {
+                            error!("Failed to write to socket: {}", e);
+                        }
+                    } else if request.starts_with("GET /stream") {
+                        // Server-Sent Events feed of newly inserted transactions
+                        if let Err(e) = stream_feed(&mut socket, &feed).await {
+                            debug!("Transaction stream closed: {}", e);
+                        }
                     } else if request.starts_with("GET /health") {
                         // Health check endpoint
                         let response = "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: 2\r\n\r\nOK";
@@ -118,3 +286,268 @@ async fn serve_metrics(listener: TcpListener) -> Result<(), AppError> {
         }
     }
 }
+
+/// Read a request up to the end of its headers.
+/// 
+/// Requests can arrive split across several reads, so this keeps reading until the
+/// blank line ending the headers, the client closes, or `MAX_REQUEST_HEAD_BYTES` have
+/// been read. Only the request line is routed on, so an oversized head is truncated
+/// rather than rejected.
+async fn read_request_head(socket: &mut TcpStream) -> std::io::Result<Vec<u8>> {
+    let mut head = Vec::with_capacity(1024);
+    let mut chunk = [0; 1024];
+
+    while head.len() < MAX_REQUEST_HEAD_BYTES {
+        let read = socket.read(&mut chunk).await?;
+        if read == 0 {
+            break;
+        }
+
+        // Only the newly read bytes (plus 3 before them) can complete the terminator
+        let search_from = head.len().saturating_sub(3);
+        head.extend_from_slice(&chunk[..read]);
+        if head[search_from..].windows(4).any(|w| w == b"\r\n\r\n") {
+            break;
+        }
+    }
+
+    head.truncate(MAX_REQUEST_HEAD_BYTES);
+    Ok(head)
+}
+
+/// Parse the `since` and `format` parameters of an `/export` request target.
+/// 
+/// `since` is required, as RFC 3339 or Unix seconds; `format` defaults to `csv`,
+/// the only supported format. Returns the error message for a 400 response.
+fn parse_export_query(target: &str) -> Result<DateTime<Utc>, String> {
+    let query = target.split_once('?').map(|(_, query)| query).unwrap_or_default();
+
+    let mut since = None;
+    for (key, value) in query.split('&').filter_map(|pair| pair.split_once('=')) {
+        let value = percent_decode(value);
+        match key {
+            "since" => since = Some(value),
+            "format" if value != "csv" => {
+                return Err(format!("Unsupported export format '{}', expected csv\n", value));
+            }
+            _ => {}
+        }
+    }
+
+    let since = since.ok_or_else(|| "Missing required 'since' parameter\n".to_string())?;
+
+    parse_since(&since)
+}
+
+/// Parse an `/accounts/<pubkey>/history` request target into account, mint, and start time.
+/// 
+/// `mint` selects a token (omitted = native SOL) and `since` bounds the history as in
+/// `/export` (omitted = all stored history). Returns the error message for a 400 response.
+fn parse_history_request(target: &str) -> Result<(String, Option<String>, DateTime<Utc>), String> {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    let account = path
+        .strip_prefix("/accounts/")
+        .and_then(|rest| rest.strip_suffix("/history"))
+        .ok_or_else(|| "Expected /accounts/<pubkey>/history\n".to_string())?;
+    if !is_pubkey(account) {
+        return Err(format!("Invalid account pubkey '{}'\n", account));
+    }
+
+    let mut mint = None;
+    let mut since = DateTime::<Utc>::UNIX_EPOCH;
+    for (key, value) in query.split('&').filter_map(|pair| pair.split_once('=')) {
+        let value = percent_decode(value);
+        match key {
+            "mint" if !is_pubkey(&value) => {
+                return Err(format!("Invalid mint pubkey '{}'\n", value));
+            }
+            "mint" => mint = Some(value),
+            "since" => since = parse_since(&value)?,
+            _ => {}
+        }
+    }
+
+    Ok((account.to_string(), mint, since))
+}
+
+/// Check that a value is a base58-encoded 32-byte public key.
+fn is_pubkey(value: &str) -> bool {
+    bs58::decode(value).into_vec().is_ok_and(|bytes| bytes.len() == 32)
+}
+
+/// Parse a `since` query value, given as RFC 3339 or Unix seconds.
+fn parse_since(since: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(seconds) = since.parse::<i64>() {
+        return DateTime::from_timestamp(seconds, 0)
+            .ok_or_else(|| format!("Invalid 'since' timestamp '{}'\n", since));
+    }
+
+    DateTime::parse_from_rfc3339(since)
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|e| format!("Invalid 'since' value '{}': {}\n", since, e))
+}
+
+/// Decode `%XX` escapes and `+` in a query parameter value.
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+                match hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                    Some(byte) => {
+                        decoded.push(byte);
+                        i += 3;
+                        continue;
+                    }
+                    None => decoded.push(b'%'),
+                }
+            }
+            b'+' => decoded.push(b' '),
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Write stored transactions since `since` as a chunked CSV response.
+/// 
+/// Rows are pulled from the store a page at a time and flushed in chunks of about
+/// `EXPORT_CHUNK_BYTES`, so large exports are never buffered in memory. A failure
+/// after the headers are sent ends the response without the terminating chunk,
+/// letting the client detect the truncated export.
+async fn stream_export<S: TransactionStore>(
+    socket: &mut TcpStream,
+    repository: Arc<S>,
+    since: DateTime<Utc>,
+) -> Result<(), AppError> {
+    socket
+        .write_all(
+            b"HTTP/1.1 200 OK\r\nContent-Type: text/csv\r\n\
+              Content-Disposition: attachment; filename=\"transactions.csv\"\r\n\
+              Transfer-Encoding: chunked\r\n\r\n",
+        )
+        .await?;
+
+    let mut rows = std::pin::pin!(export::transaction_rows(repository, since));
+    let mut buffer = String::from(CSV_HEADER);
+
+    while let Some(row) = rows.try_next().await? {
+        buffer.push_str(&row.to_csv_record());
+
+        if buffer.len() >= EXPORT_CHUNK_BYTES {
+            write_chunk(socket, &buffer).await?;
+            buffer.clear();
+        }
+    }
+
+    if !buffer.is_empty() {
+        write_chunk(socket, &buffer).await?;
+    }
+
+    // Zero-length chunk marks the end of the body
+    socket.write_all(b"0\r\n\r\n").await?;
+
+    Ok(())
+}
+
+/// Forward newly inserted transactions to an SSE client until it disconnects.
+/// 
+/// Each transaction is sent as one `data:` event holding its JSON. A client too
+/// slow to keep up skips the events it missed, counted in
+/// `solana_tracker_feed_lagged_events_total`, rather than slowing ingestion.
+async fn stream_feed(socket: &mut TcpStream, feed: &TransactionFeed) -> Result<(), AppError> {
+    // Subscribe before answering so no transaction falls between the two
+    let mut events = feed.subscribe();
+
+    socket
+        .write_all(
+            b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\n\
+              Cache-Control: no-cache\r\nConnection: keep-alive\r\n\r\n",
+        )
+        .await?;
+
+    loop {
+        match events.recv().await {
+            Ok(json) => {
+                socket.write_all(format!("data: {}\n\n", json).as_bytes()).await?;
+            }
+            Err(RecvError::Lagged(skipped)) => {
+                metrics::FEED_LAGGED_EVENTS.inc_by(skipped);
+            }
+            Err(RecvError::Closed) => return Ok(()),
+        }
+    }
+}
+
+/// Write one chunk of a `Transfer-Encoding: chunked` body.
+async fn write_chunk(socket: &mut TcpStream, data: &str) -> Result<(), AppError> {
+    socket.write_all(format!("{:x}\r\n", data.len()).as_bytes()).await?;
+    socket.write_all(data.as_bytes()).await?;
+    socket.write_all(b"\r\n").await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Connect a client to a loopback listener, returning the client and server ends.
+    async fn socket_pair() -> (TcpStream, TcpStream) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).await.unwrap();
+        let (server, _) = listener.accept().await.unwrap();
+        (client, server)
+    }
+
+    #[tokio::test]
+    async fn reads_head_larger_than_one_read() {
+        let (mut client, mut server) = socket_pair().await;
+        let request = format!(
+            "GET /metrics HTTP/1.1\r\nHost: localhost\r\nCookie: {}\r\n\r\n",
+            "a".repeat(3000)
+        );
+
+        // Split so the terminator itself straddles two writes
+        let (first, second) = request.as_bytes().split_at(request.len() - 2);
+        let (first, second) = (first.to_vec(), second.to_vec());
+        let writer = tokio::spawn(async move {
+            client.write_all(&first).await.unwrap();
+            client.flush().await.unwrap();
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            client.write_all(&second).await.unwrap();
+            client
+        });
+
+        let head = read_request_head(&mut server).await.unwrap();
+        let _client = writer.await.unwrap();
+
+        assert_eq!(head, request.as_bytes());
+    }
+
+    #[tokio::test]
+    async fn truncates_head_beyond_limit() {
+        let (mut client, mut server) = socket_pair().await;
+        let request = format!("GET /metrics HTTP/1.1\r\nCookie: {}", "a".repeat(2 * MAX_REQUEST_HEAD_BYTES));
+
+        let writer = tokio::spawn(async move {
+            // The server stops reading at the limit, so the rest may never be accepted
+            let _ = client.write_all(request.as_bytes()).await;
+            client
+        });
+
+        let head = read_request_head(&mut server).await.unwrap();
+        drop(server);
+        let _client = writer.await.unwrap();
+
+        assert_eq!(head.len(), MAX_REQUEST_HEAD_BYTES);
+        assert!(head.starts_with(b"GET /metrics HTTP/1.1\r\n"));
+    }
+}
@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+
+/// Coarse classification of what a transaction does, for easy filtering.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TxType {
+    Swap,
+    Nft,
+    Stake,
+    Transfer,
+    Unknown,
+}
+
+impl TxType {
+    /// Database and display representation of the type.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TxType::Swap => "swap",
+            TxType::Nft => "nft",
+            TxType::Stake => "stake",
+            TxType::Transfer => "transfer",
+            TxType::Unknown => "unknown",
+        }
+    }
+}
+
+/// Known program ids and the category their invocation implies.
+/// 
+/// Add new programs here; anything not listed does not affect classification.
+const PROGRAM_CATEGORIES: &[(&str, TxType)] = &[
+    // DEXes and aggregators
+    ("JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4", TxType::Swap), // Jupiter v6
+    ("JUP4Fb2cqiRUcaTHdrPC8h2gNsA2ETXiPDD33WcGuJB", TxType::Swap), // Jupiter v4
+    ("675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8", TxType::Swap), // Raydium AMM v4
+    ("CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK", TxType::Swap), // Raydium CLMM
+    ("whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc", TxType::Swap), // Orca Whirlpools
+    ("9W959DqEETiGZocYWCQPaJ6sBmUzgfxXfqGeTEdp3aQP", TxType::Swap), // Orca v2
+    ("LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo", TxType::Swap), // Meteora DLMM
+    ("Eo7WjKq67rjJQSZxS6z3YkapzY3eMj6Xy8X5EQVn5UaB", TxType::Swap), // Meteora pools
+    ("PhoeNiXZ8ByJGLkxNfZRnkUfjvmuYqLR89jjFHGqdXY", TxType::Swap), // Phoenix
+    ("srmqPvymJeFKQ4zGQed1GFppgkRHL9kaELCbyksJtPX", TxType::Swap), // OpenBook
+    ("6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P", TxType::Swap), // Pump.fun
+    // NFT programs and marketplaces
+    ("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s", TxType::Nft), // Token Metadata
+    ("CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d", TxType::Nft), // Metaplex Core
+    ("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY", TxType::Nft), // Bubblegum
+    ("M2mx93ekt1fmXSVkTrUL9xVFHkmME8HTUi5Cyc5aF7K", TxType::Nft), // Magic Eden v2
+    ("TSWAPaqyCSx2KABk68Shruf4rp7CxcNi8hAsbdwmHbN", TxType::Nft), // Tensor Swap
+    // Staking
+    ("Stake11111111111111111111111111111111111111", TxType::Stake), // Stake program
+    ("SPoo1Ku8WFXoNDMHPsrGSTSG1Y47rzgn41SLUNakuHy", TxType::Stake), // SPL Stake Pool
+    ("MarBmsSgKXdrN1egZf5sqe1TMai9K1rChYNDJgjq7aD", TxType::Stake), // Marinade
+    // Plain transfers
+    ("11111111111111111111111111111111", TxType::Transfer), // System program
+    ("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA", TxType::Transfer), // SPL Token
+    ("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb", TxType::Transfer), // Token-2022
+    ("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL", TxType::Transfer), // Associated Token Account
+];
+
+/// Classify a transaction from the programs it invoked.
+/// 
+/// Swaps, NFT activity, and staking all move tokens through the System and Token
+/// programs too, so the most specific category wins: swap, then nft, then stake,
+/// then transfer. Transactions touching no known program are `Unknown`.
+pub fn classify(program_ids: &[String]) -> TxType {
+    const PRECEDENCE: [TxType; 4] = [TxType::Swap, TxType::Nft, TxType::Stake, TxType::Transfer];
+
+    let categories: Vec<TxType> = program_ids
+        .iter()
+        .filter_map(|id| {
+            PROGRAM_CATEGORIES
+                .iter()
+                .find(|(program_id, _)| program_id == id)
+                .map(|(_, category)| *category)
+        })
+        .collect();
+
+    PRECEDENCE
+        .into_iter()
+        .find(|category| categories.contains(category))
+        .unwrap_or(TxType::Unknown)
+}
@@ -1,2 +1,3 @@
+pub mod classification;
 pub mod models;
 pub mod parser;
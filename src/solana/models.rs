@@ -1,3 +1,4 @@
+use crate::solana::classification::TxType;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -22,16 +23,62 @@ pub struct ParsedTransaction {
     /// Base58-encoded public key of the account that paid the fee
     pub fee_payer: String,
     
+    /// Base58-encoded public keys of every account that signed, fee payer first
+    pub signers: Vec<String>,
+    
     /// Whether the transaction executed successfully
     pub success: bool,
     
     /// Compute units consumed by this transaction (may be None if not available)
     pub compute_units_consumed: Option<u64>,
     
+    /// Serialized wire size of the transaction in bytes (None if it cannot be determined)
+    pub transaction_size_bytes: Option<u32>,
+    
+    /// Whether the transaction uses a durable nonce (starts with AdvanceNonceAccount)
+    pub uses_durable_nonce: bool,
+    
+    /// Heap frame size in bytes requested with ComputeBudget RequestHeapFrame (None if not requested)
+    pub requested_heap_bytes: Option<u32>,
+    
+    /// Number of top-level instructions in the message
+    pub instruction_count: u32,
+    
+    /// Number of instructions invoked through CPI, from the inner instructions metadata
+    pub inner_instruction_count: u32,
+    
+    /// Whether the transaction was fetched at Confirmed after not being found at Finalized
+    pub confirmed_only: bool,
+    
+    /// Lamports of the base fee burned, at the configured burn rate (rounded down)
+    pub sol_burned: u64,
+    
+    /// Net SOL change of the fee payer in lamports, including the fee (0 if unchanged)
+    pub fee_payer_sol_delta: i64,
+    
+    /// Base58-encoded ids of programs invoked, top-level and via CPI, in first-seen order
+    pub program_ids: Vec<String>,
+    
+    /// Coarse classification derived from the invoked programs
+    pub tx_type: TxType,
+    
+    /// Data returned by the last program that called `set_return_data` (None if none did)
+    pub return_data: Option<ReturnData>,
+    
     /// Account balance changes that occurred during this transaction
     pub balance_changes: Vec<BalanceChange>,
 }
 
+/// Program return data recorded in a transaction's metadata.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReturnData {
+    /// Base58-encoded id of the program that set the return data
+    pub program_id: String,
+    
+    /// Returned bytes, base64-encoded
+    pub data: String,
+}
+
 /// Represents a change in an account's balance during a transaction.
 /// 
 /// This captures the pre and post-transaction balance for an account,
@@ -49,13 +96,45 @@ pub struct BalanceChange {
     
     /// Balance after the transaction
     pub post_balance: i64,
+    
+    /// Human-readable balance before the transaction as a decimal string (only with STORE_UI_AMOUNTS)
+    pub pre_ui_amount: Option<String>,
+    
+    /// Human-readable balance after the transaction as a decimal string (only with STORE_UI_AMOUNTS)
+    pub post_ui_amount: Option<String>,
+    
+    /// Base58-encoded id of the token program (SPL Token or Token-2022; None for native SOL
+    /// or when the node doesn't report it)
+    pub token_program: Option<String>,
+    
+    /// Whether this is a token balance of the native mint (wSOL), semantically SOL
+    pub is_wrapped_sol: bool,
 }
 
-impl BalanceChange {
-    /// Calculate the net change in balance (post - pre).
-    /// 
-    /// Positive values indicate an increase, negative values indicate a decrease.
-    pub fn delta(&self) -> i64 {
-        self.post_balance - self.pre_balance
-    }
+/// Block-level metadata from the gRPC blocks_meta subscription.
+/// 
+/// Used for burn accounting: the leader collects the block's fee reward, and the
+/// summed rewards show how much of the fees were paid out rather than burned.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockMeta {
+    /// Slot of the block
+    pub slot: u64,
+    
+    /// Base58-encoded blockhash
+    pub blockhash: String,
+    
+    /// Slot of the parent block
+    pub parent_slot: u64,
+    
+    /// Block height (may be None for older blocks)
+    pub block_height: Option<u64>,
+    
+    /// Unix timestamp of the block (may be None if not yet estimated)
+    pub block_time: Option<DateTime<Utc>>,
+    
+    /// Base58-encoded identity of the leader, taken from the fee reward recipient
+    pub leader: Option<String>,
+    
+    /// Sum of all rewards paid in this block, in lamports
+    pub rewards_lamports: i64,
 }
@@ -1,22 +1,57 @@
 use crate::error::AppError;
-use crate::solana::models::{BalanceChange, ParsedTransaction};
+use crate::solana::classification::classify;
+use crate::solana::models::{BalanceChange, ParsedTransaction, ReturnData};
 use chrono::{DateTime, Utc};
+use solana_transaction_status::option_serializer::OptionSerializer;
+use std::collections::{HashMap, HashSet};
 use solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta;
 use tracing::{debug, warn};
 
+/// Program id of SPL Token-2022 (token extensions).
+const TOKEN_2022_PROGRAM_ID: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
+
+/// Mint of wrapped SOL (the native mint); its token balances are lamports held as SPL tokens.
+const WRAPPED_SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+
+/// Base fee charged per transaction signature, in lamports.
+const LAMPORTS_PER_SIGNATURE: u64 = 5000;
+
+/// Decimal places of native SOL (1 SOL = 10^9 lamports).
+const SOL_DECIMALS: u32 = 9;
+
+/// Options controlling what `parse_transaction` extracts.
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    /// When set, only balance changes for these accounts are kept. Token balance
+    /// changes are kept when the token account is owned by one of these accounts.
+    pub balance_change_accounts: Option<HashSet<String>>,
+    /// Also record decimal-adjusted balances alongside the raw amounts.
+    pub ui_amounts: bool,
+    /// Token balance changes smaller than this many raw units of their mint are dropped.
+    /// Mints without an entry, and native SOL, are kept whatever the change.
+    pub min_token_change: HashMap<String, u64>,
+    /// When set, only token balance changes of these mints are kept.
+    pub mint_allowlist: Option<HashSet<String>>,
+    /// Drop native SOL balance changes (the fee payer's delta is still recorded on the transaction).
+    pub skip_sol_changes: bool,
+    /// Percentage of the base fee that is burned, 0-100 (0 records nothing burned).
+    pub burn_rate_percent: u8,
+}
+
 /// Parse a Solana transaction from the RPC response into our domain model.
 /// 
 /// This function extracts all relevant fields including fee, signature, block time,
 /// success status, and balance changes from the transaction returned by the RPC client.
 pub fn parse_transaction(
     encoded_tx: &EncodedConfirmedTransactionWithStatusMeta,
+    options: &ParseOptions,
 ) -> Result<ParsedTransaction, AppError> {
     let slot = encoded_tx.slot;
     
     // Extract block time and convert to DateTime
     let block_time = encoded_tx.block_time.map(|timestamp| {
         DateTime::from_timestamp(timestamp, 0)
-            .unwrap_or_else(|| DateTime::<Utc>::MIN_UTC)
+            .unwrap_or(DateTime::<Utc>::MIN_UTC)
     });
 
     // Extract transaction metadata
@@ -32,7 +67,7 @@ pub fn parse_transaction(
     let success = meta.err.is_none();
 
     // Extract compute units consumed if available
-    let compute_units_consumed: Option<u64> = meta.compute_units_consumed.clone().into();
+    let compute_units_consumed = opt_ser_into(&meta.compute_units_consumed);
 
     // Extract the transaction signature
     let transaction = &encoded_tx.transaction.transaction;
@@ -69,8 +104,50 @@ pub fn parse_transaction(
         }
     };
 
+    // Signers are the first accounts of the message; the fee payer is always one of them
+    let signers = signers(transaction);
+
+    // Determine the serialized transaction size for fee/efficiency analysis
+    let transaction_size_bytes = transaction_size_bytes(transaction);
+
+    // Durable-nonce transactions can land long after signing, unlike blockhash ones
+    let uses_durable_nonce = uses_durable_nonce(transaction);
+
+    // Larger heap frames are charged extra compute, so they matter for fee analysis
+    let requested_heap_bytes = requested_heap_bytes(transaction);
+
+    // Instruction counts, a rough measure of complexity; MEV bundles tend to run high
+    let instruction_count = instruction_count(transaction);
+    let inner_instruction_count = match &meta.inner_instructions {
+        OptionSerializer::Some(inner) => inner
+            .iter()
+            .map(|inner| inner.instructions.len() as u32)
+            .sum(),
+        _ => 0,
+    };
+
+    // Burned share of the base fee, so burn analytics are a column sum
+    let sol_burned = sol_burned(signers.len(), fee, options.burn_rate_percent);
+
+    // The fee payer is always account 0, so its native delta comes straight from the
+    // balance arrays; this matches its BalanceChange even when balance changes are filtered
+    let fee_payer_sol_delta = match (meta.pre_balances.first(), meta.post_balances.first()) {
+        (Some(pre), Some(post)) => *post as i64 - *pre as i64,
+        _ => 0,
+    };
+
+    // Programs invoked, used for program allow-listing
+    let program_ids = invoked_program_ids(transaction, meta);
+    let tx_type = classify(&program_ids);
+
+    // Program return values; the RPC always encodes the bytes as base64
+    let return_data = opt_ser_into(&meta.return_data).map(|return_data| ReturnData {
+        program_id: return_data.program_id,
+        data: return_data.data.0,
+    });
+
     // Extract balance changes
-    let balance_changes = extract_balance_changes(transaction, meta)?;
+    let balance_changes = extract_balance_changes(transaction, meta, options)?;
 
     debug!(
         signature = %signature,
@@ -86,25 +163,333 @@ pub fn parse_transaction(
         block_time,
         fee,
         fee_payer,
+        signers,
         success,
         compute_units_consumed,
+        transaction_size_bytes,
+        uses_durable_nonce,
+        requested_heap_bytes,
+        instruction_count,
+        inner_instruction_count,
+        // Set by the fetcher when it had to fall back from Finalized
+        confirmed_only: false,
+        sol_burned,
+        fee_payer_sol_delta,
+        program_ids,
+        tx_type,
+        return_data,
         balance_changes,
     })
 }
 
+/// Determine the serialized size of a transaction in bytes.
+/// 
+/// Binary encodings are decoded and measured directly. For JSON encoding with a raw
+/// message, the wire size is reconstructed from the signatures and message fields
+/// using Solana's compact-u16 length prefixes. Parsed and accounts-only encodings
+/// drop information needed for the calculation, so None is returned.
+fn transaction_size_bytes(
+    transaction: &solana_transaction_status::EncodedTransaction,
+) -> Option<u32> {
+    use base64::Engine;
+    use solana_transaction_status::{EncodedTransaction, TransactionBinaryEncoding, UiMessage};
+
+    let size = match transaction {
+        EncodedTransaction::LegacyBinary(data)
+        | EncodedTransaction::Binary(data, TransactionBinaryEncoding::Base58) => {
+            bs58::decode(data).into_vec().ok()?.len()
+        }
+        EncodedTransaction::Binary(data, TransactionBinaryEncoding::Base64) => {
+            base64::engine::general_purpose::STANDARD.decode(data).ok()?.len()
+        }
+        EncodedTransaction::Json(ui_tx) => {
+            let raw = match &ui_tx.message {
+                UiMessage::Raw(raw) => raw,
+                UiMessage::Parsed(_) => return None,
+            };
+
+            let mut size = compact_u16_len(ui_tx.signatures.len()) + ui_tx.signatures.len() * 64;
+
+            // Versioned (v0) messages carry a one-byte version prefix
+            if raw.address_table_lookups.is_some() {
+                size += 1;
+            }
+
+            // Header (3 bytes), account keys, and recent blockhash
+            size += 3;
+            size += compact_u16_len(raw.account_keys.len()) + raw.account_keys.len() * 32;
+            size += 32;
+
+            size += compact_u16_len(raw.instructions.len());
+            for instruction in &raw.instructions {
+                let data_len = bs58::decode(&instruction.data).into_vec().ok()?.len();
+                size += 1;
+                size += compact_u16_len(instruction.accounts.len()) + instruction.accounts.len();
+                size += compact_u16_len(data_len) + data_len;
+            }
+
+            if let Some(lookups) = &raw.address_table_lookups {
+                size += compact_u16_len(lookups.len());
+                for lookup in lookups {
+                    size += 32;
+                    size += compact_u16_len(lookup.writable_indexes.len()) + lookup.writable_indexes.len();
+                    size += compact_u16_len(lookup.readonly_indexes.len()) + lookup.readonly_indexes.len();
+                }
+            }
+
+            size
+        }
+        EncodedTransaction::Accounts(_) => return None,
+    };
+
+    u32::try_from(size).ok()
+}
+
+/// List the accounts that signed a transaction.
+/// 
+/// Parsed messages flag each signer; raw messages put the signers first, as many as
+/// the header's `num_required_signatures`.
+fn signers(transaction: &solana_transaction_status::EncodedTransaction) -> Vec<String> {
+    use solana_transaction_status::{EncodedTransaction, UiMessage};
+
+    let ui_tx = match transaction {
+        EncodedTransaction::Json(ui_tx) => ui_tx,
+        _ => return Vec::new(),
+    };
+
+    match &ui_tx.message {
+        UiMessage::Parsed(parsed) => parsed
+            .account_keys
+            .iter()
+            .filter(|key| key.signer)
+            .map(|key| key.pubkey.clone())
+            .collect(),
+        UiMessage::Raw(raw) => raw
+            .account_keys
+            .iter()
+            .take(raw.header.num_required_signatures as usize)
+            .cloned()
+            .collect(),
+    }
+}
+
+/// Compute the lamports burned from a transaction's base fee.
+/// 
+/// The base fee is `LAMPORTS_PER_SIGNATURE` per signature, capped at the fee actually
+/// charged; the rest of the fee is the prioritization fee, which is not burned. The
+/// burned share is computed in integer lamports and rounded down, so a fractional
+/// lamport (only possible with an odd base fee) is never counted as burned.
+fn sol_burned(signature_count: usize, fee: u64, burn_rate_percent: u8) -> u64 {
+    let base_fee = (signature_count as u64)
+        .saturating_mul(LAMPORTS_PER_SIGNATURE)
+        .min(fee);
+
+    base_fee * u64::from(burn_rate_percent) / 100
+}
+
+/// Check whether a transaction uses a durable nonce.
+/// 
+/// The runtime treats a transaction as durable-nonce when its first instruction is
+/// the System Program's AdvanceNonceAccount, so only that position is checked.
+fn uses_durable_nonce(transaction: &solana_transaction_status::EncodedTransaction) -> bool {
+    use solana_transaction_status::{EncodedTransaction, UiInstruction, UiMessage, UiParsedInstruction};
+
+    const SYSTEM_PROGRAM_ID: &str = "11111111111111111111111111111111";
+    // Bincode-encoded SystemInstruction::AdvanceNonceAccount discriminant (u32 LE)
+    const ADVANCE_NONCE_ACCOUNT: [u8; 4] = [4, 0, 0, 0];
+
+    let ui_tx = match transaction {
+        EncodedTransaction::Json(ui_tx) => ui_tx,
+        _ => return false,
+    };
+
+    match &ui_tx.message {
+        UiMessage::Raw(raw) => raw.instructions.first().is_some_and(|instruction| {
+            let is_system = raw
+                .account_keys
+                .get(instruction.program_id_index as usize)
+                .is_some_and(|program_id| program_id == SYSTEM_PROGRAM_ID);
+
+            is_system
+                && bs58::decode(&instruction.data)
+                    .into_vec()
+                    .is_ok_and(|data| data.starts_with(&ADVANCE_NONCE_ACCOUNT))
+        }),
+        UiMessage::Parsed(parsed) => match parsed.instructions.first() {
+            Some(UiInstruction::Parsed(UiParsedInstruction::Parsed(instruction))) => {
+                instruction.program_id == SYSTEM_PROGRAM_ID
+                    && instruction.parsed.get("type").and_then(|t| t.as_str()) == Some("advanceNonce")
+            }
+            _ => false,
+        },
+    }
+}
+
+/// Count the top-level instructions of a transaction's message.
+fn instruction_count(transaction: &solana_transaction_status::EncodedTransaction) -> u32 {
+    use solana_transaction_status::{EncodedTransaction, UiMessage};
+
+    let count = match transaction {
+        EncodedTransaction::Json(ui_tx) => match &ui_tx.message {
+            UiMessage::Raw(raw) => raw.instructions.len(),
+            UiMessage::Parsed(parsed) => parsed.instructions.len(),
+        },
+        _ => 0,
+    };
+
+    count as u32
+}
+
+/// Find the heap frame size requested by a ComputeBudget RequestHeapFrame instruction.
+/// 
+/// Compute budget instructions only take effect at the top level, so inner
+/// instructions are not searched. The RPC does not parse this program, so its
+/// instructions carry the raw bincode data in either message form.
+fn requested_heap_bytes(transaction: &solana_transaction_status::EncodedTransaction) -> Option<u32> {
+    use solana_transaction_status::{EncodedTransaction, UiInstruction, UiMessage, UiParsedInstruction};
+
+    const COMPUTE_BUDGET_PROGRAM_ID: &str = "ComputeBudget111111111111111111111111111111";
+    // Borsh-encoded ComputeBudgetInstruction::RequestHeapFrame discriminant, followed by a u32 LE size
+    const REQUEST_HEAP_FRAME: u8 = 1;
+
+    let ui_tx = match transaction {
+        EncodedTransaction::Json(ui_tx) => ui_tx,
+        _ => return None,
+    };
+
+    let compute_budget_data: Vec<&str> = match &ui_tx.message {
+        UiMessage::Raw(raw) => raw
+            .instructions
+            .iter()
+            .filter(|instruction| {
+                raw.account_keys
+                    .get(instruction.program_id_index as usize)
+                    .is_some_and(|program_id| program_id == COMPUTE_BUDGET_PROGRAM_ID)
+            })
+            .map(|instruction| instruction.data.as_str())
+            .collect(),
+        UiMessage::Parsed(parsed) => parsed
+            .instructions
+            .iter()
+            .filter_map(|instruction| match instruction {
+                UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(decoded))
+                    if decoded.program_id == COMPUTE_BUDGET_PROGRAM_ID =>
+                {
+                    Some(decoded.data.as_str())
+                }
+                _ => None,
+            })
+            .collect(),
+    };
+
+    compute_budget_data.into_iter().find_map(|data| {
+        let data = bs58::decode(data).into_vec().ok()?;
+        match data.as_slice() {
+            [REQUEST_HEAP_FRAME, size @ ..] if size.len() >= 4 => {
+                Some(u32::from_le_bytes([size[0], size[1], size[2], size[3]]))
+            }
+            _ => None,
+        }
+    })
+}
+
+/// Collect the ids of every program a transaction invoked, without duplicates.
+/// 
+/// Top-level instructions come first, followed by programs reached through CPI as
+/// recorded in the inner instructions. Inner instruction indexes refer to the full
+/// account list, so addresses loaded from lookup tables are appended to the static keys.
+fn invoked_program_ids(
+    transaction: &solana_transaction_status::EncodedTransaction,
+    meta: &solana_transaction_status::UiTransactionStatusMeta,
+) -> Vec<String> {
+    use solana_transaction_status::{EncodedTransaction, UiInstruction, UiMessage, UiParsedInstruction};
+
+    let ui_tx = match transaction {
+        EncodedTransaction::Json(ui_tx) => ui_tx,
+        _ => return Vec::new(),
+    };
+
+    let mut account_keys: Vec<String> = match &ui_tx.message {
+        UiMessage::Parsed(parsed) => parsed.account_keys.iter().map(|k| k.pubkey.clone()).collect(),
+        UiMessage::Raw(raw) => raw.account_keys.clone(),
+    };
+    if let Some(loaded) = opt_ser_into(&meta.loaded_addresses) {
+        account_keys.extend(loaded.writable);
+        account_keys.extend(loaded.readonly);
+    }
+
+    let program_id = |instruction: &UiInstruction| -> Option<String> {
+        match instruction {
+            UiInstruction::Compiled(compiled) => {
+                account_keys.get(compiled.program_id_index as usize).cloned()
+            }
+            UiInstruction::Parsed(UiParsedInstruction::Parsed(parsed)) => {
+                Some(parsed.program_id.clone())
+            }
+            UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(decoded)) => {
+                Some(decoded.program_id.clone())
+            }
+        }
+    };
+
+    let mut program_ids: Vec<String> = Vec::new();
+    let mut push = |id: Option<String>| {
+        if let Some(id) = id {
+            if !program_ids.contains(&id) {
+                program_ids.push(id);
+            }
+        }
+    };
+
+    match &ui_tx.message {
+        UiMessage::Raw(raw) => {
+            for instruction in &raw.instructions {
+                push(account_keys.get(instruction.program_id_index as usize).cloned());
+            }
+        }
+        UiMessage::Parsed(parsed) => {
+            for instruction in &parsed.instructions {
+                push(program_id(instruction));
+            }
+        }
+    }
+
+    for inner in opt_ser_into(&meta.inner_instructions).unwrap_or_default() {
+        for instruction in &inner.instructions {
+            push(program_id(instruction));
+        }
+    }
+
+    program_ids
+}
+
+/// Number of bytes Solana's compact-u16 encoding uses for a length prefix.
+fn compact_u16_len(len: usize) -> usize {
+    match len {
+        0..=0x7f => 1,
+        0x80..=0x3fff => 2,
+        _ => 3,
+    }
+}
+
 /// Extract balance changes from transaction metadata.
 /// 
 /// This compares pre_balances and post_balances arrays to calculate the net change
 /// for each account involved in the transaction. SPL token balance changes are
 /// also extracted from pre_token_balances and post_token_balances if available.
+/// 
+/// When `options.balance_change_accounts` is set, changes for any other account are
+/// dropped, which keeps row volume down for high-fanout transactions. Token changes
+/// below their mint's `options.min_token_change` threshold are dropped as dust.
 fn extract_balance_changes(
     transaction: &solana_transaction_status::EncodedTransaction,
     meta: &solana_transaction_status::UiTransactionStatusMeta,
+    options: &ParseOptions,
 ) -> Result<Vec<BalanceChange>, AppError> {
     let mut balance_changes = Vec::new();
 
     // Extract account keys based on message type
-    let account_keys = match transaction {
+    let mut account_keys: Vec<String> = match transaction {
         solana_transaction_status::EncodedTransaction::Json(ui_tx) => {
             match &ui_tx.message {
                 solana_transaction_status::UiMessage::Parsed(parsed) => {
@@ -120,41 +505,57 @@ fn extract_balance_changes(
             return Ok(balance_changes);
         }
     };
+    // Balance arrays also cover addresses loaded from lookup tables, which follow the static keys
+    if let Some(loaded) = opt_ser_into(&meta.loaded_addresses) {
+        account_keys.extend(loaded.writable);
+        account_keys.extend(loaded.readonly);
+    }
 
     // Process native SOL balance changes
-    for (index, (pre_balance, post_balance)) in meta
-        .pre_balances
-        .iter()
-        .zip(meta.post_balances.iter())
-        .enumerate()
-    {
-        // Only record if there was a change
-        if pre_balance != post_balance {
-            let account_address = account_keys
-                .get(index)
-                .cloned()
-                .unwrap_or_else(|| format!("unknown_{}", index));
-
-            balance_changes.push(BalanceChange {
-                account_address,
-                mint_address: None, // None indicates native SOL
-                pre_balance: *pre_balance as i64,
-                post_balance: *post_balance as i64,
-            });
+    if !options.skip_sol_changes {
+        for (index, (pre_balance, post_balance)) in meta
+            .pre_balances
+            .iter()
+            .zip(meta.post_balances.iter())
+            .enumerate()
+        {
+            // Only record if there was a change
+            if pre_balance != post_balance {
+                let account_address = account_keys
+                    .get(index)
+                    .cloned()
+                    .unwrap_or_else(|| format!("unknown_{}", index));
+
+                if let Some(accounts) = &options.balance_change_accounts {
+                    if !accounts.contains(&account_address) {
+                        continue;
+                    }
+                }
+
+                balance_changes.push(BalanceChange {
+                    account_address,
+                    mint_address: None, // None indicates native SOL
+                    pre_balance: *pre_balance as i64,
+                    post_balance: *post_balance as i64,
+                    pre_ui_amount: options
+                        .ui_amounts
+                        .then(|| format_ui_amount(*pre_balance, SOL_DECIMALS)),
+                    post_ui_amount: options
+                        .ui_amounts
+                        .then(|| format_ui_amount(*post_balance, SOL_DECIMALS)),
+                    token_program: None,
+                    is_wrapped_sol: false,
+                });
+            }
         }
     }
 
     // Process SPL token balance changes if available
-    // Note: In Solana 1.18, these fields use OptionSerializer which implements Into<Option>
-    use solana_transaction_status::UiTransactionTokenBalance;
-    let pre_token_opt: Option<Vec<UiTransactionTokenBalance>> = meta.pre_token_balances.clone().into();
-    let post_token_opt: Option<Vec<UiTransactionTokenBalance>> = meta.post_token_balances.clone().into();
-    
-    if let (Some(ref pre_token_balances), Some(ref post_token_balances)) = (
-        pre_token_opt,
-        post_token_opt,
+    if let (Some(pre_token_balances), Some(post_token_balances)) = (
+        opt_ser_into(&meta.pre_token_balances),
+        opt_ser_into(&meta.post_token_balances),
     ) {
-        for pre_token in pre_token_balances {
+        for pre_token in &pre_token_balances {
             // Find matching post token balance by account index
             if let Some(post_token) = post_token_balances
                 .iter()
@@ -176,6 +577,37 @@ fn extract_balance_changes(
                     .parse::<i64>()
                     .unwrap_or(0);
 
+                // Keep token changes for monitored token accounts or those they own
+                if let Some(accounts) = &options.balance_change_accounts {
+                    let owner: Option<String> = opt_ser_into(&pre_token.owner);
+                    let monitored = accounts.contains(&account_address)
+                        || owner.is_some_and(|owner| accounts.contains(&owner));
+                    if !monitored {
+                        continue;
+                    }
+                }
+
+                // Distinguishes SPL Token from Token-2022 accounts, whose amounts can net out
+                // transfer fees withheld on the destination account
+                let token_program: Option<String> = opt_ser_into(&pre_token.program_id)
+                    .or_else(|| opt_ser_into(&post_token.program_id));
+                if token_program.as_deref() == Some(TOKEN_2022_PROGRAM_ID) {
+                    debug!(account = %account_address, mint = %pre_token.mint, "Token-2022 balance change");
+                }
+
+                if let Some(mints) = &options.mint_allowlist {
+                    if !mints.contains(&pre_token.mint) {
+                        continue;
+                    }
+                }
+
+                // Thresholds are per mint since token decimals vary too widely for one cutoff
+                if let Some(min) = options.min_token_change.get(&pre_token.mint) {
+                    if pre_amount.abs_diff(post_amount) < *min {
+                        continue;
+                    }
+                }
+
                 // Only record if there was a change
                 if pre_amount != post_amount {
                     balance_changes.push(BalanceChange {
@@ -183,6 +615,14 @@ fn extract_balance_changes(
                         mint_address: Some(pre_token.mint.clone()),
                         pre_balance: pre_amount,
                         post_balance: post_amount,
+                        pre_ui_amount: options
+                            .ui_amounts
+                            .then(|| pre_token.ui_token_amount.ui_amount_string.clone()),
+                        post_ui_amount: options
+                            .ui_amounts
+                            .then(|| post_token.ui_token_amount.ui_amount_string.clone()),
+                        token_program,
+                        is_wrapped_sol: pre_token.mint == WRAPPED_SOL_MINT,
                     });
                 }
             }
@@ -191,3 +631,279 @@ fn extract_balance_changes(
 
     Ok(balance_changes)
 }
+
+/// Format a raw amount in the smallest unit as an exact decimal string.
+/// 
+/// Uses integer arithmetic rather than `f64` so large balances keep every digit,
+/// e.g. 1_500_000_000 lamports with 9 decimals becomes "1.5".
+fn format_ui_amount(amount: u64, decimals: u32) -> String {
+    let divisor = 10u64.pow(decimals);
+    let whole = amount / divisor;
+    let fraction = amount % divisor;
+
+    if fraction == 0 {
+        return whole.to_string();
+    }
+
+    let fraction = format!("{:0width$}", fraction, width = decimals as usize);
+    format!("{}.{}", whole, fraction.trim_end_matches('0'))
+}
+
+/// Convert an `OptionSerializer` field from transaction metadata into an `Option`.
+/// 
+/// Solana 1.18 wraps optional metadata fields (compute units, token balances, log
+/// messages, loaded addresses, return data, ...) in `OptionSerializer`, which only
+/// converts by value. Routing every conversion through this helper keeps the clone
+/// in one place so new fields can't accidentally move out of the borrowed metadata.
+/// Both `None` and `Skip` map to `None`.
+fn opt_ser_into<T: Clone>(value: &OptionSerializer<T>) -> Option<T> {
+    value.clone().into()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::solana::classification::TxType;
+
+    pub(crate) const PAYER: &str = "AWxggjuZRmWULwxwPeM6ZZxRtdDdekVq22mFRx2QbW7U";
+    pub(crate) const RECIPIENT: &str = "7tark5iZaRrMfGKtKy1aqpGuRgoxbE6ec7Z5Qa4Jc5xr";
+    const SYSTEM_PROGRAM_ID: &str = "11111111111111111111111111111111";
+    const TOKEN_PROGRAM_ID: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+    const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+    /// Read a `getTransaction` response from `tests/fixtures/<name>.json`.
+    pub(crate) fn fixture_json(name: &str) -> serde_json::Value {
+        let path = format!("{}/tests/fixtures/{}.json", env!("CARGO_MANIFEST_DIR"), name);
+        let json = std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("read {}: {}", path, e));
+        serde_json::from_str(&json).unwrap_or_else(|e| panic!("parse {}: {}", path, e))
+    }
+
+    pub(crate) fn load_fixture(name: &str) -> EncodedConfirmedTransactionWithStatusMeta {
+        serde_json::from_value(fixture_json(name)).expect("fixture is a getTransaction response")
+    }
+
+    pub(crate) fn parse_fixture(name: &str) -> ParsedTransaction {
+        parse_transaction(&load_fixture(name), &ParseOptions::default()).expect("fixture parses")
+    }
+
+    fn change<'a>(tx: &'a ParsedTransaction, account: &str, mint: Option<&str>) -> &'a BalanceChange {
+        tx.balance_changes
+            .iter()
+            .find(|c| c.account_address == account && c.mint_address.as_deref() == mint)
+            .unwrap_or_else(|| panic!("no balance change for {} ({:?})", account, mint))
+    }
+
+    #[test]
+    fn parses_sol_transfer() {
+        let tx = parse_fixture("sol_transfer");
+
+        assert_eq!(tx.signature, "5dvLf2MYJmxLUkqR13XQU2DtPG9Rhj2WMce2FuM7ZbGxUfCjAaxqiM1m8ceikKCiZZaaF58C33mYjJ7SrxexhVAx");
+        assert_eq!(tx.slot, 250_000_000);
+        assert_eq!(tx.block_time.map(|t| t.timestamp()), Some(1_700_000_000));
+        assert_eq!(tx.fee, 5000);
+        assert!(tx.success);
+        assert_eq!(tx.fee_payer, PAYER);
+        assert_eq!(tx.signers, vec![PAYER.to_string()]);
+        assert_eq!(tx.program_ids, vec![SYSTEM_PROGRAM_ID.to_string()]);
+        assert_eq!(tx.compute_units_consumed, Some(450));
+        // The wire size of a single-signature system transfer
+        assert_eq!(tx.transaction_size_bytes, Some(215));
+
+        assert_eq!(tx.balance_changes.len(), 2);
+        let payer = change(&tx, PAYER, None);
+        assert_eq!((payer.pre_balance, payer.post_balance), (10_000_000_000, 9_998_995_000));
+        let recipient = change(&tx, RECIPIENT, None);
+        assert_eq!((recipient.pre_balance, recipient.post_balance), (1_000_000_000, 1_001_000_000));
+    }
+
+    #[test]
+    fn parses_token_transfer() {
+        let tx = parse_fixture("token_transfer");
+
+        assert_eq!(tx.program_ids, vec![TOKEN_PROGRAM_ID.to_string()]);
+
+        let usdc: Vec<&BalanceChange> = tx
+            .balance_changes
+            .iter()
+            .filter(|c| c.mint_address.as_deref() == Some(USDC_MINT))
+            .collect();
+        assert_eq!(usdc.len(), 2);
+        let source = change(&tx, "AXmf731SKh1DqQfPVBL7TGvrJ3X585TWDXLR8GjZV6MF", Some(USDC_MINT));
+        assert_eq!((source.pre_balance, source.post_balance), (100_000_000, 75_000_000));
+        let destination = change(&tx, "HEBZyWFLZyjX2rQVuRKjZxh2ynVVUPPecfHzmNc7Uf83", Some(USDC_MINT));
+        assert_eq!((destination.pre_balance, destination.post_balance), (0, 25_000_000));
+        assert_eq!(destination.token_program.as_deref(), Some(TOKEN_PROGRAM_ID));
+        assert!(!destination.is_wrapped_sol);
+
+        // The owner only paid the fee in SOL
+        let payer = change(&tx, PAYER, None);
+        assert_eq!(payer.post_balance - payer.pre_balance, -5000);
+    }
+
+    #[test]
+    fn parses_swap_routed_through_dexes() {
+        let user_usdc = "5c3op56o2ibJs12xLHbDHMKuB8AmRdqFLzw83qABuC2U";
+        let user_usdt = "2Z8TMEQU5irfGFBeP3iqSrT4EAp2FNdZmiUZNsDv177V";
+        let user_wsol = "5UiN5x3Hrw9XTGcKmsmcSVhSY5vPB6p377jJCPwZVrJn";
+        let jupiter = "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4";
+        let raydium = "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8";
+        let whirlpool = "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc";
+
+        let tx = parse_fixture("swap");
+
+        // The DEXes are only reached through Jupiter's inner instructions
+        assert_eq!(
+            tx.program_ids,
+            vec![jupiter.to_string(), raydium.to_string(), TOKEN_PROGRAM_ID.to_string(), whirlpool.to_string()]
+        );
+        assert_eq!(tx.tx_type, TxType::Swap);
+        assert_eq!((tx.instruction_count, tx.inner_instruction_count), (1, 6));
+
+        let mut mints: Vec<&str> = tx.balance_changes.iter().filter_map(|c| c.mint_address.as_deref()).collect();
+        mints.sort();
+        mints.dedup();
+        assert_eq!(mints, vec![USDC_MINT, "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCE8BenwNYB", WRAPPED_SOL_MINT]);
+
+        let usdc = change(&tx, user_usdc, Some(USDC_MINT));
+        assert_eq!(usdc.post_balance - usdc.pre_balance, -10_000_000);
+        let wsol = change(&tx, user_wsol, Some(WRAPPED_SOL_MINT));
+        assert_eq!((wsol.pre_balance, wsol.post_balance), (0, 65_000_000));
+        assert!(wsol.is_wrapped_sol);
+        // The intermediate USDT passed through the user's account without changing it
+        assert!(!tx.balance_changes.iter().any(|c| c.account_address == user_usdt));
+
+        // Only the fee left the payer as native SOL
+        assert_eq!(tx.fee_payer_sol_delta, -5000);
+    }
+
+    #[test]
+    fn parses_failed_transaction() {
+        let tx = parse_fixture("failed");
+
+        assert!(!tx.success);
+        assert_eq!(tx.fee, 5000);
+        // Only the fee moved; the failed transfer left the recipient untouched
+        assert_eq!(tx.balance_changes.len(), 1);
+        let payer = change(&tx, PAYER, None);
+        assert_eq!(payer.post_balance - payer.pre_balance, -5000);
+    }
+
+    #[test]
+    fn parses_v0_transaction_with_lookup_tables() {
+        let loaded_writable = "AHHN3ZCjfq6gCvknZyWKr5TG9cScbkFRzDei64r3jJoV";
+        let loaded_program = "EXiJsARuhbjnD7FzH69rLZELAN9BKbsHGiuaTggAGJtF";
+
+        let tx = parse_fixture("v0_lookup_tables");
+
+        // Program ids resolve through the addresses loaded from the lookup table
+        assert_eq!(tx.program_ids, vec![loaded_program.to_string(), SYSTEM_PROGRAM_ID.to_string()]);
+        // Versioned messages add the version byte and the lookup table section
+        assert_eq!(tx.transaction_size_bytes, Some(227));
+
+        assert_eq!(tx.balance_changes.len(), 2);
+        let loaded = change(&tx, loaded_writable, None);
+        assert_eq!((loaded.pre_balance, loaded.post_balance), (0, 2_000_000));
+    }
+
+    #[test]
+    fn detects_durable_nonce_from_first_instruction() {
+        assert!(parse_fixture("durable_nonce").uses_durable_nonce);
+        assert!(!parse_fixture("sol_transfer").uses_durable_nonce);
+    }
+
+    #[test]
+    fn fee_payer_sol_delta_includes_fee() {
+        let tx = parse_fixture("sol_transfer");
+        let payer = change(&tx, PAYER, None);
+
+        // The 0.001 SOL sent plus the 5000 lamport fee
+        assert_eq!(tx.fee_payer_sol_delta, payer.post_balance - payer.pre_balance);
+        assert_eq!(tx.fee_payer_sol_delta, -1_000_000 - tx.fee as i64);
+    }
+
+    #[test]
+    fn flags_wrapped_sol_balance_changes() {
+        let tx = parse_fixture("token_transfer");
+        let wsol_destination = "AykDbrRGxg8b69NQzfqDEPsEcdduAEo8ZmHSqpE2Apdi";
+
+        let wsol = change(&tx, wsol_destination, Some(WRAPPED_SOL_MINT));
+        assert!(wsol.is_wrapped_sol);
+        assert_eq!((wsol.pre_balance, wsol.post_balance), (0, 500_000_000));
+
+        // The lamports backing it are a separate native change, which is not wrapped SOL
+        assert!(!change(&tx, wsol_destination, None).is_wrapped_sol);
+        assert!(tx
+            .balance_changes
+            .iter()
+            .filter(|c| c.mint_address.as_deref() == Some(USDC_MINT))
+            .all(|c| !c.is_wrapped_sol));
+    }
+
+    #[test]
+    fn reads_requested_heap_frame() {
+        assert_eq!(parse_fixture("compute_budget").requested_heap_bytes, Some(256 * 1024));
+        assert_eq!(parse_fixture("sol_transfer").requested_heap_bytes, None);
+    }
+
+    #[test]
+    fn counts_top_level_and_inner_instructions() {
+        let mut json = fixture_json("compute_budget");
+        json["meta"]["innerInstructions"] = serde_json::json!([{
+            "index": 2,
+            "instructions": [
+                { "programIdIndex": 2, "accounts": [0, 1], "data": "3Bxs4Bc3VYuGVB19", "stackHeight": 2 },
+                { "programIdIndex": 2, "accounts": [0, 1], "data": "3Bxs4Bc3VYuGVB19", "stackHeight": 2 }
+            ]
+        }]);
+        let encoded = serde_json::from_value(json).expect("fixture is a getTransaction response");
+
+        let tx = parse_transaction(&encoded, &ParseOptions::default()).expect("fixture parses");
+
+        assert_eq!(tx.instruction_count, 3);
+        assert_eq!(tx.inner_instruction_count, 2);
+    }
+
+    #[test]
+    fn burns_share_of_base_fee_only() {
+        let options = ParseOptions { burn_rate_percent: 50, ..Default::default() };
+        let tx = parse_transaction(&load_fixture("sol_transfer"), &options).expect("fixture parses");
+        assert_eq!(tx.sol_burned, 2500);
+
+        // The prioritization fee above the base fee is not burned
+        assert_eq!(sol_burned(1, 15_000, 50), 2500);
+        assert_eq!(sol_burned(2, 10_000, 100), 10_000);
+        // The base fee is capped at the fee actually charged
+        assert_eq!(sol_burned(2, 5000, 50), 2500);
+        assert_eq!(sol_burned(1, 5001, 0), 0);
+    }
+
+    #[test]
+    fn drops_token_changes_of_mints_not_allowed() {
+        let options = ParseOptions {
+            mint_allowlist: Some(HashSet::from([USDC_MINT.to_string()])),
+            ..Default::default()
+        };
+
+        let tx = parse_transaction(&load_fixture("token_transfer"), &options).expect("fixture parses");
+
+        let mints: HashSet<&str> = tx
+            .balance_changes
+            .iter()
+            .filter_map(|c| c.mint_address.as_deref())
+            .collect();
+        assert_eq!(mints, HashSet::from([USDC_MINT]));
+        // Native SOL changes are not subject to the allowlist
+        assert!(tx.balance_changes.iter().any(|c| c.mint_address.is_none()));
+    }
+
+    #[test]
+    fn formats_ui_amounts_exactly() {
+        assert_eq!(format_ui_amount(1_500_000_000, 9), "1.5");
+        assert_eq!(format_ui_amount(2_000_000_000, 9), "2");
+        assert_eq!(format_ui_amount(1, 9), "0.000000001");
+        assert_eq!(format_ui_amount(0, 6), "0");
+        assert_eq!(format_ui_amount(42, 0), "42");
+        // Beyond f64's 53 bits of precision
+        assert_eq!(format_ui_amount(u64::MAX, 9), "18446744073.709551615");
+    }
+}
@@ -1,16 +1,34 @@
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer};
 
 /// Initialize the tracing subscriber for structured logging.
 /// 
 /// This sets up a subscriber with the specified log level and environment-based
 /// filtering. The subscriber outputs structured logs to stdout, which is suitable
 /// for both development and production deployment (where logs can be aggregated).
-pub fn init_telemetry(log_level: &str) {
+/// 
+/// With `tokio_console` set and the `tokio-console` feature compiled in, a
+/// console-subscriber layer is also installed so `tokio-console` can attach and
+/// show live task states. The log filter applies to stdout only, since the console
+/// needs the runtime's trace-level task events.
+pub fn init_telemetry(log_level: &str, tokio_console: bool) {
     let env_filter = EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| EnvFilter::new(log_level));
 
+    #[cfg(feature = "tokio-console")]
+    let console_layer = tokio_console.then(|| {
+        console_subscriber::ConsoleLayer::builder()
+            .with_default_env()
+            .spawn()
+    });
+    #[cfg(not(feature = "tokio-console"))]
+    let console_layer: Option<tracing_subscriber::layer::Identity> = None;
+
     tracing_subscriber::registry()
-        .with(env_filter)
-        .with(tracing_subscriber::fmt::layer())
+        .with(console_layer)
+        .with(tracing_subscriber::fmt::layer().with_filter(env_filter))
         .init();
+
+    if tokio_console && !cfg!(feature = "tokio-console") {
+        tracing::warn!("TOKIO_CONSOLE is set but this binary was built without the tokio-console feature");
+    }
 }